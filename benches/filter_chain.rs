@@ -0,0 +1,54 @@
+//! Filter chain latency benchmark.
+//!
+//! The command is parsed once per hook call, so latency should stay roughly
+//! flat as custom filters are added to the chain.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use claw_hooks::config::{Config, CustomFilter};
+use claw_hooks::domain::{BashInput, FilterChain, HookInput, ToolInput};
+
+/// A typical agent command: a compound statement with a pipeline and a nested shell.
+const COMMAND: &str =
+    "cd src && cargo build --release 2>&1 | tail -n 20; bash -c 'git status --short' > status.txt";
+
+/// Build a configuration with the built-in filters and `count` custom filters.
+fn config_with_custom_filters(count: usize) -> Config {
+    Config {
+        custom_filters: (0..count)
+            .map(|i| CustomFilter {
+                command: format!("^tool{}$", i),
+                args: Vec::new(),
+                message: format!("tool{} is blocked", i),
+            })
+            .collect(),
+        ..Config::default()
+    }
+}
+
+fn bench_filter_chain(c: &mut Criterion) {
+    let input = HookInput {
+        event: "PreToolUse".to_string(),
+        tool_name: "Bash".to_string(),
+        tool_input: ToolInput::Bash(BashInput {
+            command: COMMAND.to_string(),
+            timeout: None,
+        }),
+        session_id: None,
+        cwd: None,
+    };
+
+    let mut group = c.benchmark_group("filter_chain");
+    for count in [0, 10, 30, 60] {
+        let chain = FilterChain::new(&config_with_custom_filters(count));
+        group.bench_with_input(
+            BenchmarkId::new("custom_filters", count),
+            &chain,
+            |b, chain| b.iter(|| chain.execute(black_box(&input))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_filter_chain);
+criterion_main!(benches);
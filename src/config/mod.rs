@@ -6,10 +6,10 @@ mod service;
 mod types;
 mod validation;
 
-pub use types::Config;
+pub use types::{Config, CustomFilter};
 
 // Re-export for use in other modules
 pub use service::ConfigService;
 #[allow(unused_imports)]
-pub(crate) use types::{CustomFilter, StopHook};
+pub(crate) use types::StopHook;
 pub use validation::validate;
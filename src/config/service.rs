@@ -84,10 +84,16 @@ impl ConfigService {
 # https://github.com/owayo/claw-hooks
 
 # Enable blocking of rm/rmdir/del/erase commands (default: true)
+# Also blocks equivalent deletions: find -delete, find -exec rm, unlink, shred,
+# truncate -s 0, rsync --delete, Remove-Item, and deletion APIs in
+# inline interpreter code (python -c "shutil.rmtree(...)", node -e "fs.rmSync(...)")
 rm_block = true
 # Custom message for rm blocking (recommended: use with safe-rm)
 # safe-rm: https://github.com/owayo/safe-rm
 rm_block_message = "🚫 Use safe-rm instead: safe-rm <file> (validates Git status and path containment). Only clean/ignored files in project allowed."
+# Allow rm/rmdir of untracked or git-ignored paths inside the project root (default: false)
+# Git-tracked files, paths outside the project and unresolvable operands stay blocked
+rm_allow_untracked = false
 
 # Enable blocking of kill/pkill/killall/taskkill commands (default: true)
 kill_block = true
@@ -95,10 +101,167 @@ kill_block = true
 # safe-kill: https://github.com/owayo/safe-kill
 kill_block_message = "🚫 Use safe-kill instead: safe-kill <PID>, safe-kill -N <name> (pkill-style), or safe-kill -p <port>. Use -s <signal> for signal."
 
-# Enable blocking of dd command (default: true)
-dd_block = true
-# Custom message for dd blocking
-dd_block_message = "🚫 dd command blocked for safety."
+# Enable blocking of downloaded code run by a shell or interpreter (default: true)
+# e.g. `curl x | bash`, `bash <(curl x)`, `sh -c "$(wget -O- x)"`, `python <(curl x)`.
+# Downloads saved to a file or piped into other commands (`curl x | jq`) are allowed
+remote_script_block = true
+# Custom message for remote script blocking
+# remote_script_block_message = "🚫 Download the script and show it to the user first."
+
+# Enable detection of shells running decoded text (default: true)
+# e.g. `echo cm0gLXJmIC4= | base64 -d | sh`, `xxd -r -p x.hex | bash`, `eval "$(... | base64 -d)"`.
+# ANSI-C quoted names (`$'\x72\x6d'`), brace tricks (`r{m,}`), printf escapes and literal
+# base64/hex payloads are decoded and checked by the other filters; commands they block
+# are blocked with an "Obfuscation detected" reason
+obfuscation_block = true
+# Action for shells running decoded text: "block", "ask" (ask the user to confirm) or "allow"
+obfuscation_action = "ask"
+# Custom message for obfuscation detection
+# obfuscation_block_message = "🚫 Run the decoded command directly."
+
+# Enable blocking of disk and filesystem commands (default: true)
+# Blocks mkfs/mkfs.*, fdisk, parted, wipefs, shred /dev/*, blkdiscard, mount/umount and
+# diskutil erase verbs. `dd` is allowed only when of= is a regular file inside the project;
+# of=/dev/*, outputs outside the project and dd without of= are blocked.
+# (dd_block and dd_block_message are accepted as older names)
+disk_block = true
+# Custom message for disk command blocking
+disk_block_message = "🚫 Disk and filesystem commands blocked for safety."
+
+# Enable blocking of commands whose name is only known at runtime (default: true)
+# e.g. `$CMD -rf dir`, `$(cat cmd.txt) x`, or `source` of a file that does not exist yet.
+# Variables and aliases defined earlier in the same command (`X=rm; $X`, `alias d=rm; d`),
+# static substitutions (`$(which rm)`), eval strings and sourced files are resolved
+# and checked by the other filters
+dynamic_command_block = true
+# Custom message for dynamic command blocking
+# dynamic_command_block_message = "🚫 Run the command by its name instead."
+
+# Dangerous git operations, each blocked individually (default: true)
+# Global options are skipped to find the subcommand (`git -C dir -c k=v push -f`)
+git_force_push_block = true        # git push --force / -f / +refspec (--force-with-lease is allowed)
+git_reset_hard_block = true        # git reset --hard
+git_clean_block = true             # git clean -f
+git_discard_changes_block = true   # git checkout [<commit>] [--] <paths>, git checkout ., git restore <paths>
+git_branch_delete_block = true     # git branch -D, git branch --delete --force
+git_stash_drop_block = true        # git stash drop, git stash clear
+git_filter_branch_block = true     # git filter-branch, git filter-repo
+git_no_verify_block = true         # git commit --no-verify / -n
+# Custom messages per operation (git_<operation>_block_message)
+# git_force_push_block_message = "🚫 Do not force push. Ask the user instead."
+# git_reset_hard_block_message = "🚫 Use git stash instead of git reset --hard."
+
+# Enable blocking of privilege escalation (default: true)
+# sudo, doas, su, pkexec, run0 and runas anywhere in the command, including `bash -c` strings
+privilege_block = true
+# Commands allowed to run through sudo, doas, pkexec or run0 (* matches any characters).
+# Matched against the command after the wrapper and its options
+# privilege_allowed_commands = ["apt-get update", "systemctl status *"]
+# Custom message for privilege escalation blocking
+# privilege_block_message = "🚫 Do not use sudo. Ask the user instead."
+
+# Enable blocking of unsafe permission and ownership changes (default: true)
+# chmod modes beyond permission_max_mode (anywhere), recursive chmod/chown/chgrp with
+# targets outside the project, and chattr
+permission_block = true
+# Most permissive mode chmod may grant, in octal (default: "775").
+# "775" blocks world-writable (o+w) and setuid/setgid/sticky modes; "755" also blocks group-writable
+permission_max_mode = "775"
+# Custom message for permission change blocking
+# permission_block_message = "🚫 Ask the user before changing permissions."
+
+# Enable blocking of destructive container and cluster operations (default: true)
+# docker/podman system prune, volume rm/prune, compose down -v, kubectl delete of
+# namespaces, volumes, CRDs and nodes (or --all), helm uninstall, terraform destroy
+infra_block = true
+# Contexts where these operations are allowed (* matches any characters).
+# Matched against docker/kubectl --context, helm --kube-context, DOCKER_CONTEXT and TF_WORKSPACE
+# infra_allowed_contexts = ["kind-*", "minikube", "docker-desktop"]
+# Custom message for container and cluster operation blocking
+# infra_block_message = "🚫 Ask the user to run destructive cluster operations."
+
+# Enable blocking of redirects that overwrite protected paths (default: true)
+# e.g. `echo x > /etc/hosts`, `: > ~/.bashrc`, `cat img > /dev/sda`.
+# Built-in protected paths: /etc, /boot, /bin, /sbin, /usr, /lib, /lib64, /dev, /System,
+# ~/.ssh, shell profiles and ~/.gitconfig. /dev/null, /dev/stdout, /dev/stderr and /dev/tty are allowed.
+# Appending (>>) is not blocked
+redirect_block = true
+# Action for matching redirects: "block", "ask" (ask the user to confirm) or "allow"
+redirect_action = "block"
+# Additional protected path globs (* matches within a directory, ** matches any path)
+# redirect_protected_paths = ["~/.aws/**", "*.pem"]
+# Custom message for redirect blocking
+# redirect_block_message = "🚫 Do not overwrite system files."
+
+# Enable inspection of SQL run by database clients (default: true)
+# Checks `psql -c`/`-f`, `mysql -e`, `mariadb -e` and `sqlite3 DB "SQL"` statements,
+# and SQL sent on standard input (`< file.sql`, heredocs, `echo "..." | psql`).
+# Each class of statement has its own action: "block", "ask" or "allow"
+sql_block = true
+# DROP, TRUNCATE and ALTER ... DROP
+sql_destructive_action = "block"
+# DELETE and UPDATE without a WHERE clause, and standard input that cannot be
+# checked (`curl ... | psql`, a missing `<` file)
+sql_unbounded_action = "ask"
+# SELECT, SHOW, EXPLAIN and DESCRIBE
+sql_read_only_action = "allow"
+# Custom message for SQL statement blocking
+# sql_block_message = "🚫 Ask the user to run schema changes."
+
+# Enable blocking of installs that change the machine instead of the project (default: true)
+# npm/pnpm/bun -g, yarn global, pip install --user or outside a virtualenv, pipx/uv tool
+# install, cargo install, go install, gem install and system package managers
+# (brew, apt-get, dnf, pacman, ...). Project-local installs are allowed
+global_install_block = true
+# Packages allowed to be installed globally (* matches any characters)
+# global_install_allowed_packages = ["typescript", "cargo-*"]
+# Custom message for global install blocking (replaces the suggested local equivalent)
+# global_install_block_message = "🚫 Add dependencies to the project instead."
+
+# Enable blocking of interactive and never-terminating commands that hang the session (default: true)
+# Editors (vim, nano), pagers (less), monitors (top, watch), follow modes (tail -f),
+# bare REPLs (python, node, psql), git editors and prompts (git rebase -i, git commit
+# without -m, git add -p) and dev servers (npm run dev, vite, rails server).
+# Database clients (psql, mysql, sqlite3) are blocked unless given SQL with -c/-e/-f,
+# as an argument or on standard input.
+# Follow modes, monitors and servers are allowed with `timeout` or in the background (&)
+interactive_block = true
+# Additional commands to block, as the words they start with.
+# These are blocked even with `timeout` or in the background
+# interactive_commands = ["ncdu", "npm run storybook"]
+# Custom message for interactive command blocking (replaces the suggested alternative)
+# interactive_block_message = "🚫 Run only commands that exit on their own."
+
+# Enable detection of commands the parser cannot fully understand (default: true)
+# Syntax errors (`echo "unterminated`, `(cd x`) leave commands unchecked by the other
+# filters, as do command strings nested deeper than unparseable_max_depth
+# (`bash -c "bash -c '...'"`, eval, sourced files)
+unparseable_block = true
+# Action for unparseable commands: "block", "ask" (ask the user to confirm) or "allow"
+unparseable_action = "ask"
+# Maximum nesting of command strings
+unparseable_max_depth = 8
+# Custom message for unparseable commands
+# unparseable_block_message = "🚫 Use plain shell syntax."
+
+# Shell that runs the agent's commands: "bash", "powershell" or "cmd" (default: "bash")
+# Set to "powershell" or "cmd" for agents that run commands directly in PowerShell or
+# cmd.exe on Windows. Cmdlets, their aliases and cmd.exe builtins are checked as the
+# commands they act like (`Remove-Item`, `ri`, `rd /s` as rm, `Stop-Process` as kill).
+# `powershell -Command` and `cmd /c` strings are checked in every mode
+shell_dialect = "bash"
+
+# Wrappers and launchers whose command is checked like any other command
+# Built-in: sudo, doas, pkexec, run0, env (including env -S strings), nohup, setsid,
+# stdbuf, nice, ionice, time, timeout, strace, ltrace, watch, parallel, flock, chroot,
+# xvfb-run, command, builtin, exec, npx, pnpm exec/dlx, uv run, uvx, poetry run and
+# bundle exec.
+# Entries here are added to the built-ins. An entry for a built-in adds its options and
+# subcommands to the built-in ones, and a nonzero operands_before_command replaces its count
+# [wrappers.with-env]
+# flags_with_values = ["-p", "--profile"]  # Options that take a value in the next argument
+# subcommands = ["run"]                    # The command follows one of these subcommands
+# operands_before_command = 0              # Operands between the options and the command
 
 # Enable debug logging to file (default: false)
 debug = false
@@ -109,6 +272,9 @@ debug = false
 
 # Custom command filters
 # Block specific commands and suggest alternatives
+# Without `args`, `command` is a regex matched from the start of each command: its name
+# and unquoted arguments joined by spaces. Quoted arguments are matched by their text,
+# so "git push --force" also matches `git push "--force"` and "rm.*-rf" matches `rm 'a -rf'`
 # [[custom_filters]]
 # command = "npm"
 # args = ["install", "i", "add"]         # Blocks: npm install, npm i, npm add
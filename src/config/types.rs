@@ -6,6 +6,7 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use super::validation;
+use crate::domain::{FilterAction, ShellDialect, Wrapper};
 
 /// Main configuration structure.
 #[derive(Debug, Clone, Deserialize)]
@@ -17,17 +18,190 @@ pub struct Config {
     /// Custom message for rm blocking (optional)
     pub rm_block_message: Option<String>,
 
+    /// Allow rm of untracked or git-ignored paths inside the project root
+    pub rm_allow_untracked: bool,
+
     /// Enable blocking of kill/pkill/killall commands
     pub kill_block: bool,
 
     /// Custom message for kill blocking (optional)
     pub kill_block_message: Option<String>,
 
-    /// Enable blocking of dd command
-    pub dd_block: bool,
+    /// Enable blocking of downloaded code run by a shell or interpreter (curl x | sh)
+    pub remote_script_block: bool,
+
+    /// Custom message for remote script blocking (optional)
+    pub remote_script_block_message: Option<String>,
+
+    /// Enable detection of shells running decoded text (echo ... | base64 -d | sh)
+    pub obfuscation_block: bool,
+
+    /// Action for shells running decoded text ("block", "ask" or "allow")
+    pub obfuscation_action: FilterAction,
+
+    /// Custom message for obfuscation detection (optional)
+    pub obfuscation_block_message: Option<String>,
+
+    /// Enable blocking of disk and filesystem commands (dd to devices, mkfs, fdisk, mount)
+    #[serde(alias = "dd_block")]
+    pub disk_block: bool,
+
+    /// Custom message for disk command blocking (optional)
+    #[serde(alias = "dd_block_message")]
+    pub disk_block_message: Option<String>,
+
+    /// Enable blocking of commands whose name is only known at runtime ($VAR, $(...))
+    pub dynamic_command_block: bool,
+
+    /// Custom message for dynamic command blocking (optional)
+    pub dynamic_command_block_message: Option<String>,
+
+    /// Enable blocking of git push --force/-f/+refspec
+    pub git_force_push_block: bool,
+
+    /// Custom message for git push --force blocking (optional)
+    pub git_force_push_block_message: Option<String>,
+
+    /// Enable blocking of git reset --hard
+    pub git_reset_hard_block: bool,
+
+    /// Custom message for git reset --hard blocking (optional)
+    pub git_reset_hard_block_message: Option<String>,
+
+    /// Enable blocking of git clean -f
+    pub git_clean_block: bool,
+
+    /// Custom message for git clean -f blocking (optional)
+    pub git_clean_block_message: Option<String>,
+
+    /// Enable blocking of git checkout -- <paths>/git restore <paths>
+    pub git_discard_changes_block: bool,
+
+    /// Custom message for git checkout/restore blocking (optional)
+    pub git_discard_changes_block_message: Option<String>,
+
+    /// Enable blocking of git branch -D
+    pub git_branch_delete_block: bool,
+
+    /// Custom message for git branch -D blocking (optional)
+    pub git_branch_delete_block_message: Option<String>,
+
+    /// Enable blocking of git stash drop/clear
+    pub git_stash_drop_block: bool,
+
+    /// Custom message for git stash drop blocking (optional)
+    pub git_stash_drop_block_message: Option<String>,
+
+    /// Enable blocking of git filter-branch/filter-repo
+    pub git_filter_branch_block: bool,
+
+    /// Custom message for git filter-branch blocking (optional)
+    pub git_filter_branch_block_message: Option<String>,
+
+    /// Enable blocking of git commit --no-verify
+    pub git_no_verify_block: bool,
+
+    /// Custom message for git commit --no-verify blocking (optional)
+    pub git_no_verify_block_message: Option<String>,
+
+    /// Enable blocking of privilege escalation (sudo, doas, su, pkexec)
+    pub privilege_block: bool,
+
+    /// Command globs allowed to run through sudo, doas, pkexec or run0 (apt-get update)
+    pub privilege_allowed_commands: Vec<String>,
+
+    /// Custom message for privilege escalation blocking (optional)
+    pub privilege_block_message: Option<String>,
 
-    /// Custom message for dd blocking (optional)
-    pub dd_block_message: Option<String>,
+    /// Enable blocking of unsafe permission and ownership changes
+    /// (chmod 777, recursive chmod/chown outside the project, chattr)
+    pub permission_block: bool,
+
+    /// Most permissive mode chmod may grant, in octal ("775" blocks world-writable and setuid modes)
+    pub permission_max_mode: String,
+
+    /// Custom message for permission change blocking (optional)
+    pub permission_block_message: Option<String>,
+
+    /// Enable blocking of destructive container and cluster operations
+    /// (docker system prune, kubectl delete ns, helm uninstall, terraform destroy)
+    pub infra_block: bool,
+
+    /// Context globs where destructive container and cluster operations are allowed (kind-*)
+    pub infra_allowed_contexts: Vec<String>,
+
+    /// Custom message for container and cluster operation blocking (optional)
+    pub infra_block_message: Option<String>,
+
+    /// Enable blocking of redirects that overwrite protected paths (> /etc/hosts)
+    pub redirect_block: bool,
+
+    /// Action for overwriting redirects to protected paths ("block", "ask" or "allow")
+    pub redirect_action: FilterAction,
+
+    /// Additional protected path globs, on top of the built-in system and device paths
+    pub redirect_protected_paths: Vec<String>,
+
+    /// Custom message for redirect blocking (optional)
+    pub redirect_block_message: Option<String>,
+
+    /// Enable inspection of SQL run by database clients (psql -c, mysql -e, sqlite3, stdin)
+    pub sql_block: bool,
+
+    /// Action for destructive statements (DROP, TRUNCATE, ALTER ... DROP)
+    pub sql_destructive_action: FilterAction,
+
+    /// Action for DELETE and UPDATE without a WHERE clause, and for SQL that
+    /// cannot be inspected (unreadable files, piped output, dollar-quoted bodies)
+    pub sql_unbounded_action: FilterAction,
+
+    /// Action for read-only queries (SELECT, SHOW, EXPLAIN)
+    pub sql_read_only_action: FilterAction,
+
+    /// Custom message for SQL statement blocking (optional)
+    pub sql_block_message: Option<String>,
+
+    /// Enable blocking of installs outside the project
+    /// (npm i -g, pip install --user, cargo install, go install, brew install, apt-get install)
+    pub global_install_block: bool,
+
+    /// Package globs allowed to be installed globally (typescript, cargo-*)
+    pub global_install_allowed_packages: Vec<String>,
+
+    /// Custom message for global install blocking (optional)
+    pub global_install_block_message: Option<String>,
+
+    /// Enable blocking of interactive and never-terminating commands
+    /// (vim, less, top, git rebase -i, npm run dev, tail -f, bare REPLs)
+    pub interactive_block: bool,
+
+    /// Additional commands to block, as the words they start with (ncdu, npm run storybook),
+    /// even when run with timeout or in the background
+    pub interactive_commands: Vec<String>,
+
+    /// Custom message for interactive command blocking (optional)
+    pub interactive_block_message: Option<String>,
+
+    /// Enable detection of commands the parser cannot fully understand
+    /// (syntax errors, command strings nested too deeply)
+    pub unparseable_block: bool,
+
+    /// Action for commands the parser cannot fully understand ("block", "ask" or "allow")
+    pub unparseable_action: FilterAction,
+
+    /// Nesting depth of command strings (bash -c, eval, source) beyond which a command is unparseable
+    pub unparseable_max_depth: usize,
+
+    /// Custom message for unparseable commands (optional)
+    pub unparseable_block_message: Option<String>,
+
+    /// Shell that runs the agent's commands ("bash", "powershell" or "cmd")
+    pub shell_dialect: ShellDialect,
+
+    /// Wrappers and launchers whose command is checked, added to the built-ins;
+    /// entries for a built-in extend its options (map format: [wrappers.name])
+    #[serde(default)]
+    pub wrappers: BTreeMap<String, Wrapper>,
 
     /// Enable debug logging to file
     pub debug: bool,
@@ -53,10 +227,64 @@ impl Default for Config {
         Self {
             rm_block: true,
             rm_block_message: None,
+            rm_allow_untracked: false,
             kill_block: true,
             kill_block_message: None,
-            dd_block: true,
-            dd_block_message: None,
+            remote_script_block: true,
+            remote_script_block_message: None,
+            obfuscation_block: true,
+            obfuscation_action: FilterAction::Ask,
+            obfuscation_block_message: None,
+            disk_block: true,
+            disk_block_message: None,
+            dynamic_command_block: true,
+            dynamic_command_block_message: None,
+            git_force_push_block: true,
+            git_force_push_block_message: None,
+            git_reset_hard_block: true,
+            git_reset_hard_block_message: None,
+            git_clean_block: true,
+            git_clean_block_message: None,
+            git_discard_changes_block: true,
+            git_discard_changes_block_message: None,
+            git_branch_delete_block: true,
+            git_branch_delete_block_message: None,
+            git_stash_drop_block: true,
+            git_stash_drop_block_message: None,
+            git_filter_branch_block: true,
+            git_filter_branch_block_message: None,
+            git_no_verify_block: true,
+            git_no_verify_block_message: None,
+            privilege_block: true,
+            privilege_allowed_commands: Vec::new(),
+            privilege_block_message: None,
+            permission_block: true,
+            permission_max_mode: "775".to_string(),
+            permission_block_message: None,
+            infra_block: true,
+            infra_allowed_contexts: Vec::new(),
+            infra_block_message: None,
+            redirect_block: true,
+            redirect_action: FilterAction::Block,
+            redirect_protected_paths: Vec::new(),
+            redirect_block_message: None,
+            sql_block: true,
+            sql_destructive_action: FilterAction::Block,
+            sql_unbounded_action: FilterAction::Ask,
+            sql_read_only_action: FilterAction::Allow,
+            sql_block_message: None,
+            global_install_block: true,
+            global_install_allowed_packages: Vec::new(),
+            global_install_block_message: None,
+            interactive_block: true,
+            interactive_commands: Vec::new(),
+            interactive_block_message: None,
+            unparseable_block: true,
+            unparseable_action: FilterAction::Ask,
+            unparseable_max_depth: 8,
+            unparseable_block_message: None,
+            shell_dialect: ShellDialect::Bash,
+            wrappers: BTreeMap::new(),
             debug: false,
             log_path: default_log_path(),
             custom_filters: Vec::new(),
@@ -98,7 +326,8 @@ impl Config {
 /// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct CustomFilter {
-    /// Command name (exact match when `args` is specified) or regex pattern
+    /// Command name (exact match when `args` is specified) or regex pattern, matched
+    /// against the command name and its unquoted arguments joined by spaces
     pub command: String,
 
     /// Optional list of arguments to match (any match triggers the filter)
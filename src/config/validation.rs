@@ -15,6 +15,72 @@ pub fn validate(config: &Config) -> Result<()> {
         }
     }
 
+    // Validate redirect protected paths
+    for (i, pattern) in config.redirect_protected_paths.iter().enumerate() {
+        if pattern.is_empty() {
+            bail!("redirect_protected_paths[{}]: pattern cannot be empty", i);
+        }
+    }
+
+    // Validate allowed privileged commands
+    for (i, pattern) in config.privilege_allowed_commands.iter().enumerate() {
+        if pattern.is_empty() {
+            bail!("privilege_allowed_commands[{}]: pattern cannot be empty", i);
+        }
+    }
+
+    // Validate the maximum chmod mode
+    let mode = &config.permission_max_mode;
+    if mode.is_empty() || mode.len() > 4 || u32::from_str_radix(mode, 8).is_err() {
+        bail!(
+            "permission_max_mode: '{}' must be an octal mode such as \"775\"",
+            mode
+        );
+    }
+
+    // Validate allowed container and cluster contexts
+    for (i, pattern) in config.infra_allowed_contexts.iter().enumerate() {
+        if pattern.is_empty() {
+            bail!("infra_allowed_contexts[{}]: pattern cannot be empty", i);
+        }
+    }
+
+    // Validate allowed global packages
+    for (i, pattern) in config.global_install_allowed_packages.iter().enumerate() {
+        if pattern.is_empty() {
+            bail!(
+                "global_install_allowed_packages[{}]: pattern cannot be empty",
+                i
+            );
+        }
+    }
+
+    // Validate additional interactive commands
+    for (i, command) in config.interactive_commands.iter().enumerate() {
+        if command.trim().is_empty() {
+            bail!("interactive_commands[{}]: command cannot be empty", i);
+        }
+    }
+
+    // Validate wrappers (map format)
+    for (name, wrapper) in &config.wrappers {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            bail!("wrappers: '{}' must be a command name", name);
+        }
+        for flag in &wrapper.flags_with_values {
+            if !flag.starts_with('-') {
+                bail!(
+                    "wrappers['{}']: flags_with_values entry '{}' must start with '-'",
+                    name,
+                    flag
+                );
+            }
+        }
+        if wrapper.subcommands.iter().any(|sub| sub.trim().is_empty()) {
+            bail!("wrappers['{}']: subcommands cannot be empty", name);
+        }
+    }
+
     // Validate custom filters
     for (i, filter) in config.custom_filters.iter().enumerate() {
         if filter.command.is_empty() {
@@ -1,37 +1,159 @@
 //! Filter chain implementation.
 
+use std::sync::Arc;
+
 use crate::config::Config;
+use crate::domain::parser::{ShellParser, WrapperRegistry};
 use crate::domain::Decision;
 use crate::domain::HookInput;
+use crate::domain::ShellDialect;
 
 use super::{
-    CustomCommandFilter, DdFilter, ExtensionHookFilter, Filter, KillFilter, RmFilter,
-    StopHookFilter,
+    CustomCommandFilter, DiskFilter, DynamicCommandFilter, ExtensionHookFilter, Filter,
+    FilterContext, GitFilter, GitOperation, GlobalInstallFilter, InfraFilter, InteractiveFilter,
+    KillFilter, ObfuscationFilter, PermissionFilter, PrivilegeFilter, RedirectFilter,
+    RemoteScriptFilter, RmFilter, SqlClass, SqlFilter, StopHookFilter, UnparseableFilter,
 };
 
 /// Chain of filters that processes hook inputs.
 pub struct FilterChain {
     filters: Vec<Box<dyn Filter>>,
+    /// Shell the Bash tool commands are written for
+    dialect: ShellDialect,
+    /// Nesting depth of command strings beyond which a command is unparseable
+    max_depth: usize,
+    /// Built-in and configured wrappers whose command is unwrapped
+    wrappers: Arc<WrapperRegistry>,
 }
 
 impl FilterChain {
     /// Create a new FilterChain from configuration.
     pub fn new(config: &Config) -> Self {
-        let mut filters: Vec<Box<dyn Filter>> = Vec::new();
-
         // Add built-in filters
-        filters.push(Box::new(KillFilter::new(
-            config.kill_block,
-            config.kill_block_message.clone(),
-        )));
-        filters.push(Box::new(DdFilter::new(
-            config.dd_block,
-            config.dd_block_message.clone(),
-        )));
-        filters.push(Box::new(RmFilter::new(
-            config.rm_block,
-            config.rm_block_message.clone(),
-        )));
+        let mut filters: Vec<Box<dyn Filter>> = vec![
+            Box::new(
+                PrivilegeFilter::new(
+                    config.privilege_block,
+                    config.privilege_block_message.clone(),
+                )
+                .with_allowed_commands(&config.privilege_allowed_commands),
+            ),
+            Box::new(KillFilter::new(
+                config.kill_block,
+                config.kill_block_message.clone(),
+            )),
+            Box::new(RemoteScriptFilter::new(
+                config.remote_script_block,
+                config.remote_script_block_message.clone(),
+            )),
+            Box::new(
+                ObfuscationFilter::new(
+                    config.obfuscation_block,
+                    config.obfuscation_block_message.clone(),
+                )
+                .with_action(config.obfuscation_action),
+            ),
+            Box::new(DiskFilter::new(
+                config.disk_block,
+                config.disk_block_message.clone(),
+            )),
+            Box::new(
+                RmFilter::new(config.rm_block, config.rm_block_message.clone())
+                    .with_allow_untracked(config.rm_allow_untracked),
+            ),
+            Box::new(
+                PermissionFilter::new(
+                    config.permission_block,
+                    config.permission_block_message.clone(),
+                )
+                .with_max_mode(&config.permission_max_mode),
+            ),
+            Box::new(
+                GitFilter::new()
+                    .with_operation(
+                        GitOperation::ForcePush,
+                        config.git_force_push_block,
+                        config.git_force_push_block_message.clone(),
+                    )
+                    .with_operation(
+                        GitOperation::ResetHard,
+                        config.git_reset_hard_block,
+                        config.git_reset_hard_block_message.clone(),
+                    )
+                    .with_operation(
+                        GitOperation::Clean,
+                        config.git_clean_block,
+                        config.git_clean_block_message.clone(),
+                    )
+                    .with_operation(
+                        GitOperation::DiscardChanges,
+                        config.git_discard_changes_block,
+                        config.git_discard_changes_block_message.clone(),
+                    )
+                    .with_operation(
+                        GitOperation::BranchDelete,
+                        config.git_branch_delete_block,
+                        config.git_branch_delete_block_message.clone(),
+                    )
+                    .with_operation(
+                        GitOperation::StashDrop,
+                        config.git_stash_drop_block,
+                        config.git_stash_drop_block_message.clone(),
+                    )
+                    .with_operation(
+                        GitOperation::FilterBranch,
+                        config.git_filter_branch_block,
+                        config.git_filter_branch_block_message.clone(),
+                    )
+                    .with_operation(
+                        GitOperation::NoVerify,
+                        config.git_no_verify_block,
+                        config.git_no_verify_block_message.clone(),
+                    ),
+            ),
+            Box::new(
+                InfraFilter::new(config.infra_block, config.infra_block_message.clone())
+                    .with_allowed_contexts(&config.infra_allowed_contexts),
+            ),
+            Box::new(
+                RedirectFilter::new(config.redirect_block, config.redirect_block_message.clone())
+                    .with_action(config.redirect_action)
+                    .with_protected_paths(&config.redirect_protected_paths),
+            ),
+            Box::new(
+                SqlFilter::new(config.sql_block, config.sql_block_message.clone())
+                    .with_action(SqlClass::Destructive, config.sql_destructive_action)
+                    .with_action(SqlClass::Unbounded, config.sql_unbounded_action)
+                    .with_action(SqlClass::ReadOnly, config.sql_read_only_action),
+            ),
+            Box::new(
+                GlobalInstallFilter::new(
+                    config.global_install_block,
+                    config.global_install_block_message.clone(),
+                )
+                .with_allowed_packages(&config.global_install_allowed_packages)
+                // pip installs into the virtualenv the agent runs in
+                .with_active_virtualenv(std::env::var_os("VIRTUAL_ENV").is_some()),
+            ),
+            Box::new(
+                InteractiveFilter::new(
+                    config.interactive_block,
+                    config.interactive_block_message.clone(),
+                )
+                .with_commands(&config.interactive_commands),
+            ),
+            Box::new(DynamicCommandFilter::new(
+                config.dynamic_command_block,
+                config.dynamic_command_block_message.clone(),
+            )),
+            Box::new(
+                UnparseableFilter::new(
+                    config.unparseable_block,
+                    config.unparseable_block_message.clone(),
+                )
+                .with_action(config.unparseable_action),
+            ),
+        ];
 
         // Add custom filters
         for custom in &config.custom_filters {
@@ -72,19 +194,40 @@ impl FilterChain {
         // Sort by priority (lower = higher priority)
         filters.sort_by_key(|f| f.priority());
 
-        Self { filters }
+        Self {
+            filters,
+            dialect: config.shell_dialect,
+            max_depth: config.unparseable_max_depth,
+            wrappers: Arc::new(WrapperRegistry::default().with_wrappers(&config.wrappers)),
+        }
     }
 
     /// Execute all applicable filters and return the first blocking decision.
+    /// If no filter blocks, the first Ask decision is returned.
     /// For Allow decisions, additional_context from all filters is merged.
+    ///
+    /// The command is parsed once and the result shared by all filters.
     pub fn execute(&self, input: &HookInput) -> Decision {
+        let parser = ShellParser::new()
+            .with_dialect(self.dialect)
+            .with_max_depth(self.max_depth)
+            .with_wrappers(Arc::clone(&self.wrappers));
+        let ctx = FilterContext::with_parser(input, parser);
         let mut merged_context: Option<String> = None;
+        let mut ask: Option<Decision> = None;
 
         for filter in &self.filters {
-            if filter.applies_to(input) {
-                let decision = filter.execute(input);
+            if filter.applies_to(&ctx) {
+                let decision = filter.execute(&ctx);
                 match decision {
-                    Decision::Block { .. } => return decision,
+                    Decision::Block { message } => {
+                        return Decision::Block {
+                            message: Self::explain_obfuscation(filter.as_ref(), &ctx, message),
+                        };
+                    }
+                    Decision::Ask { .. } => {
+                        ask.get_or_insert(decision);
+                    }
                     Decision::Allow { additional_context } => {
                         // Merge additional context from all Allow decisions
                         if let Some(ctx) = additional_context {
@@ -98,8 +241,28 @@ impl FilterChain {
             }
         }
 
+        if let Some(ask) = ask {
+            return ask;
+        }
+
         Decision::Allow {
             additional_context: merged_context,
         }
     }
+
+    /// Give a block message an obfuscation reason when the filter only blocks
+    /// commands decoded from obfuscated text (`$'\x72\x6d' -rf x`, `echo cm0= | base64 -d | sh`).
+    fn explain_obfuscation(filter: &dyn Filter, ctx: &FilterContext, message: String) -> String {
+        let Some(decoded) = ctx.commands().iter().find(|cmd| cmd.context.obfuscated) else {
+            return message;
+        };
+        if filter.applies_to(&ctx.without_obfuscated()) {
+            return message;
+        }
+        format!(
+            "🚫 Obfuscation detected: the command decodes to `{}`. {}",
+            decoded.argv.join(" "),
+            message.trim_start_matches('🚫').trim_start()
+        )
+    }
 }
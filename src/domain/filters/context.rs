@@ -0,0 +1,92 @@
+//! Analysis context shared by the filters of a chain.
+
+use std::sync::Arc;
+
+use crate::domain::parser::{ParseConfidence, ParsedCommand, ShellParser, WrapperRegistry};
+use crate::domain::{HookInput, ToolInput};
+
+/// A hook input together with the analysis every filter needs.
+///
+/// The Bash command of a PreToolUse event is parsed once when the context is
+/// created, so the cost of a hook call does not grow with the number of filters.
+pub struct FilterContext<'a> {
+    input: &'a HookInput,
+    commands: Vec<ParsedCommand>,
+    confidence: ParseConfidence,
+    wrappers: Arc<WrapperRegistry>,
+}
+
+impl<'a> FilterContext<'a> {
+    /// Create the context for a hook input, parsing its Bash command if it has one.
+    pub fn new(input: &'a HookInput) -> Self {
+        Self::with_parser(input, ShellParser::new())
+    }
+
+    /// Create the context for a hook input, parsing its Bash command with a configured
+    /// parser (dialect, maximum nesting depth, wrappers) in the input's working directory.
+    pub fn with_parser(input: &'a HookInput, mut parser: ShellParser) -> Self {
+        if let Some(cwd) = input.cwd.as_deref().filter(|cwd| !cwd.is_empty()) {
+            parser = parser.with_cwd(cwd);
+        }
+        let commands = match Self::bash_command_of(input) {
+            Some(command) => parser.parse(command),
+            None => Vec::new(),
+        };
+        Self {
+            input,
+            commands,
+            confidence: parser.confidence().clone(),
+            wrappers: Arc::clone(parser.wrappers()),
+        }
+    }
+
+    /// The hook input being filtered.
+    pub fn input(&self) -> &HookInput {
+        self.input
+    }
+
+    /// The Bash command string of a PreToolUse event for the Bash tool.
+    pub fn bash_command(&self) -> Option<&str> {
+        Self::bash_command_of(self.input)
+    }
+
+    /// Commands parsed from the Bash command (empty for any other input).
+    pub fn commands(&self) -> &[ParsedCommand] {
+        &self.commands
+    }
+
+    /// How completely the Bash command was understood (complete for any other input).
+    pub fn confidence(&self) -> &ParseConfidence {
+        &self.confidence
+    }
+
+    /// The built-in and configured wrappers the commands were unwrapped with.
+    pub fn wrappers(&self) -> &WrapperRegistry {
+        &self.wrappers
+    }
+
+    /// The same context without the commands decoded from obfuscated text.
+    pub fn without_obfuscated(&self) -> Self {
+        Self {
+            input: self.input,
+            commands: self
+                .commands
+                .iter()
+                .filter(|cmd| !cmd.context.obfuscated)
+                .cloned()
+                .collect(),
+            confidence: self.confidence.clone(),
+            wrappers: Arc::clone(&self.wrappers),
+        }
+    }
+
+    fn bash_command_of(input: &HookInput) -> Option<&str> {
+        if input.event != "PreToolUse" || input.tool_name != "Bash" {
+            return None;
+        }
+        match &input.tool_input {
+            ToolInput::Bash(bash) => Some(&bash.command),
+            _ => None,
+        }
+    }
+}
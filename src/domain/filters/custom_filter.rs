@@ -2,9 +2,9 @@
 
 use regex::Regex;
 
-use super::Filter;
-use crate::domain::parser::ShellParser;
-use crate::domain::{Decision, HookInput, ToolInput};
+use super::{Filter, FilterContext};
+use crate::domain::parser::ParsedCommand;
+use crate::domain::Decision;
 
 /// Filter mode for custom command matching.
 enum FilterMode {
@@ -84,111 +84,47 @@ impl CustomCommandFilter {
         })
     }
 
-    /// Strip quoted content from a command string for pattern matching.
-    /// This prevents false positives like matching "yarn" in `echo "yarn"`.
-    fn strip_quoted_content(s: &str) -> String {
-        let mut result = String::new();
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut chars = s.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            if c == '\\' && !in_single_quote {
-                // Skip escaped character
-                chars.next();
-                continue;
-            }
-
-            if c == '\'' && !in_double_quote {
-                in_single_quote = !in_single_quote;
-                continue;
-            }
-
-            if c == '"' && !in_single_quote {
-                in_double_quote = !in_double_quote;
-                continue;
-            }
-
-            if !in_single_quote && !in_double_quote {
-                result.push(c);
-            }
-        }
-
-        result
+    /// Check if a command matches using regex mode.
+    /// The pattern is matched against the command name and its unquoted arguments
+    /// joined by spaces, so quoted arguments never look like separate commands.
+    fn matches_regex(cmd: &ParsedCommand, pattern: &Regex) -> bool {
+        pattern.is_match(&cmd.argv.join(" "))
     }
 
-    /// Check if any command in the string matches using regex mode.
-    fn matches_regex(&self, command: &str, pattern: &Regex) -> bool {
-        let mut parser = ShellParser::new();
-        let command_strings = parser.extract_command_strings(command);
-
-        command_strings
-            .iter()
-            .any(|cmd| pattern.is_match(&Self::strip_quoted_content(cmd)))
-    }
+    /// Check if a command matches using args mode.
+    fn matches_args(cmd: &ParsedCommand, target_cmd: &Regex, target_args: &[String]) -> bool {
+        // Check if command name matches regex
+        if !target_cmd.is_match(cmd.name()) {
+            return false;
+        }
 
-    /// Check if any command in the string matches using args mode.
-    fn matches_args(
-        &self,
-        input_command: &str,
-        target_cmd: &Regex,
-        target_args: &[String],
-    ) -> bool {
-        let mut parser = ShellParser::new();
-        let command_strings = parser.extract_command_strings(input_command);
-
-        for cmd_str in command_strings {
-            let stripped = Self::strip_quoted_content(&cmd_str);
-            let parts: Vec<&str> = stripped.split_whitespace().collect();
-
-            if parts.is_empty() {
-                continue;
-            }
-
-            // Check if command name matches regex
-            if !target_cmd.is_match(parts[0]) {
-                continue;
-            }
-
-            // If no args specified, any usage of the command matches
-            if target_args.is_empty() {
-                return true;
-            }
-
-            // Check if any of the target args is present
-            if parts.len() > 1 && target_args.iter().any(|arg| parts[1] == arg) {
-                return true;
-            }
+        // If no args specified, any usage of the command matches
+        if target_args.is_empty() {
+            return true;
         }
 
-        false
+        // Check if any of the target args is present
+        cmd.args()
+            .first()
+            .is_some_and(|arg| target_args.contains(arg))
     }
 
-    /// Check if any command in the string matches the filter.
-    fn matches(&self, command: &str) -> bool {
-        match &self.mode {
-            FilterMode::Regex(pattern) => self.matches_regex(command, pattern),
-            FilterMode::Args { command: cmd, args } => self.matches_args(command, cmd, args),
-        }
+    /// Check if any parsed command matches the filter.
+    fn matches(&self, commands: &[ParsedCommand]) -> bool {
+        commands.iter().any(|cmd| match &self.mode {
+            FilterMode::Regex(pattern) => Self::matches_regex(cmd, pattern),
+            FilterMode::Args { command, args } => Self::matches_args(cmd, command, args),
+        })
     }
 }
 
 impl Filter for CustomCommandFilter {
-    fn applies_to(&self, input: &HookInput) -> bool {
-        // Only applies to Bash tool in PreToolUse event
-        if input.event != "PreToolUse" || input.tool_name != "Bash" {
-            return false;
-        }
-
-        // Extract command from tool input
-        if let ToolInput::Bash(bash) = &input.tool_input {
-            return self.matches(&bash.command);
-        }
-
-        false
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.matches(ctx.commands())
     }
 
-    fn execute(&self, _input: &HookInput) -> Decision {
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
         Decision::Block {
             message: self.message.clone(),
         }
@@ -202,14 +138,19 @@ impl Filter for CustomCommandFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::parser::ShellParser;
+
+    fn matches(filter: &CustomCommandFilter, command: &str) -> bool {
+        filter.matches(&ShellParser::new().parse(command))
+    }
 
     // Regex mode tests
     #[test]
     fn test_custom_filter_regex() {
         let filter = CustomCommandFilter::new("python", "Use uv instead".to_string()).unwrap();
-        assert!(filter.matches("python script.py"));
-        assert!(filter.matches("python"));
-        assert!(!filter.matches("ls"));
+        assert!(matches(&filter, "python script.py"));
+        assert!(matches(&filter, "python"));
+        assert!(!matches(&filter, "ls"));
     }
 
     #[test]
@@ -217,17 +158,17 @@ mod tests {
         let filter = CustomCommandFilter::new("yarn", "Use pnpm instead".to_string()).unwrap();
 
         // yarn after semicolon should be detected
-        assert!(filter.matches("echo \"install\"; yarn install"));
+        assert!(matches(&filter, "echo \"install\"; yarn install"));
 
         // yarn in quotes should NOT trigger (it's not a command)
-        assert!(!filter.matches("echo \"not yarn install\"; pnpm install"));
+        assert!(!matches(&filter, "echo \"not yarn install\"; pnpm install"));
 
         // Direct yarn command
-        assert!(filter.matches("yarn install"));
-        assert!(filter.matches("yarn add react"));
+        assert!(matches(&filter, "yarn install"));
+        assert!(matches(&filter, "yarn add react"));
 
         // pnpm should pass
-        assert!(!filter.matches("pnpm install"));
+        assert!(!matches(&filter, "pnpm install"));
     }
 
     #[test]
@@ -235,12 +176,12 @@ mod tests {
         let filter = CustomCommandFilter::new("python", "Use uv instead".to_string()).unwrap();
 
         // python in chained commands
-        assert!(filter.matches("cd /app && python script.py"));
-        assert!(filter.matches("echo done; python main.py"));
-        assert!(filter.matches("ls | python filter.py"));
+        assert!(matches(&filter, "cd /app && python script.py"));
+        assert!(matches(&filter, "echo done; python main.py"));
+        assert!(matches(&filter, "ls | python filter.py"));
 
         // python in quotes should NOT trigger
-        assert!(!filter.matches("echo \"python is great\""));
+        assert!(!matches(&filter, "echo \"python is great\""));
     }
 
     // Args mode tests
@@ -254,19 +195,19 @@ mod tests {
         .unwrap();
 
         // Should match
-        assert!(filter.matches("npm install"));
-        assert!(filter.matches("npm i"));
-        assert!(filter.matches("npm add react"));
-        assert!(filter.matches("npm install lodash"));
+        assert!(matches(&filter, "npm install"));
+        assert!(matches(&filter, "npm i"));
+        assert!(matches(&filter, "npm add react"));
+        assert!(matches(&filter, "npm install lodash"));
 
         // Should not match (different subcommand)
-        assert!(!filter.matches("npm run build"));
-        assert!(!filter.matches("npm test"));
-        assert!(!filter.matches("npm --version"));
+        assert!(!matches(&filter, "npm run build"));
+        assert!(!matches(&filter, "npm test"));
+        assert!(!matches(&filter, "npm --version"));
 
         // Should not match (different command)
-        assert!(!filter.matches("pnpm install"));
-        assert!(!filter.matches("yarn add"));
+        assert!(!matches(&filter, "pnpm install"));
+        assert!(!matches(&filter, "yarn add"));
     }
 
     #[test]
@@ -279,14 +220,14 @@ mod tests {
         .unwrap();
 
         // Should match in chained commands
-        assert!(filter.matches("echo done; npm install"));
-        assert!(filter.matches("cd /app && npm i lodash"));
+        assert!(matches(&filter, "echo done; npm install"));
+        assert!(matches(&filter, "cd /app && npm i lodash"));
 
         // Should not match when in quotes
-        assert!(!filter.matches("echo \"npm install\""));
+        assert!(!matches(&filter, "echo \"npm install\""));
 
         // Should not match different subcommand in chain
-        assert!(!filter.matches("npm run build && echo done"));
+        assert!(!matches(&filter, "npm run build && echo done"));
     }
 
     #[test]
@@ -296,13 +237,13 @@ mod tests {
             CustomCommandFilter::with_args("yarn", vec![], "Use pnpm instead".to_string()).unwrap();
 
         // Should match all yarn commands
-        assert!(filter.matches("yarn"));
-        assert!(filter.matches("yarn install"));
-        assert!(filter.matches("yarn add react"));
-        assert!(filter.matches("yarn run build"));
+        assert!(matches(&filter, "yarn"));
+        assert!(matches(&filter, "yarn install"));
+        assert!(matches(&filter, "yarn add react"));
+        assert!(matches(&filter, "yarn run build"));
 
         // Should not match other commands
-        assert!(!filter.matches("npm install"));
+        assert!(!matches(&filter, "npm install"));
     }
 
     #[test]
@@ -315,12 +256,12 @@ mod tests {
         .unwrap();
 
         // Should match
-        assert!(filter.matches("hoge --fuga"));
-        assert!(filter.matches("hoge -f value"));
+        assert!(matches(&filter, "hoge --fuga"));
+        assert!(matches(&filter, "hoge -f value"));
 
         // Should not match
-        assert!(!filter.matches("hoge --other"));
-        assert!(!filter.matches("hoge run"));
+        assert!(!matches(&filter, "hoge --other"));
+        assert!(!matches(&filter, "hoge run"));
     }
 
     #[test]
@@ -334,16 +275,57 @@ mod tests {
         .unwrap();
 
         // Should match both pip and pip3
-        assert!(filter.matches("pip install requests"));
-        assert!(filter.matches("pip3 install requests"));
-        assert!(filter.matches("pip uninstall requests"));
-        assert!(filter.matches("pip3 uninstall requests"));
+        assert!(matches(&filter, "pip install requests"));
+        assert!(matches(&filter, "pip3 install requests"));
+        assert!(matches(&filter, "pip uninstall requests"));
+        assert!(matches(&filter, "pip3 uninstall requests"));
 
         // Should not match other subcommands
-        assert!(!filter.matches("pip list"));
-        assert!(!filter.matches("pip3 --version"));
+        assert!(!matches(&filter, "pip list"));
+        assert!(!matches(&filter, "pip3 --version"));
 
         // Should not match other commands
-        assert!(!filter.matches("python install"));
+        assert!(!matches(&filter, "python install"));
+    }
+
+    #[test]
+    fn test_custom_filter_matches_normalized_name() {
+        let filter = CustomCommandFilter::new("yarn", "Use pnpm instead".to_string()).unwrap();
+        assert!(matches(&filter, "/usr/local/bin/yarn install"));
+        assert!(matches(&filter, "\\yarn install"));
+        assert!(matches(&filter, "command yarn add react"));
+        assert!(matches(&filter, "sudo -u node yarn build"));
+
+        let filter = CustomCommandFilter::with_args(
+            "npm",
+            vec!["install".to_string()],
+            "Use pnpm instead".to_string(),
+        )
+        .unwrap();
+        assert!(matches(&filter, "./node_modules/.bin/npm install"));
+        assert!(matches(&filter, "exec npm install"));
+        assert!(!matches(&filter, "exec npm run build"));
+    }
+
+    #[test]
+    fn test_custom_filter_matches_unquoted_arguments() {
+        let filter = CustomCommandFilter::with_args(
+            "npm",
+            vec!["install".to_string()],
+            "Use pnpm instead".to_string(),
+        )
+        .unwrap();
+        assert!(matches(&filter, "npm \"install\" lodash"));
+        assert!(matches(&filter, "npm 'install'"));
+
+        let filter =
+            CustomCommandFilter::new("git push --force", "No force push".to_string()).unwrap();
+        assert!(matches(&filter, "git push \"--force\""));
+        assert!(!matches(&filter, "git commit -m 'git push --force'"));
+
+        // The text of quoted arguments is matched too
+        let filter = CustomCommandFilter::new("rm.*-rf", "No rm -rf".to_string()).unwrap();
+        assert!(matches(&filter, "rm -i 'notes -rf.txt'"));
+        assert!(!matches(&filter, "echo 'rm -rf x'"));
     }
 }
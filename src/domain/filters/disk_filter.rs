@@ -0,0 +1,226 @@
+//! Disk and filesystem command filter implementation.
+
+use std::fs;
+
+use super::{Filter, FilterContext};
+use crate::domain::parser::ParsedCommand;
+use crate::domain::workspace::Workspace;
+use crate::domain::Decision;
+
+/// Default message for disk command blocking.
+const DEFAULT_DISK_MESSAGE: &str = "🚫 Disk and filesystem commands (dd to devices, mkfs, fdisk, parted, wipefs, mount) are blocked for safety. Use cp or rsync for file operations. If you need them, use safe-dd or request explicit permission.";
+
+/// Filter for blocking commands that overwrite disks or change filesystems.
+///
+/// `dd` is allowed when it writes to a regular file inside the project
+/// (`dd if=/dev/zero of=disk.img`); any other output, including devices, is blocked.
+pub struct DiskFilter {
+    enabled: bool,
+    message: String,
+}
+
+impl DiskFilter {
+    /// Create a new DiskFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            message: custom_message.unwrap_or_else(|| DEFAULT_DISK_MESSAGE.to_string()),
+        }
+    }
+
+    /// Commands that create filesystems, discard blocks or unmount (always blocked)
+    const DISK_COMMANDS: &'static [&'static str] = &[
+        "mkfs",       // Unix (also mkfs.<type>)
+        "mke2fs",     // Linux
+        "mkswap",     // Linux
+        "newfs",      // BSD/macOS
+        "blkdiscard", // Linux
+        "umount",     // Unix
+    ];
+
+    /// Partitioning commands, allowed only to list partitions (`fdisk -l`)
+    const PARTITION_COMMANDS: &'static [&'static str] =
+        &["fdisk", "sfdisk", "cfdisk", "gdisk", "sgdisk", "parted"];
+
+    /// diskutil verbs that erase, partition or unmount disks (macOS)
+    const DISKUTIL_VERBS: &'static [&'static str] = &[
+        "erasedisk",
+        "erasevolume",
+        "reformat",
+        "partitiondisk",
+        "zerodisk",
+        "randomdisk",
+        "secureerase",
+        "unmount",
+        "unmountdisk",
+    ];
+
+    /// Commands that change the directory relative `of=` paths resolve against
+    const DIRECTORY_COMMANDS: &'static [&'static str] = &["cd", "pushd", "popd"];
+
+    /// Check if any parsed command operates on disks or filesystems.
+    fn contains_disk_operation(commands: &[ParsedCommand], cwd: Option<&str>) -> bool {
+        commands.iter().any(|cmd| {
+            let has_arg =
+                |flags: &[&str]| cmd.args().iter().any(|arg| flags.contains(&arg.as_str()));
+            match cmd.name() {
+                name if Self::DISK_COMMANDS.contains(&name) || name.starts_with("mkfs.") => true,
+                name if Self::PARTITION_COMMANDS.contains(&name) => !has_arg(&["-l", "--list"]),
+                // wipefs erases signatures with -a or -o; only --no-act is certain not to write
+                "wipefs" => !has_arg(&["-n", "--no-act"]),
+                "shred" => cmd.args().iter().any(|arg| arg.starts_with("/dev/")),
+                // Without arguments (or with -l), mount lists the mounted filesystems
+                "mount" => cmd.args().iter().any(|arg| arg != "-l"),
+                "diskutil" => cmd.args().first().is_some_and(|verb| {
+                    Self::DISKUTIL_VERBS.contains(&verb.to_ascii_lowercase().as_str())
+                }),
+                "dd" => !Self::writes_to_project_file(commands, cmd, cwd),
+                _ => false,
+            }
+        })
+    }
+
+    /// Check if a dd command writes to a regular file inside the project.
+    ///
+    /// Writes to stdout, devices, paths outside the project or paths that cannot
+    /// be resolved statically (variables, arguments supplied by xargs) are not.
+    fn writes_to_project_file(
+        commands: &[ParsedCommand],
+        dd: &ParsedCommand,
+        cwd: Option<&str>,
+    ) -> bool {
+        // The last of= operand wins
+        let Some(output) = dd
+            .args()
+            .iter()
+            .rev()
+            .find_map(|arg| arg.strip_prefix("of="))
+        else {
+            return false;
+        };
+        if output.starts_with("/dev/") || dd.wrappers.iter().any(|wrapper| wrapper == "xargs") {
+            return false;
+        }
+        if commands
+            .iter()
+            .any(|cmd| Self::DIRECTORY_COMMANDS.contains(&cmd.name()))
+        {
+            return false;
+        }
+
+        let Some(workspace) = Workspace::detect(cwd) else {
+            return false;
+        };
+        let Some(path) = workspace.resolve(output) else {
+            return false;
+        };
+        if !workspace.contains(&path) {
+            return false;
+        }
+        // An existing output must be a regular file (following symlinks) inside the project
+        match fs::canonicalize(&path) {
+            Ok(target) => {
+                workspace.contains(&target) && fs::metadata(&target).is_ok_and(|m| m.is_file())
+            }
+            Err(_) => true,
+        }
+    }
+}
+
+impl Filter for DiskFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && Self::contains_disk_operation(ctx.commands(), ctx.input().cwd.as_deref())
+    }
+
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
+        Decision::Block {
+            message: self.message.clone(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        15 // High priority, between kill (10) and rm (20)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::filters::test_repo::TestRepo;
+    use crate::domain::parser::ShellParser;
+
+    fn contains_disk_operation(command: &str) -> bool {
+        DiskFilter::contains_disk_operation(&ShellParser::new().parse(command), Some("/"))
+    }
+
+    #[test]
+    fn test_contains_disk_operation() {
+        // Simple dd commands
+        assert!(contains_disk_operation("dd if=/dev/zero of=/dev/sda"));
+        assert!(contains_disk_operation(
+            "dd if=input.img of=output.img bs=4M"
+        ));
+        assert!(!contains_disk_operation("ls -la"));
+        assert!(!contains_disk_operation("echo dd"));
+
+        // Piped commands
+        assert!(contains_disk_operation("cat file | dd of=output.img"));
+
+        // Chained commands
+        assert!(contains_disk_operation(
+            "sync && dd if=/dev/sda of=backup.img"
+        ));
+
+        // Path-qualified, escaped and wrapped command names
+        assert!(contains_disk_operation("/bin/dd if=/dev/zero of=x"));
+        assert!(contains_disk_operation("command 'dd' if=a of=b"));
+
+        // Filesystem and partitioning commands
+        assert!(contains_disk_operation("mkfs.ext4 /dev/sdb1"));
+        assert!(contains_disk_operation("sudo mkfs -t xfs /dev/sdb1"));
+        assert!(contains_disk_operation("fdisk /dev/sda"));
+        assert!(contains_disk_operation("parted /dev/sda mklabel gpt"));
+        assert!(contains_disk_operation("wipefs -a /dev/sdb"));
+        assert!(contains_disk_operation("shred -n 3 /dev/sdb"));
+        assert!(contains_disk_operation("blkdiscard /dev/nvme0n1"));
+        assert!(contains_disk_operation("mount /dev/sdb1 /mnt"));
+        assert!(contains_disk_operation("umount /mnt"));
+        assert!(contains_disk_operation(
+            "diskutil eraseDisk APFS Disk disk2"
+        ));
+
+        // Read-only forms
+        assert!(!contains_disk_operation("fdisk -l"));
+        assert!(!contains_disk_operation("parted --list"));
+        assert!(!contains_disk_operation("wipefs --no-act -a /dev/sdb"));
+        assert!(!contains_disk_operation("mount"));
+        assert!(!contains_disk_operation("diskutil list"));
+        assert!(!contains_disk_operation("shred notes.txt"));
+    }
+
+    #[test]
+    fn test_dd_output_in_project() {
+        let repo = TestRepo::new("disk-filter-test");
+        repo.write("images/existing.img", "");
+        let cwd = repo.cwd();
+        let blocked =
+            |cmd: &str| DiskFilter::contains_disk_operation(&ShellParser::new().parse(cmd), cwd);
+
+        // Regular files inside the project are allowed
+        assert!(!blocked("dd if=/dev/zero of=disk.img bs=1M count=10"));
+        assert!(!blocked("dd if=/dev/sda of=images/existing.img"));
+        assert!(!blocked("dd if=a of=/dev/null of=images/new.img"));
+
+        // Devices, paths outside the project and unresolvable outputs are blocked
+        assert!(blocked("dd if=disk.img of=/dev/sdb"));
+        assert!(blocked("dd if=disk.img of=images/new.img of=/dev/sdb"));
+        assert!(blocked("dd if=/dev/zero of=/tmp/disk.img"));
+        assert!(blocked("dd if=/dev/zero of=../disk.img"));
+        assert!(blocked("dd if=/dev/zero of=images"));
+        assert!(blocked("dd if=/dev/zero of=$OUT"));
+        assert!(blocked("dd if=/dev/zero bs=1M count=1"));
+        assert!(blocked("cd /dev && dd if=/dev/zero of=sda"));
+        assert!(blocked("echo /dev/sda | xargs dd if=/dev/zero of=disk.img"));
+    }
+}
@@ -0,0 +1,113 @@
+//! Dynamic command filter implementation.
+
+use std::path::Path;
+
+use super::{Filter, FilterContext};
+use crate::domain::parser::{
+    is_dynamic_command_name, is_source_command, sourced_script_path, ParsedCommand,
+};
+use crate::domain::Decision;
+
+/// Default message for blocking commands whose name is only known at runtime.
+const DEFAULT_DYNAMIC_COMMAND_MESSAGE: &str = "🚫 Command is only known at runtime ($VAR, $(...), backticks or a sourced file that does not exist yet) and cannot be checked. Run the command by its name instead.";
+
+/// Filter for blocking commands whose name cannot be resolved statically.
+///
+/// Variables and aliases defined earlier in the same command string are resolved
+/// by the parser, so `X=ls; $X` passes while `$CMD -rf dir` is blocked.
+/// Sourced files are scanned by the parser; files that cannot be read are blocked.
+pub struct DynamicCommandFilter {
+    enabled: bool,
+    message: String,
+}
+
+impl DynamicCommandFilter {
+    /// Create a new DynamicCommandFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            message: custom_message.unwrap_or_else(|| DEFAULT_DYNAMIC_COMMAND_MESSAGE.to_string()),
+        }
+    }
+
+    /// Check if any parsed command has a name only known at runtime,
+    /// or sources a file that cannot be read.
+    fn contains_dynamic_command(commands: &[ParsedCommand], cwd: Option<&Path>) -> bool {
+        commands.iter().any(|cmd| {
+            is_dynamic_command_name(cmd.name())
+                || (is_source_command(cmd.name())
+                    && !sourced_script_path(cmd.args(), cwd).is_some_and(|path| path.is_file()))
+        })
+    }
+}
+
+impl Filter for DynamicCommandFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        let cwd = ctx.input().cwd.as_deref().map(Path::new);
+        self.enabled && Self::contains_dynamic_command(ctx.commands(), cwd)
+    }
+
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
+        Decision::Block {
+            message: self.message.clone(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        30 // After the command-specific filters so their messages take precedence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+
+    fn contains_dynamic_command(command: &str) -> bool {
+        DynamicCommandFilter::contains_dynamic_command(&ShellParser::new().parse(command), None)
+    }
+
+    #[test]
+    fn test_contains_dynamic_command() {
+        assert!(contains_dynamic_command("$CMD -rf dir"));
+        assert!(contains_dynamic_command("\"$(cat cmd.txt)\" -rf dir"));
+        assert!(contains_dynamic_command("`cat cmd.txt` -rf dir"));
+        assert!(contains_dynamic_command("sudo $CMD dir"));
+
+        assert!(!contains_dynamic_command("ls -la"));
+        assert!(!contains_dynamic_command("echo $HOME $(date)"));
+    }
+
+    #[test]
+    #[cfg(feature = "ast-parser")]
+    fn test_resolved_command_is_not_dynamic() {
+        assert!(!contains_dynamic_command("X=ls; $X -la"));
+        assert!(!contains_dynamic_command("alias l='ls -la'; l"));
+        assert!(contains_dynamic_command("X=ls; read X; $X -la"));
+        assert!(!contains_dynamic_command("$(which ls) -la"));
+    }
+
+    #[test]
+    fn test_unreadable_sourced_file_is_dynamic() {
+        assert!(contains_dynamic_command(
+            "echo 'rm -rf x' > /tmp/claw-hooks-missing.sh; source /tmp/claw-hooks-missing.sh"
+        ));
+        assert!(contains_dynamic_command(". $VENV/bin/activate"));
+
+        let script = std::env::temp_dir().join(format!(
+            "claw-hooks-dynamic-filter-{}.sh",
+            std::process::id()
+        ));
+        std::fs::write(&script, "echo sourced\n").unwrap();
+        let sourced = contains_dynamic_command(&format!("source {}", script.display()));
+        // Relative paths resolve against the directory the command runs in
+        let name = script.file_name().unwrap().to_str().unwrap();
+        let commands = ShellParser::new().parse(&format!("source ./{}", name));
+        let relative = DynamicCommandFilter::contains_dynamic_command(&commands, script.parent());
+        std::fs::remove_file(&script).unwrap();
+        assert!(!sourced);
+        assert!(!relative);
+        assert!(contains_dynamic_command(&format!("source ./{}", name)));
+    }
+}
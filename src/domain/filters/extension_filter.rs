@@ -5,8 +5,8 @@ use std::path::Path;
 use std::process::Command;
 use tracing::{debug, warn};
 
-use super::Filter;
-use crate::domain::{Decision, HookInput, ToolInput};
+use super::{Filter, FilterContext};
+use crate::domain::{Decision, ToolInput};
 
 /// Parsed command template result.
 struct ParsedCommand {
@@ -226,7 +226,9 @@ impl ExtensionHookFilter {
 }
 
 impl Filter for ExtensionHookFilter {
-    fn applies_to(&self, input: &HookInput) -> bool {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        let input = ctx.input();
+
         // Applies to Write, Edit, MultiEdit in both PreToolUse and PostToolUse events
         // NOT for Read operations
         //
@@ -251,9 +253,9 @@ impl Filter for ExtensionHookFilter {
         false
     }
 
-    fn execute(&self, input: &HookInput) -> Decision {
+    fn execute(&self, ctx: &FilterContext) -> Decision {
         // Extract file path and execute commands
-        if let ToolInput::File(file_input) = &input.tool_input {
+        if let ToolInput::File(file_input) = &ctx.input().tool_input {
             if let Some(commands) = self.get_matching_commands(&file_input.file_path) {
                 // Execute commands and collect output
                 let (_all_success, output) = self.execute_commands(commands, &file_input.file_path);
@@ -1,14 +1,15 @@
 //! Filter trait definition.
 
-use crate::domain::{Decision, HookInput};
+use super::FilterContext;
+use crate::domain::Decision;
 
 /// Trait for command filters.
 pub trait Filter: Send + Sync {
-    /// Check if this filter applies to the given input.
-    fn applies_to(&self, input: &HookInput) -> bool;
+    /// Check if this filter applies to the input in the context.
+    fn applies_to(&self, ctx: &FilterContext) -> bool;
 
     /// Execute the filter and return a decision.
-    fn execute(&self, input: &HookInput) -> Decision;
+    fn execute(&self, ctx: &FilterContext) -> Decision;
 
     /// Get the priority of this filter (lower = higher priority).
     fn priority(&self) -> u32;
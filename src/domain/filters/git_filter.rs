@@ -0,0 +1,421 @@
+//! Git command filter implementation.
+
+use super::{Filter, FilterContext};
+use crate::domain::parser::{git_subcommand_index, ParsedCommand};
+use crate::domain::Decision;
+
+/// Categories of git operations that destroy work or bypass safeguards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitOperation {
+    /// `push --force`, `push -f`, `push origin +main`
+    ForcePush,
+    /// `reset --hard`
+    ResetHard,
+    /// `clean -f` (including `-fdx`)
+    Clean,
+    /// `checkout -- <paths>`, `checkout .`, `checkout <commit> <paths>`, `restore <paths>`
+    DiscardChanges,
+    /// `branch -D`, `branch --delete --force`
+    BranchDelete,
+    /// `stash drop`, `stash clear`
+    StashDrop,
+    /// `filter-branch`, `filter-repo`
+    FilterBranch,
+    /// `commit --no-verify`, `commit -n`
+    NoVerify,
+}
+
+impl GitOperation {
+    /// Default message for blocking the operation.
+    fn default_message(self) -> &'static str {
+        match self {
+            Self::ForcePush => "🚫 git push --force overwrites remote history. Push without force, or ask the user before force pushing.",
+            Self::ResetHard => "🚫 git reset --hard discards uncommitted changes. Use git stash or git reset --keep instead.",
+            Self::Clean => "🚫 git clean -f permanently deletes untracked files. Preview with git clean -n and remove files individually.",
+            Self::DiscardChanges => "🚫 git checkout [<commit>] [--] <paths> and git restore discard uncommitted changes. Commit or stash them first.",
+            Self::BranchDelete => "🚫 git branch -D deletes unmerged branches. Use git branch -d, which refuses to delete unmerged work.",
+            Self::StashDrop => "🚫 git stash drop/clear permanently deletes stashed changes.",
+            Self::FilterBranch => "🚫 git filter-branch rewrites the history of the whole repository.",
+            Self::NoVerify => "🚫 git commit --no-verify skips the repository's commit hooks. Fix the reported problems instead.",
+        }
+    }
+
+    /// Check if git arguments after the subcommand perform this operation.
+    fn matches(self, subcommand: &str, args: &[String]) -> bool {
+        match (self, subcommand) {
+            (Self::ForcePush, "push") => {
+                let options = GitOptions::new(args, "o", &["--push-option", "--repo", "--exec"]);
+                options.has(Some('f'), "--force")
+                    || options.operands().iter().any(|arg| arg.starts_with('+'))
+            }
+            (Self::ResetHard, "reset") => GitOptions::new(args, "", &[]).has(None, "--hard"),
+            (Self::Clean, "clean") => {
+                let options = GitOptions::new(args, "e", &["--exclude"]);
+                options.has(Some('f'), "--force") && !options.has(Some('n'), "--dry-run")
+            }
+            (Self::DiscardChanges, "checkout") => {
+                let options = GitOptions::new(args, "bB", &["--conflict", "--orphan"]);
+                // Operands after the first (the commit to check out) are paths:
+                // `checkout HEAD file` overwrites it like `checkout -- file`
+                !options.pathspecs().is_empty()
+                    || options.operands().contains(&".")
+                    || options.operands().len() > 1
+            }
+            (Self::DiscardChanges, "restore") => {
+                let options = GitOptions::new(args, "s", &["--source"]);
+                // `restore --staged` alone only unstages changes
+                let staged_only =
+                    options.has(Some('S'), "--staged") && !options.has(Some('W'), "--worktree");
+                let has_paths =
+                    !options.operands().is_empty() || options.has(None, "--pathspec-from-file");
+                has_paths && !staged_only
+            }
+            (Self::BranchDelete, "branch") => {
+                let options = GitOptions::new(args, "", &["--set-upstream-to", "--contains"]);
+                options.has(Some('D'), "")
+                    || (options.has(Some('d'), "--delete") && options.has(Some('f'), "--force"))
+            }
+            (Self::StashDrop, "stash") => args
+                .iter()
+                .find(|arg| !arg.starts_with('-'))
+                .is_some_and(|action| action == "drop" || action == "clear"),
+            (Self::FilterBranch, "filter-branch" | "filter-repo") => true,
+            (Self::NoVerify, "commit") => {
+                let options = GitOptions::new(
+                    args,
+                    "mFcCt",
+                    &[
+                        "--message",
+                        "--file",
+                        "--reuse-message",
+                        "--reedit-message",
+                        "--fixup",
+                        "--squash",
+                        "--author",
+                        "--date",
+                        "--template",
+                        "--trailer",
+                    ],
+                );
+                options.has(Some('n'), "--no-verify")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Options and operands of a git subcommand.
+struct GitOptions<'a> {
+    /// Option arguments (`-f`, `-fdx`, `--force`, `--source=HEAD`)
+    options: Vec<&'a str>,
+    /// Positional arguments before `--`
+    operands: Vec<&'a str>,
+    /// Arguments after `--`
+    pathspecs: Vec<&'a str>,
+}
+
+impl<'a> GitOptions<'a> {
+    /// Split arguments, skipping the values of options that take one
+    /// (short options in `short_with_value`, long options in `long_with_value`).
+    fn new(args: &'a [String], short_with_value: &str, long_with_value: &[&str]) -> Self {
+        let mut parsed = Self {
+            options: Vec::new(),
+            operands: Vec::new(),
+            pathspecs: Vec::new(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--" {
+                parsed.pathspecs.extend(iter.by_ref().map(String::as_str));
+            } else if arg.starts_with("--") {
+                parsed.options.push(arg);
+                if long_with_value.contains(&arg.as_str()) {
+                    iter.next();
+                }
+            } else if arg.len() > 1 && arg.starts_with('-') {
+                // In a cluster (`-fdx`), an option taking a value consumes the rest
+                // of the cluster, or the next argument if it ends the cluster
+                let flags = &arg[1..];
+                let end = Self::value_start(flags, short_with_value);
+                parsed.options.push(&arg[..1 + end]);
+                if end == flags.len() && flags.ends_with(|c| short_with_value.contains(c)) {
+                    iter.next();
+                }
+            } else {
+                parsed.operands.push(arg);
+            }
+        }
+        parsed
+    }
+
+    /// Byte length of the flags in a short option cluster before any option value.
+    fn value_start(flags: &str, short_with_value: &str) -> usize {
+        flags
+            .char_indices()
+            .find(|(_, c)| short_with_value.contains(*c))
+            .map_or(flags.len(), |(i, c)| i + c.len_utf8())
+    }
+
+    /// Check if a short flag (possibly in a cluster) or a long option is set.
+    fn has(&self, short: Option<char>, long: &str) -> bool {
+        self.options
+            .iter()
+            .any(|option| match option.strip_prefix("--") {
+                Some(name) => {
+                    !long.is_empty()
+                        && (*option == long
+                            || name.split_once('=').is_some_and(|(n, _)| n == &long[2..]))
+                }
+                None => short.is_some_and(|flag| option[1..].contains(flag)),
+            })
+    }
+
+    fn operands(&self) -> &[&'a str] {
+        &self.operands
+    }
+
+    fn pathspecs(&self) -> &[&'a str] {
+        &self.pathspecs
+    }
+}
+
+/// Filter for blocking dangerous git operations.
+///
+/// Global git options (`git -C dir -c k=v push -f`) are skipped to find the subcommand.
+pub struct GitFilter {
+    /// Blocked operations with their messages
+    operations: Vec<(GitOperation, String)>,
+}
+
+impl GitFilter {
+    /// Create a new GitFilter that blocks no operations.
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+        }
+    }
+
+    /// Block an operation if `enabled`, with an optional custom message.
+    pub fn with_operation(
+        mut self,
+        operation: GitOperation,
+        enabled: bool,
+        custom_message: Option<String>,
+    ) -> Self {
+        if enabled {
+            self.operations.push((
+                operation,
+                custom_message.unwrap_or_else(|| operation.default_message().to_string()),
+            ));
+        }
+        self
+    }
+
+    /// Find the message of the first blocked operation performed by the parsed commands.
+    fn blocked_operation(&self, commands: &[ParsedCommand]) -> Option<&str> {
+        commands
+            .iter()
+            .filter(|cmd| cmd.name() == "git")
+            .find_map(|cmd| {
+                let args = cmd.args();
+                let i = git_subcommand_index(args)?;
+                self.operations
+                    .iter()
+                    .find(|(operation, _)| operation.matches(&args[i], &args[i + 1..]))
+                    .map(|(_, message)| message.as_str())
+            })
+    }
+}
+
+impl Default for GitFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Filter for GitFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.blocked_operation(ctx.commands()).is_some()
+    }
+
+    fn execute(&self, ctx: &FilterContext) -> Decision {
+        Decision::Block {
+            message: self
+                .blocked_operation(ctx.commands())
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        22 // After rm; owns git clean so that git_clean_block applies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+
+    const ALL_OPERATIONS: &[GitOperation] = &[
+        GitOperation::ForcePush,
+        GitOperation::ResetHard,
+        GitOperation::Clean,
+        GitOperation::DiscardChanges,
+        GitOperation::BranchDelete,
+        GitOperation::StashDrop,
+        GitOperation::FilterBranch,
+        GitOperation::NoVerify,
+    ];
+
+    fn filter_with(operations: &[GitOperation]) -> GitFilter {
+        operations
+            .iter()
+            .fold(GitFilter::new(), |filter, operation| {
+                filter.with_operation(*operation, true, None)
+            })
+    }
+
+    /// The operation blocked for a command, with all operations enabled.
+    fn blocked(command: &str) -> Option<GitOperation> {
+        let commands = ShellParser::new().parse(command);
+        ALL_OPERATIONS.iter().copied().find(|operation| {
+            filter_with(&[*operation])
+                .blocked_operation(&commands)
+                .is_some()
+        })
+    }
+
+    #[test]
+    fn test_force_push() {
+        assert_eq!(blocked("git push --force"), Some(GitOperation::ForcePush));
+        assert_eq!(
+            blocked("git push -f origin main"),
+            Some(GitOperation::ForcePush)
+        );
+        assert_eq!(
+            blocked("git push -uf origin main"),
+            Some(GitOperation::ForcePush)
+        );
+        assert_eq!(
+            blocked("git push origin +main"),
+            Some(GitOperation::ForcePush)
+        );
+        assert_eq!(
+            blocked("git -C repo -c push.default=current push -f"),
+            Some(GitOperation::ForcePush)
+        );
+        assert_eq!(blocked("git push"), None);
+        assert_eq!(blocked("git push --force-with-lease origin main"), None);
+        assert_eq!(blocked("git push -o ci.skip origin main"), None);
+    }
+
+    #[test]
+    fn test_discarding_changes() {
+        assert_eq!(
+            blocked("git reset --hard HEAD~1"),
+            Some(GitOperation::ResetHard)
+        );
+        assert_eq!(blocked("git clean -fdx"), Some(GitOperation::Clean));
+        assert_eq!(blocked("git clean --force -d"), Some(GitOperation::Clean));
+        assert_eq!(blocked("git -C repo clean -xf"), Some(GitOperation::Clean));
+        assert_eq!(
+            blocked("git checkout -- ."),
+            Some(GitOperation::DiscardChanges)
+        );
+        assert_eq!(
+            blocked("git checkout ."),
+            Some(GitOperation::DiscardChanges)
+        );
+        assert_eq!(
+            blocked("git checkout HEAD -- src/main.rs"),
+            Some(GitOperation::DiscardChanges)
+        );
+        assert_eq!(
+            blocked("git checkout HEAD tracked"),
+            Some(GitOperation::DiscardChanges)
+        );
+        assert_eq!(
+            blocked("git checkout main src/lib.rs"),
+            Some(GitOperation::DiscardChanges)
+        );
+        assert_eq!(blocked("git restore ."), Some(GitOperation::DiscardChanges));
+        assert_eq!(
+            blocked("git restore --staged --worktree src"),
+            Some(GitOperation::DiscardChanges)
+        );
+
+        assert_eq!(blocked("git reset --soft HEAD~1"), None);
+        assert_eq!(blocked("git clean -n"), None);
+        assert_eq!(blocked("git clean -fn"), None);
+        assert_eq!(blocked("git clean -fdn"), None);
+        assert_eq!(blocked("git checkout main"), None);
+        assert_eq!(blocked("git checkout -b feature"), None);
+        assert_eq!(blocked("git checkout -b feature origin/main"), None);
+        assert_eq!(blocked("git restore --staged ."), None);
+        assert_eq!(blocked("git restore -S src"), None);
+    }
+
+    #[test]
+    fn test_history_and_branches() {
+        assert_eq!(
+            blocked("git branch -D feature"),
+            Some(GitOperation::BranchDelete)
+        );
+        assert_eq!(
+            blocked("git branch --delete --force feature"),
+            Some(GitOperation::BranchDelete)
+        );
+        assert_eq!(
+            blocked("git branch -df feature"),
+            Some(GitOperation::BranchDelete)
+        );
+        assert_eq!(blocked("git stash drop"), Some(GitOperation::StashDrop));
+        assert_eq!(blocked("git stash clear"), Some(GitOperation::StashDrop));
+        assert_eq!(
+            blocked("git filter-branch --tree-filter 'rm x' HEAD"),
+            Some(GitOperation::FilterBranch)
+        );
+        assert_eq!(
+            blocked("git commit --no-verify -m wip"),
+            Some(GitOperation::NoVerify)
+        );
+        assert_eq!(blocked("git commit -anm wip"), Some(GitOperation::NoVerify));
+
+        assert_eq!(blocked("git branch -d feature"), None);
+        assert_eq!(blocked("git stash pop"), None);
+        assert_eq!(blocked("git stash"), None);
+        assert_eq!(blocked("git commit -m -n"), None);
+        assert_eq!(blocked("git commit -mn"), None);
+        assert_eq!(blocked("echo git push -f"), None);
+    }
+
+    #[test]
+    fn test_nested_and_wrapped_commands() {
+        assert_eq!(
+            blocked("cd repo && sudo git push -f"),
+            Some(GitOperation::ForcePush)
+        );
+        assert_eq!(
+            blocked("bash -c 'git reset --hard'"),
+            Some(GitOperation::ResetHard)
+        );
+    }
+
+    #[test]
+    fn test_disabled_operations_and_messages() {
+        let commands = ShellParser::new().parse("git reset --hard");
+        let filter = GitFilter::new()
+            .with_operation(GitOperation::ResetHard, false, None)
+            .with_operation(GitOperation::ForcePush, true, None);
+        assert!(filter.blocked_operation(&commands).is_none());
+
+        let filter = GitFilter::new().with_operation(
+            GitOperation::ResetHard,
+            true,
+            Some("Use git stash".to_string()),
+        );
+        assert_eq!(filter.blocked_operation(&commands), Some("Use git stash"));
+    }
+}
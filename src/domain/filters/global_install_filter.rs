@@ -0,0 +1,580 @@
+//! Global package installation filter implementation.
+
+use std::path::Path;
+
+use regex::Regex;
+
+use super::{glob_regex, Filter, FilterContext};
+use crate::domain::parser::{is_source_command, ParsedCommand};
+use crate::domain::Decision;
+
+/// Default message for blocking global installs, before the local equivalent.
+const DEFAULT_GLOBAL_INSTALL_MESSAGE: &str = "installs packages outside the project and changes the developer's machine. Configure global_install_allowed_packages in config.toml to allow specific packages.";
+
+/// Kinds of installs outside the project, each with its local equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Install {
+    /// Global JavaScript packages (`npm i -g`, `yarn global add`, `deno install -g`)
+    JavaScript,
+    /// User or system Python packages (`pip install --user`, `pip install` outside a virtualenv)
+    Python,
+    /// Python applications (`pipx install`, `uv tool install`)
+    PythonTool,
+    /// Cargo binaries (`cargo install`)
+    Cargo,
+    /// Go binaries (`go install`)
+    Go,
+    /// Ruby gems (`gem install`)
+    Ruby,
+    /// System packages (`brew install`, `apt-get install`)
+    System,
+}
+
+impl Install {
+    /// Suggested project-local equivalent.
+    fn suggestion(self) -> &'static str {
+        match self {
+            Self::JavaScript => "Add it to the project instead (npm install --save-dev <pkg>) and run it with npx.",
+            Self::Python => "Install it into a virtualenv instead (python3 -m venv .venv && .venv/bin/pip install <pkg>), or use uv add.",
+            Self::PythonTool => "Run it without installing instead (pipx run <app>, uvx <app>).",
+            Self::Cargo => "Add it to the project instead (cargo add <crate>), or ask the user to install the tool.",
+            Self::Go => "Run it without installing instead (go run <pkg>@<version>), or add it as a tool dependency (go get -tool <pkg>).",
+            Self::Ruby => "Add it to the Gemfile instead and run bundle install.",
+            Self::System => "Ask the user to install system packages.",
+        }
+    }
+}
+
+/// JavaScript package manager subcommands that install or remove packages
+const JS_INSTALL_SUBCOMMANDS: &[&str] = &[
+    "install",
+    "i",
+    "add",
+    "update",
+    "up",
+    "upgrade",
+    "uninstall",
+    "remove",
+    "rm",
+    "un",
+];
+
+/// System package managers
+const SYSTEM_MANAGERS: &[&str] = &[
+    "brew", "port", "apt", "apt-get", "aptitude", "dnf", "yum", "zypper", "apk", "snap", "winget",
+    "choco", "scoop",
+];
+
+/// System package manager subcommands that change installed packages
+const SYSTEM_SUBCOMMANDS: &[&str] = &[
+    "install",
+    "in",
+    "add",
+    "reinstall",
+    "upgrade",
+    "full-upgrade",
+    "dist-upgrade",
+    "dup",
+    "refresh",
+    "uninstall",
+    "remove",
+    "rm",
+    "del",
+    "erase",
+    "purge",
+    "autoremove",
+];
+
+/// Python interpreter options without a value, which can precede `-m` in one argument
+const PYTHON_FLAGS: &str = "bBdEhiIOPqsSuvVx";
+
+/// Options whose value is a separate argument, so it is not taken for a package name
+const OPTIONS_WITH_VALUES: &[&str] = &[
+    // npm, pnpm, yarn and bun
+    "--registry",
+    "--prefix",
+    "--filter",
+    // pip
+    "-r",
+    "--requirement",
+    "-c",
+    "--constraint",
+    "-i",
+    "--index-url",
+    "--extra-index-url",
+    "-f",
+    "--find-links",
+    "--python",
+    // cargo
+    "--version",
+    "--git",
+    "--branch",
+    "--tag",
+    "--rev",
+    "--path",
+    "--root",
+    "--features",
+    "-F",
+    "--target",
+    "-j",
+    "--jobs",
+    // apt and dnf
+    "-o",
+    "--option",
+    "-t",
+    "--target-release",
+];
+
+/// Filter for blocking installs that change the developer's machine instead of the project
+/// (`npm i -g`, `pip install --user`, `pip install` outside a virtualenv, `cargo install`,
+/// `go install`, `brew install`, `apt-get install`).
+///
+/// Installs whose packages all match an allowed glob pass.
+pub struct GlobalInstallFilter {
+    enabled: bool,
+    custom_message: Option<String>,
+    allowed_packages: Vec<Regex>,
+    /// The agent runs inside an activated virtualenv, so `pip install` stays in it
+    active_virtualenv: bool,
+}
+
+impl GlobalInstallFilter {
+    /// Create a new GlobalInstallFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            custom_message,
+            allowed_packages: Vec::new(),
+            active_virtualenv: false,
+        }
+    }
+
+    /// Allow installing packages matching any of the globs (`typescript`, `@types/*`, `cargo-*`).
+    pub fn with_allowed_packages(mut self, patterns: &[String]) -> Self {
+        self.allowed_packages.extend(
+            patterns
+                .iter()
+                .filter_map(|pattern| glob_regex(pattern).ok()),
+        );
+        self
+    }
+
+    /// Set whether the agent runs inside an activated virtualenv (`VIRTUAL_ENV` is set).
+    pub fn with_active_virtualenv(mut self, active: bool) -> Self {
+        self.active_virtualenv = active;
+        self
+    }
+
+    /// Find the first install outside the project whose packages are not all allowed.
+    fn find_global_install<'a>(
+        &self,
+        commands: &'a [ParsedCommand],
+        cwd: Option<&str>,
+    ) -> Option<(&'a ParsedCommand, Install)> {
+        // `source .venv/bin/activate && pip install x` installs into the virtualenv
+        let activates_virtualenv = commands.iter().any(|cmd| {
+            is_source_command(cmd.name())
+                && cmd
+                    .args()
+                    .first()
+                    .is_some_and(|path| path.ends_with("bin/activate"))
+        });
+
+        commands.iter().find_map(|cmd| {
+            let (install, packages) = Self::install(cmd)?;
+            if install == Install::Python
+                && !cmd.args().iter().any(|arg| arg == "--user")
+                && (self.active_virtualenv
+                    || activates_virtualenv
+                    || Self::runs_from_virtualenv(cmd, cwd))
+            {
+                return None;
+            }
+            let allowed = !packages.is_empty()
+                && packages.iter().all(|package| {
+                    self.allowed_packages
+                        .iter()
+                        .any(|pattern| pattern.is_match(package))
+                });
+            (!allowed).then_some((cmd, install))
+        })
+    }
+
+    /// Get the kind of install a command performs outside the project, with its package names.
+    fn install(cmd: &ParsedCommand) -> Option<(Install, Vec<String>)> {
+        Self::install_args(cmd.name(), cmd.args())
+    }
+
+    /// Get the kind of install a command name and its arguments perform, with the package names.
+    fn install_args(name: &str, args: &[String]) -> Option<(Install, Vec<String>)> {
+        let has = |flags: &[&str]| args.iter().any(|arg| flags.contains(&arg.as_str()));
+        let operands = operands(args);
+        let subcommand = operands.first().copied();
+        let packages = |skip: usize| {
+            operands
+                .iter()
+                .skip(skip)
+                .map(|package| package_name(package))
+                .collect::<Vec<_>>()
+        };
+
+        let install = match name {
+            "npm" | "pnpm" | "bun" => {
+                let global = has(&["-g", "--global", "--location=global"]);
+                (global && subcommand.is_some_and(|s| JS_INSTALL_SUBCOMMANDS.contains(&s)))
+                    .then_some((Install::JavaScript, 1))
+            }
+            "yarn" => match operands.as_slice() {
+                ["global", command, ..] if JS_INSTALL_SUBCOMMANDS.contains(command) => {
+                    Some((Install::JavaScript, 2))
+                }
+                _ => None,
+            },
+            "deno" => (subcommand == Some("install") && has(&["-g", "--global"]))
+                .then_some((Install::JavaScript, 1)),
+            "pip" | "pip3" => Self::pip_install(args, &operands, 0),
+            // `python -m pip ...` runs like `pip ...`
+            _ if name.starts_with("python") => {
+                return Self::install_args("pip", &args[pip_module_args(args)?..]);
+            }
+            "uv" => match operands.as_slice() {
+                ["pip", "install", ..] if has(&["--system"]) => Some((Install::Python, 2)),
+                ["tool", "install", ..] => Some((Install::PythonTool, 2)),
+                _ => None,
+            },
+            "pipx" => (subcommand == Some("install")).then_some((Install::PythonTool, 1)),
+            "cargo" => (subcommand == Some("install")).then_some((Install::Cargo, 1)),
+            "go" => (subcommand == Some("install")).then_some((Install::Go, 1)),
+            "gem" => (subcommand == Some("install")).then_some((Install::Ruby, 1)),
+            // pacman -S, -U and -R change packages; -Ss, -Si, -Sl, -Sg and -Sp only query.
+            // The operation may follow other options (`pacman --noconfirm -S foo`)
+            "pacman" => {
+                let short: String = args
+                    .iter()
+                    .filter(|arg| arg.starts_with('-') && !arg.starts_with("--"))
+                    .map(|arg| &arg[1..])
+                    .collect();
+                let sync = short.contains('S') || has(&["--sync"]);
+                let query = short.contains(['s', 'i', 'l', 'g', 'p'])
+                    || has(&["--search", "--info", "--list", "--groups", "--print"]);
+                let changes = short.contains(['U', 'R']) || has(&["--upgrade", "--remove"]);
+                (sync && !query || changes).then_some((Install::System, 0))
+            }
+            _ if SYSTEM_MANAGERS.contains(&name) => subcommand
+                .filter(|s| SYSTEM_SUBCOMMANDS.contains(s))
+                .map(|_| (Install::System, 1)),
+            _ => None,
+        };
+        install.map(|(install, skip)| (install, packages(skip)))
+    }
+
+    /// Get the install performed by pip arguments, given the operand index of `install`.
+    fn pip_install(args: &[String], operands: &[&str], index: usize) -> Option<(Install, usize)> {
+        // --target installs into a directory instead of site-packages
+        let targeted = args
+            .iter()
+            .any(|arg| arg == "-t" || arg.starts_with("--target"));
+        (operands.get(index) == Some(&"install") && !targeted)
+            .then_some((Install::Python, index + 1))
+    }
+
+    /// Check if a Python command runs by a path inside a virtualenv (`.venv/bin/pip`).
+    fn runs_from_virtualenv(cmd: &ParsedCommand, cwd: Option<&str>) -> bool {
+        if !cmd.command_word.contains(['/', '\\']) {
+            return false;
+        }
+        let path = Path::new(&cmd.command_word);
+        let path = match cwd {
+            Some(dir) if path.is_relative() => Path::new(dir).join(path),
+            _ => path.to_path_buf(),
+        };
+        // <venv>/bin/pip (Scripts\pip.exe on Windows) next to <venv>/pyvenv.cfg
+        path.parent()
+            .and_then(Path::parent)
+            .is_some_and(|venv| venv.join("pyvenv.cfg").is_file())
+    }
+
+    fn message(&self, cmd: &ParsedCommand, install: Install) -> String {
+        if let Some(message) = &self.custom_message {
+            return message.clone();
+        }
+        format!(
+            "🚫 `{}` {} {}",
+            cmd.name(),
+            DEFAULT_GLOBAL_INSTALL_MESSAGE,
+            install.suggestion()
+        )
+    }
+}
+
+/// Get the index of the first argument after `-m pip` in Python interpreter arguments,
+/// wherever the module option comes among the interpreter options (`-I -m pip`, `-Im pip`).
+fn pip_module_args(args: &[String]) -> Option<usize> {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        // Short options can be combined, the module option last (`-Im pip`, `-mpip`)
+        let flags = arg.strip_prefix('-').filter(|_| !arg.starts_with("--"));
+        match flags.and_then(|flags| flags.split_once('m')) {
+            Some((before, module)) if before.chars().all(|c| PYTHON_FLAGS.contains(c)) => {
+                return match module {
+                    "" => (args.get(i + 1)? == "pip").then_some(i + 2),
+                    module => (module == "pip").then_some(i + 1),
+                };
+            }
+            _ => {}
+        }
+        match arg.as_str() {
+            "-W" | "-X" => i += 2,
+            // Code or a script ends the interpreter options
+            _ if !arg.starts_with('-') || arg.starts_with("-c") => return None,
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Collect the positional arguments, skipping options and the values of known options.
+fn operands(args: &[String]) -> Vec<&str> {
+    let mut operands = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if OPTIONS_WITH_VALUES.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            operands.push(arg.as_str());
+        }
+    }
+    operands
+}
+
+/// Strip the version or source from a package argument
+/// (`typescript@5`, `@types/node@20`, `requests==2.31`, `golang.org/x/tools/gopls@latest`).
+fn package_name(package: &str) -> String {
+    let (scope, rest) = match package.strip_prefix('@') {
+        Some(rest) => ("@", rest),
+        None => ("", package),
+    };
+    let end = rest
+        .find(['@', '=', '<', '>', '~', '!', '[', ';', ' '])
+        .unwrap_or(rest.len());
+    format!("{}{}", scope, &rest[..end])
+}
+
+impl Filter for GlobalInstallFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled
+            && self
+                .find_global_install(ctx.commands(), ctx.input().cwd.as_deref())
+                .is_some()
+    }
+
+    fn execute(&self, ctx: &FilterContext) -> Decision {
+        match self.find_global_install(ctx.commands(), ctx.input().cwd.as_deref()) {
+            Some((cmd, install)) => Decision::Block {
+                message: self.message(cmd, install),
+            },
+            None => Decision::allow(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        35 // After dynamic commands, before interactive commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+    use std::fs;
+
+    fn installs(filter: &GlobalInstallFilter, command: &str, cwd: Option<&str>) -> Option<Install> {
+        filter
+            .find_global_install(&ShellParser::new().parse(command), cwd)
+            .map(|(_, install)| install)
+    }
+
+    fn install(command: &str) -> Option<Install> {
+        installs(&GlobalInstallFilter::new(true, None), command, None)
+    }
+
+    #[test]
+    fn test_global_installs() {
+        assert_eq!(install("npm i -g typescript"), Some(Install::JavaScript));
+        assert_eq!(
+            install("npm --global install eslint"),
+            Some(Install::JavaScript)
+        );
+        assert_eq!(install("pnpm add -g pnpm"), Some(Install::JavaScript));
+        assert_eq!(install("yarn global add serve"), Some(Install::JavaScript));
+        assert_eq!(
+            install("bun install --global vercel"),
+            Some(Install::JavaScript)
+        );
+        assert_eq!(
+            install("pip install --user requests"),
+            Some(Install::Python)
+        );
+        assert_eq!(install("pip3 install requests"), Some(Install::Python));
+        assert_eq!(
+            install("python3 -m pip install -U pip"),
+            Some(Install::Python)
+        );
+        assert_eq!(
+            install("python3 -I -m pip install requests"),
+            Some(Install::Python)
+        );
+        assert_eq!(
+            install("python -E -W ignore -m pip install requests"),
+            Some(Install::Python)
+        );
+        assert_eq!(
+            install("python3 -Im pip install requests"),
+            Some(Install::Python)
+        );
+        assert_eq!(
+            install("uv pip install --system ruff"),
+            Some(Install::Python)
+        );
+        assert_eq!(install("pipx install poetry"), Some(Install::PythonTool));
+        assert_eq!(install("cargo install ripgrep"), Some(Install::Cargo));
+        assert_eq!(
+            install("go install golang.org/x/tools/gopls@latest"),
+            Some(Install::Go)
+        );
+        assert_eq!(install("gem install rails"), Some(Install::Ruby));
+        assert_eq!(install("brew install jq"), Some(Install::System));
+        assert_eq!(
+            install("sudo apt-get install -y curl"),
+            Some(Install::System)
+        );
+        assert_eq!(install("pacman -Syu"), Some(Install::System));
+        assert_eq!(install("pacman --noconfirm -S foo"), Some(Install::System));
+        assert_eq!(install("pacman --needed --sync foo"), Some(Install::System));
+
+        // Project-local installs and queries
+        assert_eq!(install("npm install"), None);
+        assert_eq!(install("npm i -D typescript"), None);
+        assert_eq!(install("pnpm add zod"), None);
+        assert_eq!(install("yarn add react"), None);
+        assert_eq!(install("npm ls -g"), None);
+        assert_eq!(install("yarn global list"), None);
+        assert_eq!(install("pip list --user"), None);
+        assert_eq!(install("pip install --target vendor requests"), None);
+        assert_eq!(install("uv pip install requests"), None);
+        assert_eq!(install("python3 -m venv .venv"), None);
+        assert_eq!(install("python3 tool.py -m pip install requests"), None);
+        assert_eq!(install("cargo add serde"), None);
+        assert_eq!(install("cargo build"), None);
+        assert_eq!(install("go get github.com/x/y"), None);
+        assert_eq!(install("brew list"), None);
+        assert_eq!(install("apt-cache search curl"), None);
+        assert_eq!(install("pacman -Ss curl"), None);
+        assert_eq!(install("pacman --noconfirm -S -s curl"), None);
+    }
+
+    #[test]
+    fn test_pip_in_virtualenv() {
+        let dir = std::env::temp_dir().join(format!(
+            "claw-hooks-global-install-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(dir.join(".venv/bin")).unwrap();
+        fs::write(dir.join(".venv/pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+        let cwd = dir.to_str();
+        let filter = GlobalInstallFilter::new(true, None);
+
+        assert_eq!(
+            installs(&filter, ".venv/bin/pip install requests", cwd),
+            None
+        );
+        assert_eq!(
+            installs(&filter, ".venv/bin/python -m pip install requests", cwd),
+            None
+        );
+        assert_eq!(
+            installs(
+                &filter,
+                "source .venv/bin/activate && pip install requests",
+                cwd
+            ),
+            None
+        );
+        assert_eq!(
+            installs(&filter, "venv/bin/pip install requests", cwd),
+            Some(Install::Python)
+        );
+        assert_eq!(
+            installs(&filter, "/usr/bin/pip install requests", cwd),
+            Some(Install::Python)
+        );
+        assert_eq!(
+            installs(&filter, ".venv/bin/pip install --user requests", cwd),
+            Some(Install::Python)
+        );
+
+        // Nested commands are judged by their own command word
+        assert_eq!(
+            installs(&filter, "bash -c '.venv/bin/pip install requests'", cwd),
+            None
+        );
+        assert_eq!(
+            installs(
+                &filter,
+                "sh -c 'pip install requests; .venv/bin/pip list'",
+                cwd
+            ),
+            Some(Install::Python)
+        );
+
+        let filter = filter.with_active_virtualenv(true);
+        assert_eq!(installs(&filter, "pip install requests", cwd), None);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_allowed_packages() {
+        let filter = GlobalInstallFilter::new(true, None).with_allowed_packages(&[
+            "typescript".to_string(),
+            "@types/*".to_string(),
+            "cargo-*".to_string(),
+        ]);
+
+        assert_eq!(installs(&filter, "npm i -g typescript@5", None), None);
+        assert_eq!(
+            installs(&filter, "npm i -g @types/node@20 typescript", None),
+            None
+        );
+        assert_eq!(
+            installs(&filter, "cargo install --locked cargo-nextest", None),
+            None
+        );
+
+        assert_eq!(
+            installs(&filter, "npm i -g typescript eslint", None),
+            Some(Install::JavaScript)
+        );
+        assert_eq!(
+            installs(&filter, "npm i -g", None),
+            Some(Install::JavaScript)
+        );
+        assert_eq!(
+            installs(&filter, "cargo install --version 1.0 ripgrep", None),
+            Some(Install::Cargo)
+        );
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("typescript@5.4"), "typescript");
+        assert_eq!(package_name("@types/node@20"), "@types/node");
+        assert_eq!(package_name("requests>=2.31"), "requests");
+        assert_eq!(package_name("uvicorn[standard]"), "uvicorn");
+        assert_eq!(
+            package_name("golang.org/x/tools/gopls@latest"),
+            "golang.org/x/tools/gopls"
+        );
+    }
+}
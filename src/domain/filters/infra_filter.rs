@@ -0,0 +1,370 @@
+//! Container and cluster command filter implementation.
+
+use regex::Regex;
+
+use super::{glob_regex, Filter, FilterContext};
+use crate::domain::parser::ParsedCommand;
+use crate::domain::Decision;
+
+/// Default message for blocking destructive container and cluster operations.
+const DEFAULT_INFRA_MESSAGE: &str = "🚫 Destructive container/cluster operation blocked (docker prune, volume rm, compose down -v, kubectl delete ns, helm uninstall, terraform destroy). Ask the user to run it. Configure infra_allowed_contexts in config.toml to allow disposable contexts.";
+
+/// Docker and Compose options that take a separate value
+const DOCKER_OPTIONS_WITH_VALUES: &[&str] = &[
+    "-c",
+    "--context",
+    "-H",
+    "--host",
+    "--config",
+    "-l",
+    "--log-level",
+    "-f",
+    "--file",
+    "-p",
+    "--project-name",
+    "--profile",
+    "--env-file",
+    "--project-directory",
+];
+
+/// kubectl options that take a separate value
+const KUBECTL_OPTIONS_WITH_VALUES: &[&str] = &[
+    "-n",
+    "--namespace",
+    "--context",
+    "--cluster",
+    "--kubeconfig",
+    "--user",
+    "-s",
+    "--server",
+    "--token",
+    "--as",
+    "-l",
+    "--selector",
+    "--field-selector",
+    "-f",
+    "--filename",
+    "-o",
+    "--output",
+    "--grace-period",
+    "--timeout",
+];
+
+/// Helm options that take a separate value
+const HELM_OPTIONS_WITH_VALUES: &[&str] = &[
+    "-n",
+    "--namespace",
+    "--kube-context",
+    "--kubeconfig",
+    "--timeout",
+];
+
+/// Kubernetes resource kinds whose deletion destroys data or whole environments
+const PROTECTED_KINDS: &[&str] = &[
+    "ns",
+    "namespace",
+    "namespaces",
+    "pv",
+    "persistentvolume",
+    "persistentvolumes",
+    "pvc",
+    "persistentvolumeclaim",
+    "persistentvolumeclaims",
+    "crd",
+    "crds",
+    "customresourcedefinition",
+    "customresourcedefinitions",
+    "no",
+    "node",
+    "nodes",
+];
+
+/// Filter for blocking destructive container, cluster and infrastructure operations
+/// (`docker system prune`, `docker volume rm`, `docker compose down -v`,
+/// `kubectl delete ns`, `helm uninstall`, `terraform destroy`).
+///
+/// Operations that name an allowed context (`kubectl --context kind-dev delete ns x`)
+/// pass.
+pub struct InfraFilter {
+    enabled: bool,
+    message: String,
+    allowed_contexts: Vec<Regex>,
+}
+
+impl InfraFilter {
+    /// Create a new InfraFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            message: custom_message.unwrap_or_else(|| DEFAULT_INFRA_MESSAGE.to_string()),
+            allowed_contexts: Vec::new(),
+        }
+    }
+
+    /// Allow operations in contexts matching any of the globs (`kind-*`, `minikube`).
+    ///
+    /// Matched against the `--context` of docker and kubectl, the `--kube-context` of helm,
+    /// and the `DOCKER_CONTEXT`/`TF_WORKSPACE` assignments of the command.
+    pub fn with_allowed_contexts(mut self, patterns: &[String]) -> Self {
+        self.allowed_contexts.extend(
+            patterns
+                .iter()
+                .filter_map(|pattern| glob_regex(pattern).ok()),
+        );
+        self
+    }
+
+    /// Check if any parsed command performs a destructive operation outside an allowed context.
+    fn contains_destructive_operation(&self, commands: &[ParsedCommand]) -> bool {
+        commands.iter().any(|cmd| {
+            Self::is_destructive(cmd)
+                && !Self::context(cmd).is_some_and(|context| {
+                    self.allowed_contexts
+                        .iter()
+                        .any(|pattern| pattern.is_match(context))
+                })
+        })
+    }
+
+    /// Check if a command performs a destructive operation.
+    fn is_destructive(cmd: &ParsedCommand) -> bool {
+        let args = cmd.args();
+        match cmd.name() {
+            "docker" | "podman" | "nerdctl" => {
+                let operands = operands(args, DOCKER_OPTIONS_WITH_VALUES);
+                match operands.as_slice() {
+                    ["system", "prune", ..] => true,
+                    ["volume", "rm" | "remove" | "prune", ..] => true,
+                    ["compose", "down", ..] => removes_volumes(args),
+                    _ => false,
+                }
+            }
+            "docker-compose" | "podman-compose" => {
+                operands(args, DOCKER_OPTIONS_WITH_VALUES).first() == Some(&"down")
+                    && removes_volumes(args)
+            }
+            "kubectl" | "oc" => {
+                let operands = operands(args, KUBECTL_OPTIONS_WITH_VALUES);
+                let Some((&"delete", resources)) = operands.split_first() else {
+                    return false;
+                };
+                has_flag(args, Some('A'), &["--all", "--all-namespaces"])
+                    || resources.iter().any(|resource| {
+                        // `ns`, `ns/name`, `pvc,pv`, `namespace.v1`
+                        resource
+                            .split(',')
+                            .filter_map(|kind| kind.split(['/', '.']).next())
+                            .any(|kind| {
+                                PROTECTED_KINDS.contains(&kind.to_ascii_lowercase().as_str())
+                            })
+                    })
+            }
+            "helm" => matches!(
+                operands(args, HELM_OPTIONS_WITH_VALUES).first(),
+                Some(&("uninstall" | "delete" | "del" | "un"))
+            ),
+            "terraform" | "tofu" | "terragrunt" => match operands(args, &[]).as_slice() {
+                ["destroy", ..] | ["run-all" | "run", "destroy", ..] => true,
+                ["apply", ..] => has_flag(args, None, &["-destroy", "--destroy"]),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Get the context a command runs against, if it names one.
+    fn context(cmd: &ParsedCommand) -> Option<&str> {
+        let env = |name: &str| {
+            cmd.env
+                .iter()
+                .rev()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let args = cmd.args();
+        match cmd.name() {
+            "docker" | "podman" | "nerdctl" | "docker-compose" | "podman-compose" => {
+                option_value(args, &["--context", "-c"]).or_else(|| env("DOCKER_CONTEXT"))
+            }
+            "kubectl" | "oc" => option_value(args, &["--context"]),
+            "helm" => option_value(args, &["--kube-context"]),
+            "terraform" | "tofu" | "terragrunt" => env("TF_WORKSPACE"),
+            _ => None,
+        }
+    }
+}
+
+/// Collect the positional arguments, skipping options and the values of `options_with_values`.
+fn operands<'a>(args: &'a [String], options_with_values: &[&str]) -> Vec<&'a str> {
+    let mut operands = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if options_with_values.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            operands.push(arg.as_str());
+        }
+    }
+    operands
+}
+
+/// Get the value of an option given as `--name value` or `--name=value`.
+/// The last occurrence wins, as it does for the CLIs' flag parsers.
+fn option_value<'a>(args: &'a [String], names: &[&str]) -> Option<&'a str> {
+    let mut value = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if names.contains(&arg.as_str()) {
+            value = iter.next().map(String::as_str);
+        } else if let Some(inline) = names.iter().find_map(|name| {
+            arg.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+        }) {
+            value = Some(inline);
+        }
+    }
+    value
+}
+
+/// Check if a short flag (possibly combined, `-af`) or a long flag is set.
+fn has_flag(args: &[String], short: Option<char>, long: &[&str]) -> bool {
+    args.iter().any(|arg| {
+        long.contains(&arg.as_str())
+            || short.is_some_and(|flag| {
+                arg.len() > 1
+                    && arg.starts_with('-')
+                    && !arg.starts_with("--")
+                    && arg.contains(flag)
+            })
+    })
+}
+
+/// Check if `compose down` arguments remove named volumes.
+fn removes_volumes(args: &[String]) -> bool {
+    has_flag(args, Some('v'), &["--volumes"])
+}
+
+impl Filter for InfraFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && self.contains_destructive_operation(ctx.commands())
+    }
+
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
+        Decision::Block {
+            message: self.message.clone(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        24 // After rm and git, which have more specific messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+
+    fn blocked(filter: &InfraFilter, command: &str) -> bool {
+        filter.contains_destructive_operation(&ShellParser::new().parse(command))
+    }
+
+    fn contains_destructive_operation(command: &str) -> bool {
+        blocked(&InfraFilter::new(true, None), command)
+    }
+
+    #[test]
+    fn test_destructive_container_operations() {
+        assert!(contains_destructive_operation("docker system prune -af"));
+        assert!(contains_destructive_operation("docker volume rm pgdata"));
+        assert!(contains_destructive_operation("docker volume prune -f"));
+        assert!(contains_destructive_operation("docker compose down -v"));
+        assert!(contains_destructive_operation(
+            "docker compose -f dev.yml down --volumes"
+        ));
+        assert!(contains_destructive_operation("docker-compose down -v"));
+        assert!(contains_destructive_operation("sudo podman system prune"));
+
+        assert!(!contains_destructive_operation("docker compose down"));
+        assert!(!contains_destructive_operation("docker volume ls"));
+        assert!(!contains_destructive_operation("docker ps -a"));
+        assert!(!contains_destructive_operation(
+            "docker run -v data:/data app"
+        ));
+    }
+
+    #[test]
+    fn test_destructive_cluster_operations() {
+        assert!(contains_destructive_operation("kubectl delete ns staging"));
+        assert!(contains_destructive_operation(
+            "kubectl --context prod delete namespace/staging"
+        ));
+        assert!(contains_destructive_operation(
+            "kubectl delete pvc,pods data"
+        ));
+        assert!(contains_destructive_operation("kubectl delete pods --all"));
+        assert!(contains_destructive_operation(
+            "kubectl -n app delete pvc data"
+        ));
+        assert!(contains_destructive_operation("helm uninstall api"));
+        assert!(contains_destructive_operation("helm -n app delete api"));
+        assert!(contains_destructive_operation(
+            "terraform destroy -auto-approve"
+        ));
+        assert!(contains_destructive_operation(
+            "terraform -chdir=infra apply -destroy"
+        ));
+        assert!(contains_destructive_operation("terragrunt run-all destroy"));
+
+        assert!(!contains_destructive_operation("kubectl delete pod api-1"));
+        assert!(!contains_destructive_operation("kubectl get ns"));
+        assert!(!contains_destructive_operation(
+            "kubectl -n delete get pods"
+        ));
+        assert!(!contains_destructive_operation("helm list"));
+        assert!(!contains_destructive_operation("terraform plan -destroy"));
+        assert!(!contains_destructive_operation("terraform apply"));
+    }
+
+    #[test]
+    fn test_allowed_contexts() {
+        let filter = InfraFilter::new(true, None)
+            .with_allowed_contexts(&["kind-*".to_string(), "minikube".to_string()]);
+
+        assert!(!blocked(&filter, "kubectl --context kind-dev delete ns x"));
+        assert!(!blocked(&filter, "kubectl delete ns x --context=minikube"));
+        assert!(!blocked(
+            &filter,
+            "helm --kube-context kind-ci uninstall api"
+        ));
+        assert!(!blocked(
+            &filter,
+            "DOCKER_CONTEXT=kind-a docker system prune -f"
+        ));
+
+        assert!(blocked(&filter, "kubectl delete ns x"));
+        assert!(blocked(
+            &filter,
+            "kubectl --context prod-kind-dev delete ns x"
+        ));
+        assert!(blocked(&filter, "helm uninstall api"));
+        assert!(blocked(&filter, "TF_WORKSPACE=prod terraform destroy"));
+
+        // The last context given is the one used
+        assert!(blocked(
+            &filter,
+            "kubectl --context=kind-x delete ns prod --context=prod"
+        ));
+        assert!(!blocked(
+            &filter,
+            "kubectl --context prod delete ns x --context kind-dev"
+        ));
+    }
+}
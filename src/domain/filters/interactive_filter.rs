@@ -0,0 +1,556 @@
+//! Interactive command filter implementation.
+
+use super::sql_filter::SQLITE_OPTIONS_WITH_VALUES;
+use super::{Filter, FilterContext};
+use crate::domain::parser::{git_subcommand_index, normalize_command_name, ParsedCommand};
+use crate::domain::Decision;
+
+/// Why a command hangs the session, with the non-interactive alternative to suggest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hang {
+    Editor,
+    Pager,
+    Monitor,
+    Follow,
+    Repl,
+    Server,
+    GitPrompt,
+    /// A command listed in `interactive_commands`
+    Configured,
+}
+
+impl Hang {
+    /// Suggested non-interactive alternative.
+    fn suggestion(self) -> &'static str {
+        match self {
+            Self::Editor => "Edit files with your file editing tools, or with sed or patch.",
+            Self::Pager => "Use cat, head or grep instead, and git --no-pager for git output.",
+            Self::Monitor => "Take a snapshot instead (ps aux, top -b -n 1).",
+            Self::Follow => "Read the current output instead (tail -n 100), or stop following with a timeout (timeout 10 tail -f app.log).",
+            Self::Repl => "Pass the code with -c/-e, a heredoc or a script file instead of starting a REPL.",
+            Self::Server => "Run it in the background with a timeout (timeout 60 npm run dev > dev.log 2>&1 &), or run a command that exits (npm run build).",
+            Self::GitPrompt => "Pass the message with -m, set GIT_EDITOR=true (GIT_SEQUENCE_EDITOR for rebase -i), or stage paths instead of using -p/-i.",
+            Self::Configured => "It is listed in interactive_commands in config.toml; run a command that exits on its own instead.",
+        }
+    }
+
+    /// Check if the command stops on its own when run with a timeout or in the background.
+    fn runs_unattended(self) -> bool {
+        matches!(self, Self::Monitor | Self::Follow | Self::Server)
+    }
+}
+
+/// Full-screen editors
+const EDITORS: &[&str] = &[
+    "vi", "vim", "nvim", "view", "nano", "pico", "emacs", "micro", "hx", "joe",
+];
+
+/// Editor flags that run without a UI (`emacs --batch`, `vim -es`)
+const BATCH_EDITOR_FLAGS: &[&str] = &["--batch", "-batch", "-es", "-Es", "--headless"];
+
+/// Pagers, which wait for the user when they read from the terminal
+const PAGERS: &[&str] = &["less", "more", "most"];
+
+/// Full-screen monitors and commands that repeat until interrupted
+const MONITORS: &[&str] = &[
+    "top", "htop", "btop", "atop", "iotop", "iftop", "nethogs", "nmon", "glances", "watch",
+];
+
+/// Interpreters and shells that start a REPL without arguments
+const REPLS: &[&str] = &[
+    "python",
+    "python2",
+    "python3",
+    "pypy",
+    "pypy3",
+    "ipython",
+    "bpython",
+    "node",
+    "deno",
+    "ruby",
+    "irb",
+    "pry",
+    "php",
+    "perl",
+    "lua",
+    "luajit",
+    "ghci",
+    "R",
+    "julia",
+    "scala",
+    "iex",
+    "erl",
+    "redis-cli",
+    "mongosh",
+];
+
+/// Database clients, which start a shell unless given SQL to run
+const DATABASE_CLIENTS: &[&str] = &["psql", "mysql", "mariadb", "sqlite3"];
+
+/// Package manager scripts that start development servers or watchers
+const SERVER_SCRIPTS: &[&str] = &["dev", "start", "serve", "watch", "preview"];
+
+/// Commands that start servers or watchers, as argument vector prefixes
+const SERVERS: &[&[&str]] = &[
+    &["next", "dev"],
+    &["next", "start"],
+    &["nuxt", "dev"],
+    &["webpack", "serve"],
+    &["webpack-dev-server"],
+    &["ng", "serve"],
+    &["nodemon"],
+    &["http-server"],
+    &["live-server"],
+    &["rails", "server"],
+    &["rails", "s"],
+    &["python", "-m", "http.server"],
+    &["python3", "-m", "http.server"],
+    &["python", "manage.py", "runserver"],
+    &["python3", "manage.py", "runserver"],
+    &["flask", "run"],
+    &["uvicorn"],
+    &["gunicorn"],
+    &["php", "artisan", "serve"],
+    &["php", "-S"],
+    &["hugo", "server"],
+    &["jekyll", "serve"],
+    &["cargo", "watch"],
+    &["kubectl", "port-forward"],
+];
+
+/// Default message for blocking interactive commands, before the suggestion.
+const DEFAULT_INTERACTIVE_MESSAGE: &str =
+    "waits for input or never exits, which hangs the session.";
+
+/// Filter for blocking interactive and never-terminating commands that hang the agent
+/// (`vim`, `less`, `top`, `git rebase -i`, `npm run dev`, `tail -f`, bare `python`).
+///
+/// Follow-mode, monitoring and server commands pass when run with `timeout` or in
+/// the background.
+pub struct InteractiveFilter {
+    enabled: bool,
+    custom_message: Option<String>,
+    extra_commands: Vec<Vec<String>>,
+}
+
+impl InteractiveFilter {
+    /// Create a new InteractiveFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            custom_message,
+            extra_commands: Vec::new(),
+        }
+    }
+
+    /// Block additional commands, given as the words they start with (`ncdu`, `npm run storybook`).
+    pub fn with_commands(mut self, commands: &[String]) -> Self {
+        self.extra_commands.extend(
+            commands
+                .iter()
+                .map(|command| {
+                    command
+                        .split_whitespace()
+                        .map(String::from)
+                        .collect::<Vec<_>>()
+                })
+                .filter(|words| !words.is_empty()),
+        );
+        self
+    }
+
+    /// Find the first command that hangs the session, with the reason.
+    fn find_hanging_command<'a>(
+        &self,
+        commands: &'a [ParsedCommand],
+    ) -> Option<(&'a ParsedCommand, Hang)> {
+        commands.iter().find_map(|cmd| {
+            let hang = self.hang(cmd)?;
+            let unattended = cmd.context.background || cmd.wrappers.iter().any(|w| w == "timeout");
+            (!(hang.runs_unattended() && unattended)).then_some((cmd, hang))
+        })
+    }
+
+    /// Get why a command hangs the session, if it does.
+    fn hang(&self, cmd: &ParsedCommand) -> Option<Hang> {
+        let (name, args) = (cmd.name(), cmd.args());
+        // stdin is not the terminal when it is piped or redirected
+        let reads_terminal = !cmd.context.stdin_redirected
+            && cmd.context.pipeline_position.unwrap_or(0) == 0
+            && !cmd.wrappers.iter().any(|w| w == "xargs");
+        let has = |flags: &[&str]| args.iter().any(|arg| flags.contains(&arg.as_str()));
+
+        if self
+            .extra_commands
+            .iter()
+            .any(|prefix| starts_with(&cmd.argv, prefix))
+        {
+            return Some(Hang::Configured);
+        }
+        if SERVERS.iter().any(|prefix| starts_with(&cmd.argv, prefix)) {
+            return Some(Hang::Server);
+        }
+
+        match name {
+            _ if EDITORS.contains(&name) => (!has(BATCH_EDITOR_FLAGS)).then_some(Hang::Editor),
+            _ if PAGERS.contains(&name) => reads_terminal.then_some(Hang::Pager),
+            // top exits after -n (Linux) or -l (macOS) iterations
+            "top" => {
+                (!args.iter().any(|arg| is_short_flag(arg, &['n', 'l']))).then_some(Hang::Monitor)
+            }
+            _ if MONITORS.contains(&name) => Some(Hang::Monitor),
+            "tail" => args
+                .iter()
+                .any(|arg| arg.starts_with("--follow") || is_short_flag(arg, &['f', 'F']))
+                .then_some(Hang::Follow),
+            "journalctl" => has(&["-f", "--follow"]).then_some(Hang::Follow),
+            "kubectl" | "docker" | "podman" => {
+                let logs = args.iter().any(|arg| arg == "logs");
+                (logs && has(&["-f", "--follow"])).then_some(Hang::Follow)
+            }
+            "npm" | "pnpm" | "yarn" | "bun" => {
+                let mut operands = args
+                    .iter()
+                    .filter(|arg| !arg.starts_with('-'))
+                    .map(String::as_str);
+                let script = match operands.next() {
+                    Some("run" | "run-script") => operands.next(),
+                    script => script,
+                };
+                match script {
+                    Some(script) if SERVER_SCRIPTS.contains(&script) => Some(Hang::Server),
+                    Some("repl") if name == "bun" => Some(Hang::Repl),
+                    _ => None,
+                }
+            }
+            // `vite build` exits; `vite`, `vite dev` and `vite preview` serve
+            "vite" => match args.iter().find(|arg| !arg.starts_with('-')) {
+                None => Some(Hang::Server),
+                Some(command) => ["dev", "serve", "preview"]
+                    .contains(&command.as_str())
+                    .then_some(Hang::Server),
+            },
+            "git" => Self::git_prompt(cmd),
+            _ if DATABASE_CLIENTS.contains(&name) => {
+                (reads_terminal && !runs_sql(name, args)).then_some(Hang::Repl)
+            }
+            _ if REPLS.contains(&name) => {
+                let interactive = args.is_empty()
+                    || (name.starts_with("python") || name == "node")
+                        && has(&["-i", "--interactive"])
+                    || name == "php" && has(&["-a"])
+                    || name == "deno" && args.first().is_some_and(|arg| arg == "repl");
+                (interactive && reads_terminal).then_some(Hang::Repl)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if a git command opens an editor or prompts for input.
+    fn git_prompt(cmd: &ParsedCommand) -> Option<Hang> {
+        let args = cmd.args();
+        let i = git_subcommand_index(args)?;
+        let (subcommand, options) = (args[i].as_str(), &args[i + 1..]);
+        let has = |flags: &[&str]| options.iter().any(|arg| flags.contains(&arg.as_str()));
+        // Editors set for the command (`GIT_EDITOR=true git commit`, `git -c core.editor=true`)
+        let editor_set = |variables: &[&str], keys: &[&str]| {
+            cmd.env
+                .iter()
+                .any(|(name, _)| variables.contains(&name.as_str()))
+                || args[..i]
+                    .iter()
+                    .any(|arg| keys.iter().any(|key| arg.starts_with(&format!("{}=", key))))
+        };
+
+        let prompts = match subcommand {
+            "rebase" => {
+                has(&["-i", "--interactive"])
+                    && !editor_set(
+                        &["GIT_SEQUENCE_EDITOR", "GIT_EDITOR"],
+                        &["sequence.editor", "core.editor"],
+                    )
+            }
+            "add" => has(&["-i", "--interactive", "-p", "--patch", "-e", "--edit"]),
+            "checkout" | "reset" | "restore" | "stash" => has(&["-p", "--patch"]),
+            "commit" => {
+                let has_message = options.iter().any(|arg| {
+                    ["--message", "--file", "--reuse-message", "--fixup"]
+                        .iter()
+                        .any(|flag| arg.starts_with(flag))
+                        || arg == "--no-edit"
+                        || is_short_flag(arg, &['m', 'F', 'C'])
+                });
+                // `-e` opens the editor on the message given by `-m`, `-F` or `-C`
+                let edits = has(&["-e", "--edit"]);
+                (!has_message || edits)
+                    && !editor_set(&["GIT_EDITOR", "VISUAL", "EDITOR"], &["core.editor"])
+            }
+            _ => false,
+        };
+        prompts.then_some(Hang::GitPrompt)
+    }
+
+    fn message(&self, cmd: &ParsedCommand, hang: Hang) -> String {
+        if let Some(message) = &self.custom_message {
+            return message.clone();
+        }
+        let label = match cmd.name() {
+            "git" => git_subcommand_index(cmd.args())
+                .map_or("git".to_string(), |i| format!("git {}", cmd.args()[i])),
+            name => name.to_string(),
+        };
+        format!(
+            "🚫 `{}` {} {}",
+            label,
+            DEFAULT_INTERACTIVE_MESSAGE,
+            hang.suggestion()
+        )
+    }
+}
+
+/// Check if an argument vector starts with the given words (command name normalized).
+fn starts_with(argv: &[String], prefix: &[impl AsRef<str>]) -> bool {
+    argv.len() >= prefix.len()
+        && argv
+            .iter()
+            .zip(prefix)
+            .enumerate()
+            .all(|(i, (word, expected))| match i {
+                0 => normalize_command_name(expected.as_ref()) == *word,
+                _ => word == expected.as_ref(),
+            })
+}
+
+/// Check if a database client runs SQL given in its arguments, or exits without
+/// reading any (`psql -c`, `mysql -e`, `sqlite3 app.db 'SELECT 1'`, `--version`).
+fn runs_sql(name: &str, args: &[String]) -> bool {
+    // Short options with the value attached or separate (`-cSELECT 1`, `-c 'SELECT 1'`)
+    let short = |letters: &[char]| {
+        args.iter().any(|arg| {
+            !arg.starts_with("--")
+                && arg
+                    .strip_prefix('-')
+                    .and_then(|option| option.chars().next())
+                    .is_some_and(|letter| letters.contains(&letter))
+        })
+    };
+    let long = |flags: &[&str]| {
+        args.iter().any(|arg| {
+            flags
+                .iter()
+                .any(|flag| arg == flag || arg.starts_with(&format!("{}=", flag)))
+        })
+    };
+
+    match name {
+        "psql" => {
+            short(&['c', 'f', 'l', 'V'])
+                || long(&["--command", "--file", "--list", "--version", "--help"])
+        }
+        "mysql" | "mariadb" => short(&['e', 'V']) || long(&["--execute", "--version", "--help"]),
+        // sqlite3 [options] DATABASE [SQL...]
+        "sqlite3" => {
+            let mut operands = 0;
+            let mut iter = args.iter();
+            while let Some(arg) = iter.next() {
+                match arg.strip_prefix('-').map(|o| o.trim_start_matches('-')) {
+                    Some("version" | "help") => return true,
+                    Some(option)
+                        if SQLITE_OPTIONS_WITH_VALUES
+                            .contains(&format!("-{}", option).as_str()) =>
+                    {
+                        iter.next();
+                    }
+                    // -cmd and -init run SQL before the shell starts
+                    Some("cmd" | "init") => {
+                        iter.next();
+                    }
+                    Some(_) => {}
+                    None => operands += 1,
+                }
+            }
+            operands > 1
+        }
+        _ => false,
+    }
+}
+
+/// Check if an argument is a short option cluster containing any of the letters (`-fn100`).
+fn is_short_flag(arg: &str, letters: &[char]) -> bool {
+    arg.len() > 1
+        && arg.starts_with('-')
+        && !arg.starts_with("--")
+        && arg[1..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .any(|c| letters.contains(&c))
+}
+
+impl Filter for InteractiveFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && self.find_hanging_command(ctx.commands()).is_some()
+    }
+
+    fn execute(&self, ctx: &FilterContext) -> Decision {
+        match self.find_hanging_command(ctx.commands()) {
+            Some((cmd, hang)) => Decision::Block {
+                message: self.message(cmd, hang),
+            },
+            None => Decision::allow(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        40 // After the safety filters, whose messages matter more
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+
+    fn hangs(filter: &InteractiveFilter, command: &str) -> Option<Hang> {
+        filter
+            .find_hanging_command(&ShellParser::new().parse(command))
+            .map(|(_, hang)| hang)
+    }
+
+    fn hang(command: &str) -> Option<Hang> {
+        hangs(&InteractiveFilter::new(true, None), command)
+    }
+
+    #[test]
+    fn test_interactive_commands() {
+        assert_eq!(hang("vim src/main.rs"), Some(Hang::Editor));
+        assert_eq!(hang("cd docs && nano notes.md"), Some(Hang::Editor));
+        assert_eq!(hang("less README.md"), Some(Hang::Pager));
+        assert_eq!(hang("top"), Some(Hang::Monitor));
+        assert_eq!(hang("watch -n 1 ls"), Some(Hang::Monitor));
+        assert_eq!(hang("python3"), Some(Hang::Repl));
+        assert_eq!(hang("python -i script.py"), Some(Hang::Repl));
+        assert_eq!(hang("psql"), Some(Hang::Repl));
+
+        assert_eq!(hang("emacs --batch -l build.el"), None);
+        assert_eq!(hang("git log | less"), None);
+        assert_eq!(hang("top -b -n 1"), None);
+        assert_eq!(hang("top -l 1"), None);
+        assert_eq!(hang("python3 script.py"), None);
+        assert_eq!(hang("python3 -c 'print(1)'"), None);
+        assert_eq!(hang("echo 'print(1)' | python3"), None);
+        assert_eq!(hang("node < build.js"), None);
+        assert_eq!(hang("echo vim"), None);
+    }
+
+    #[test]
+    fn test_database_clients() {
+        assert_eq!(hang("psql -d app"), Some(Hang::Repl));
+        assert_eq!(hang("psql -h db -U app app"), Some(Hang::Repl));
+        assert_eq!(hang("mysql -u root app"), Some(Hang::Repl));
+        assert_eq!(hang("mariadb app"), Some(Hang::Repl));
+        assert_eq!(hang("sqlite3 app.db"), Some(Hang::Repl));
+        assert_eq!(hang("sqlite3 -cmd '.tables' app.db"), Some(Hang::Repl));
+        assert_eq!(hang("sudo -u postgres psql app"), Some(Hang::Repl));
+
+        // SQL from arguments, files or standard input
+        assert_eq!(hang("psql -d app -c 'SELECT 1'"), None);
+        assert_eq!(hang("psql -d app -f migrate.sql"), None);
+        assert_eq!(hang("psql --command='SELECT 1' app"), None);
+        assert_eq!(hang("psql -l"), None);
+        assert_eq!(hang("mysql -u root app -e 'SHOW TABLES'"), None);
+        assert_eq!(hang("mysql --execute='SHOW TABLES'"), None);
+        assert_eq!(hang("sqlite3 app.db 'SELECT 1'"), None);
+        assert_eq!(hang("sqlite3 -separator , app.db"), Some(Hang::Repl));
+        assert_eq!(hang("sqlite3 -separator , app.db .tables"), None);
+        assert_eq!(hang("sqlite3 --version"), None);
+        assert_eq!(hang("psql app < migrate.sql"), None);
+        assert_eq!(hang("echo 'SELECT 1' | mysql app"), None);
+    }
+
+    #[test]
+    fn test_repl_with_heredoc() {
+        assert_eq!(hang("python3 <<EOF\nprint(1)\nEOF"), None);
+        assert_eq!(hang("psql <<< 'SELECT 1'"), None);
+    }
+
+    #[test]
+    fn test_never_terminating_commands() {
+        assert_eq!(hang("tail -f app.log"), Some(Hang::Follow));
+        assert_eq!(hang("tail -fn100 app.log"), Some(Hang::Follow));
+        assert_eq!(hang("journalctl -u nginx --follow"), Some(Hang::Follow));
+        assert_eq!(hang("kubectl logs -f api"), Some(Hang::Follow));
+        assert_eq!(hang("npm run dev"), Some(Hang::Server));
+        assert_eq!(hang("npm start"), Some(Hang::Server));
+        assert_eq!(hang("pnpm dev --port 3000"), Some(Hang::Server));
+        assert_eq!(hang("yarn run watch"), Some(Hang::Server));
+        assert_eq!(hang("vite"), Some(Hang::Server));
+        assert_eq!(hang("vite preview --port 4173"), Some(Hang::Server));
+        assert_eq!(hang("python3 -m http.server 8000"), Some(Hang::Server));
+        assert_eq!(hang("cargo watch -x test"), Some(Hang::Server));
+
+        // Commands that exit
+        assert_eq!(hang("tail -n 100 app.log"), None);
+        assert_eq!(hang("npm run build"), None);
+        assert_eq!(hang("npm test"), None);
+        assert_eq!(hang("vite build --mode dev"), None);
+
+        // Run with a timeout or in the background
+        assert_eq!(hang("timeout 10 tail -f app.log"), None);
+        assert_eq!(hang("timeout 60 npm run dev"), None);
+        assert_eq!(hang("npm run dev > dev.log 2>&1 &"), None);
+    }
+
+    #[test]
+    fn test_git_prompts() {
+        assert_eq!(hang("git rebase -i HEAD~3"), Some(Hang::GitPrompt));
+        assert_eq!(hang("git add -p"), Some(Hang::GitPrompt));
+        assert_eq!(hang("git commit"), Some(Hang::GitPrompt));
+        assert_eq!(hang("git commit --amend"), Some(Hang::GitPrompt));
+        assert_eq!(hang("git commit -m x -e"), Some(Hang::GitPrompt));
+        assert_eq!(hang("git commit --edit -F msg.txt"), Some(Hang::GitPrompt));
+        assert_eq!(hang("GIT_EDITOR=true git commit -m x -e"), None);
+
+        assert_eq!(hang("git commit -m 'Fix build'"), None);
+        assert_eq!(hang("git commit -am 'Fix build'"), None);
+        assert_eq!(hang("git commit --amend --no-edit"), None);
+        assert_eq!(hang("git commit --message='Fix build'"), None);
+        assert_eq!(hang("GIT_EDITOR=true git commit"), None);
+        assert_eq!(hang("GIT_SEQUENCE_EDITOR=: git rebase -i HEAD~3"), None);
+        assert_eq!(hang("git -c sequence.editor=true rebase -i HEAD~3"), None);
+        assert_eq!(hang("git add src/main.rs"), None);
+        assert_eq!(hang("git --no-pager log"), None);
+    }
+
+    #[test]
+    fn test_extra_commands() {
+        let filter = InteractiveFilter::new(true, None)
+            .with_commands(&["ncdu".to_string(), "npm run storybook".to_string()]);
+
+        assert_eq!(hangs(&filter, "ncdu /"), Some(Hang::Configured));
+        assert_eq!(hangs(&filter, "npm run storybook"), Some(Hang::Configured));
+        // Configured commands are not known to stop on their own
+        assert_eq!(
+            hangs(&filter, "timeout 30 npm run storybook"),
+            Some(Hang::Configured)
+        );
+        assert_eq!(hangs(&filter, "ncdu / &"), Some(Hang::Configured));
+        assert_eq!(hangs(&filter, "npm run build-storybook"), None);
+
+        let commands = ShellParser::new().parse("ncdu /");
+        let (cmd, hang) = filter.find_hanging_command(&commands).unwrap();
+        let message = filter.message(cmd, hang);
+        assert!(message.contains("interactive_commands"));
+        assert!(!message.contains("background"));
+    }
+
+    #[test]
+    fn test_message() {
+        let filter = InteractiveFilter::new(true, None);
+        let commands = ShellParser::new().parse("git rebase -i HEAD~3");
+        let (cmd, hang) = filter.find_hanging_command(&commands).unwrap();
+        let message = filter.message(cmd, hang);
+        assert!(message.starts_with("🚫 `git rebase`"));
+        assert!(message.contains("GIT_SEQUENCE_EDITOR"));
+    }
+}
@@ -1,8 +1,8 @@
 //! Kill command filter implementation.
 
-use super::Filter;
-use crate::domain::parser::ShellParser;
-use crate::domain::{Decision, HookInput, ToolInput};
+use super::{Filter, FilterContext};
+use crate::domain::parser::ParsedCommand;
+use crate::domain::Decision;
 
 /// Default message for kill blocking (generic, can be customized via config).
 const DEFAULT_KILL_MESSAGE: &str = "🚫 kill/pkill/killall command blocked for safety. Use safe-kill: safe-kill <PID>, safe-kill -N <name>, or safe-kill -p <port>.";
@@ -30,64 +30,39 @@ impl KillFilter {
         "taskkill", // Windows
     ];
 
-    /// Check if any command in the string is a kill-related command.
-    fn contains_kill_command(command: &str) -> bool {
-        let mut parser = ShellParser::new();
-        let commands = parser.extract_commands(command);
-
+    /// Check if any parsed command is a kill-related command.
+    fn contains_kill_command(commands: &[ParsedCommand]) -> bool {
         // Check for direct kill commands (Unix and Windows)
         if commands
             .iter()
-            .any(|cmd| Self::KILL_COMMANDS.contains(&cmd.as_str()))
+            .any(|cmd| Self::KILL_COMMANDS.contains(&cmd.name()))
         {
             return true;
         }
 
         // Also check for xargs with kill commands
         // Pattern: "xargs kill", "xargs -0 kill", etc.
-        Self::contains_xargs_kill(command)
+        commands.iter().any(Self::is_xargs_kill)
     }
 
-    /// Check if the command contains xargs with a kill command.
-    fn contains_xargs_kill(command: &str) -> bool {
-        // Split by pipes and check each segment
-        for segment in command.split('|') {
-            let trimmed = segment.trim();
-            if trimmed.starts_with("xargs") {
-                // Check if any kill command is mentioned after xargs
-                let parts: Vec<&str> = trimmed.split_whitespace().collect();
-                for part in parts.iter().skip(1) {
-                    // Skip xargs flags
-                    if !part.starts_with('-') && Self::KILL_COMMANDS.contains(part) {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
+    /// Check if the command is xargs running a kill command.
+    fn is_xargs_kill(cmd: &ParsedCommand) -> bool {
+        cmd.name() == "xargs"
+            && cmd
+                .args()
+                .iter()
+                // Skip xargs flags
+                .any(|arg| !arg.starts_with('-') && Self::KILL_COMMANDS.contains(&arg.as_str()))
     }
 }
 
 impl Filter for KillFilter {
-    fn applies_to(&self, input: &HookInput) -> bool {
-        if !self.enabled {
-            return false;
-        }
-
-        // Only applies to Bash tool in PreToolUse event
-        if input.event != "PreToolUse" || input.tool_name != "Bash" {
-            return false;
-        }
-
-        // Extract command from tool input
-        if let ToolInput::Bash(bash) = &input.tool_input {
-            return Self::contains_kill_command(&bash.command);
-        }
-
-        false
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && Self::contains_kill_command(ctx.commands())
     }
 
-    fn execute(&self, _input: &HookInput) -> Decision {
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
         Decision::Block {
             message: self.message.clone(),
         }
@@ -101,32 +76,47 @@ impl Filter for KillFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::parser::ShellParser;
+
+    fn contains_kill_command(command: &str) -> bool {
+        KillFilter::contains_kill_command(&ShellParser::new().parse(command))
+    }
 
     #[test]
     fn test_contains_kill_command() {
         // Simple Unix commands
-        assert!(KillFilter::contains_kill_command("kill 1234"));
-        assert!(KillFilter::contains_kill_command("pkill node"));
-        assert!(KillFilter::contains_kill_command("killall python"));
-        assert!(!KillFilter::contains_kill_command("ls -la"));
-        assert!(!KillFilter::contains_kill_command("echo kill"));
+        assert!(contains_kill_command("kill 1234"));
+        assert!(contains_kill_command("pkill node"));
+        assert!(contains_kill_command("killall python"));
+        assert!(!contains_kill_command("ls -la"));
+        assert!(!contains_kill_command("echo kill"));
 
         // Windows commands
-        assert!(KillFilter::contains_kill_command("taskkill /PID 1234"));
-        assert!(KillFilter::contains_kill_command(
-            "taskkill /IM node.exe /F"
-        ));
+        assert!(contains_kill_command("taskkill /PID 1234"));
+        assert!(contains_kill_command("taskkill /IM node.exe /F"));
 
         // Piped commands
-        assert!(KillFilter::contains_kill_command(
-            "ps aux | grep node | xargs kill"
-        ));
-        assert!(KillFilter::contains_kill_command(
-            "pgrep node | xargs kill -9"
-        ));
+        assert!(contains_kill_command("ps aux | grep node | xargs kill"));
+        assert!(contains_kill_command("pgrep node | xargs kill -9"));
 
         // Chained commands
-        assert!(KillFilter::contains_kill_command("cd /tmp && kill 1234"));
-        assert!(KillFilter::contains_kill_command("echo test; pkill node"));
+        assert!(contains_kill_command("cd /tmp && kill 1234"));
+        assert!(contains_kill_command("echo test; pkill node"));
+
+        // Path-qualified, escaped and wrapped command names
+        assert!(contains_kill_command("/usr/bin/kill -9 1234"));
+        assert!(contains_kill_command("builtin kill 1234"));
+        assert!(contains_kill_command("\\pkill node"));
+        assert!(contains_kill_command("taskkill.exe /IM node.exe"));
+
+        // Inline interpreter code
+        assert!(contains_kill_command(
+            "python -c 'import os; os.kill(1234, 9)'"
+        ));
+        assert!(contains_kill_command("node -e 'process.kill(1234)'"));
+        assert!(contains_kill_command(
+            "python3 -c 'import subprocess; subprocess.run([\"pkill\", \"node\"])'"
+        ));
+        assert!(!contains_kill_command("python -c 'print(1)'"));
     }
 }
@@ -1,19 +1,108 @@
 //! Filter system for command filtering.
 
+use regex::Regex;
+
 mod chain;
+mod context;
 mod custom_filter;
-mod dd_filter;
+mod disk_filter;
+mod dynamic_filter;
 mod extension_filter;
 mod filter_trait;
+mod git_filter;
+mod global_install_filter;
+mod infra_filter;
+mod interactive_filter;
 mod kill_filter;
+mod obfuscation_filter;
+mod permission_filter;
+mod privilege_filter;
+mod redirect_filter;
+mod remote_script_filter;
 mod rm_filter;
+mod sql_filter;
 mod stop_filter;
+#[cfg(test)]
+mod test_repo;
+mod unparseable_filter;
 
 pub use chain::FilterChain;
+pub use context::FilterContext;
 pub use custom_filter::CustomCommandFilter;
-pub use dd_filter::DdFilter;
+pub use disk_filter::DiskFilter;
+pub use dynamic_filter::DynamicCommandFilter;
 pub use extension_filter::ExtensionHookFilter;
 pub use filter_trait::Filter;
+pub use git_filter::{GitFilter, GitOperation};
+pub use global_install_filter::GlobalInstallFilter;
+pub use infra_filter::InfraFilter;
+pub use interactive_filter::InteractiveFilter;
 pub use kill_filter::KillFilter;
+pub use obfuscation_filter::ObfuscationFilter;
+pub use permission_filter::PermissionFilter;
+pub use privilege_filter::PrivilegeFilter;
+pub use redirect_filter::RedirectFilter;
+pub use remote_script_filter::RemoteScriptFilter;
 pub use rm_filter::RmFilter;
+pub use sql_filter::{SqlClass, SqlFilter};
 pub use stop_filter::StopHookFilter;
+pub use unparseable_filter::UnparseableFilter;
+
+/// Compile a glob to a regex matching the whole text.
+///
+/// `*` and `?` do not match `/`, `**` matches any text and a trailing `/**`
+/// also matches the directory itself.
+pub(super) fn glob_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let (pattern, suffix) = match pattern.strip_suffix("/**") {
+        Some(dir) => (dir, "(?:/.*)?"),
+        None => (pattern, ""),
+    };
+
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.next_if_eq(&'*').is_some() => regex.push_str(".*"),
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push_str(suffix);
+    regex.push('$');
+
+    Regex::new(&regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_regex() {
+        let glob = glob_regex("kind-*").unwrap();
+        assert!(glob.is_match("kind-dev"));
+        assert!(!glob.is_match("prod-kind-dev"));
+
+        let glob = glob_regex("systemctl status *").unwrap();
+        assert!(glob.is_match("systemctl status nginx"));
+        assert!(!glob.is_match("systemctl status nginx/../x"));
+
+        let glob = glob_regex("/etc/*.conf").unwrap();
+        assert!(glob.is_match("/etc/resolv.conf"));
+        assert!(!glob.is_match("/etc/nginx/nginx.conf"));
+
+        let glob = glob_regex("/etc/**").unwrap();
+        assert!(glob.is_match("/etc"));
+        assert!(glob.is_match("/etc/nginx/nginx.conf"));
+        assert!(!glob.is_match("/etcetera"));
+
+        let glob = glob_regex("**/*.pem").unwrap();
+        assert!(glob.is_match("/srv/keys/server.pem"));
+        assert!(!glob.is_match("server.pem.bak"));
+
+        let glob = glob_regex("a.b?").unwrap();
+        assert!(glob.is_match("a.bc"));
+        assert!(!glob.is_match("axbc"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Obfuscation filter implementation.
+
+use super::{Filter, FilterContext};
+use crate::domain::parser::{ParsedCommand, ScriptSource, DECODERS};
+use crate::domain::{Decision, FilterAction};
+
+/// Default message for shells that run decoded text.
+const DEFAULT_OBFUSCATION_MESSAGE: &str = "🚫 Obfuscation detected: encoded text is decoded and run by a shell (base64 -d | sh, xxd -r | bash). Run the decoded command directly so it can be checked. Configure obfuscation_action in config.toml to change this behavior.";
+
+/// Filter for shells and interpreters that run text produced by a decoder
+/// (`echo cm0gLXJmIC4= | base64 -d | sh`, `eval "$(xxd -r -p x.hex)"`).
+///
+/// Payloads the parser can decode are also checked by the other filters, and
+/// the filter chain gives their blocks an obfuscation reason.
+pub struct ObfuscationFilter {
+    enabled: bool,
+    message: String,
+    action: FilterAction,
+}
+
+impl ObfuscationFilter {
+    /// Create a new ObfuscationFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            message: custom_message.unwrap_or_else(|| DEFAULT_OBFUSCATION_MESSAGE.to_string()),
+            action: FilterAction::Ask,
+        }
+    }
+
+    /// Set whether shells running decoded text are blocked, need user confirmation or pass.
+    pub fn with_action(mut self, action: FilterAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Check if any parsed command runs code produced by a decoder.
+    fn runs_decoded_text(commands: &[ParsedCommand]) -> bool {
+        commands.iter().any(|cmd| match cmd.script_source() {
+            // Piped into a shell or interpreter reading its program from stdin
+            Some(ScriptSource::Stdin) => cmd
+                .context
+                .upstream
+                .iter()
+                .any(|name| DECODERS.contains(&name.as_str())),
+            // Substituted into the code or the script path
+            Some(source) => {
+                source.is_substituted()
+                    && cmd
+                        .substitutions(commands)
+                        .any(|substitution| DECODERS.contains(&substitution.name()))
+            }
+            None => false,
+        })
+    }
+}
+
+impl Filter for ObfuscationFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && Self::runs_decoded_text(ctx.commands())
+    }
+
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
+        self.action.decision(self.message.clone())
+    }
+
+    fn priority(&self) -> u32 {
+        13 // After remote scripts, whose downloads may also be decoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+    use crate::domain::{BashInput, HookInput, ToolInput};
+
+    fn runs_decoded_text(command: &str) -> bool {
+        ObfuscationFilter::runs_decoded_text(&ShellParser::new().parse(command))
+    }
+
+    #[test]
+    fn test_decoded_text_piped_into_shells() {
+        assert!(runs_decoded_text("echo cm0gLXJmIC4= | base64 -d | sh"));
+        assert!(runs_decoded_text("echo bHMK | base64 --decode | bash"));
+        assert!(runs_decoded_text("cat payload.b64 | base64 -d | sudo sh"));
+        assert!(runs_decoded_text("xxd -r -p payload.hex | bash -s"));
+        assert!(runs_decoded_text("echo . fr- mr | rev | sh"));
+        assert!(runs_decoded_text("base64 -d payload | python3 -"));
+
+        assert!(!runs_decoded_text("echo cm0gLXJmIC4= | base64 -d"));
+        assert!(!runs_decoded_text("base64 -d payload > script.sh"));
+        assert!(!runs_decoded_text("echo 'ls' | sh"));
+        assert!(!runs_decoded_text("git rev-parse HEAD | xargs echo"));
+    }
+
+    #[test]
+    fn test_decoded_text_substituted_into_shells() {
+        assert!(runs_decoded_text(
+            "eval \"$(echo cm0gLXJmIC4= | base64 -d)\""
+        ));
+        assert!(runs_decoded_text("bash <(xxd -r -p payload.hex)"));
+
+        assert!(!runs_decoded_text("echo \"$(base64 -d token)\""));
+        assert!(!runs_decoded_text("bash deploy.sh \"$(base64 -d token)\""));
+    }
+
+    #[test]
+    fn test_action() {
+        let input = HookInput {
+            event: "PreToolUse".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: ToolInput::Bash(BashInput {
+                command: "echo bHMK | base64 -d | sh".to_string(),
+                timeout: None,
+            }),
+            session_id: None,
+            cwd: None,
+        };
+
+        let ctx = FilterContext::new(&input);
+
+        let filter = ObfuscationFilter::new(true, None);
+        assert!(filter.applies_to(&ctx));
+        assert!(matches!(filter.execute(&ctx), Decision::Ask { .. }));
+
+        let filter = ObfuscationFilter::new(true, None).with_action(FilterAction::Block);
+        assert!(matches!(filter.execute(&ctx), Decision::Block { .. }));
+
+        assert!(!ObfuscationFilter::new(false, None).applies_to(&ctx));
+    }
+}
@@ -0,0 +1,335 @@
+//! Permission and ownership change filter implementation.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{Filter, FilterContext};
+use crate::domain::parser::ParsedCommand;
+use crate::domain::workspace::Workspace;
+use crate::domain::Decision;
+
+/// Default message for blocking permission and ownership changes.
+const DEFAULT_PERMISSION_MESSAGE: &str = "🚫 Permission change blocked: modes beyond permission_max_mode (chmod 777, chmod o+w, chmod +s), recursive chmod/chown outside the project and chattr are not allowed. Use the narrowest mode on project files (chmod u+x script.sh).";
+
+/// Default most permissive mode a change may grant (rwxrwxr-x)
+const DEFAULT_MAX_MODE: u32 = 0o775;
+
+/// Characters of a glob, which cannot be resolved statically
+const GLOB_CHARS: &[char] = &['*', '?', '['];
+
+/// Filter for blocking permission and ownership changes that weaken the system
+/// (`chmod -R 777 .`, `chmod -R a+w /`, `chown -R` outside the project, `chattr`).
+///
+/// Modes that grant bits beyond the maximum mode are blocked anywhere; recursive changes
+/// are blocked unless every target is inside the project.
+pub struct PermissionFilter {
+    enabled: bool,
+    message: String,
+    max_mode: u32,
+}
+
+impl PermissionFilter {
+    /// Create a new PermissionFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            message: custom_message.unwrap_or_else(|| DEFAULT_PERMISSION_MESSAGE.to_string()),
+            max_mode: DEFAULT_MAX_MODE,
+        }
+    }
+
+    /// Set the most permissive mode a change may grant, in octal (`755` also blocks
+    /// group-writable modes). Invalid modes keep the default.
+    pub fn with_max_mode(mut self, max_mode: &str) -> Self {
+        if let Ok(mode) = u32::from_str_radix(max_mode, 8) {
+            self.max_mode = mode;
+        }
+        self
+    }
+
+    /// Commands that change the directory relative targets resolve against
+    const DIRECTORY_COMMANDS: &'static [&'static str] = &["cd", "pushd", "popd"];
+
+    /// Check if any parsed command changes permissions or ownership unsafely.
+    fn contains_unsafe_change(&self, commands: &[ParsedCommand], cwd: Option<&str>) -> bool {
+        let changes_directory = commands
+            .iter()
+            .any(|cmd| Self::DIRECTORY_COMMANDS.contains(&cmd.name()));
+
+        commands.iter().any(|cmd| {
+            let Some(change) = Change::parse(cmd) else {
+                return false;
+            };
+            if change
+                .granted
+                .is_some_and(|granted| granted & !self.max_mode != 0)
+            {
+                return true;
+            }
+            change.recursive && !Self::inside_project(&change.targets, cwd, changes_directory)
+        })
+    }
+
+    /// Check if all targets resolve to the project root or paths inside it.
+    fn inside_project(targets: &[&str], cwd: Option<&str>, changes_directory: bool) -> bool {
+        let Some(workspace) = Workspace::detect(cwd) else {
+            return false;
+        };
+        !targets.is_empty()
+            && targets.iter().all(|target| {
+                if changes_directory && !Path::new(target).is_absolute() {
+                    return false;
+                }
+                let Some(path) = resolve_target(&workspace, target) else {
+                    return false;
+                };
+                // Targets given on the command line are followed when they are symlinks
+                let path = fs::canonicalize(&path).unwrap_or(path);
+                path == workspace.root() || workspace.contains(&path)
+            })
+    }
+}
+
+/// A permission or ownership change parsed from a command.
+struct Change<'a> {
+    /// Permission bits the mode may grant, if the command sets a known mode
+    granted: Option<u32>,
+    /// Applies to directory trees (`-R`), or cannot be assessed at all (`chattr`)
+    recursive: bool,
+    targets: Vec<&'a str>,
+}
+
+impl<'a> Change<'a> {
+    /// Parse a chmod, chown, chgrp or chattr command.
+    fn parse(cmd: &'a ParsedCommand) -> Option<Self> {
+        let (option_letters, takes_operand) = match cmd.name() {
+            "chmod" => ("cfvR", true),
+            "chown" | "chgrp" => ("cfvhHLPR", true),
+            // Attributes such as immutable (+i) outlive the command; never allowed
+            "chattr" => {
+                return Some(Self {
+                    granted: None,
+                    recursive: true,
+                    targets: Vec::new(),
+                })
+            }
+            _ => return None,
+        };
+
+        let mut recursive = false;
+        let mut reference = false;
+        let mut operands = Vec::new();
+        let mut iter = cmd.args().iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--" => {
+                    operands.extend(iter.by_ref().map(String::as_str));
+                    break;
+                }
+                "--recursive" => recursive = true,
+                "--reference" => {
+                    reference = true;
+                    iter.next();
+                }
+                _ if arg.starts_with("--reference=") => reference = true,
+                _ if arg.starts_with("--") => {}
+                // `-R`, `-Rv`; chmod modes such as `-w` are operands
+                _ if arg.len() > 1
+                    && arg.starts_with('-')
+                    && arg[1..].chars().all(|c| option_letters.contains(c)) =>
+                {
+                    recursive |= arg.contains('R');
+                }
+                _ => operands.push(arg.as_str()),
+            }
+        }
+
+        // The mode or owner comes first unless it is copied from a reference file
+        let mut operands = operands.into_iter();
+        let first = if takes_operand && !reference {
+            operands.next()
+        } else {
+            None
+        };
+        let granted = match cmd.name() {
+            "chmod" => first.map(|mode| granted_bits(mode).unwrap_or(0o7777)),
+            _ => None,
+        };
+        Some(Self {
+            granted,
+            recursive,
+            targets: operands.collect(),
+        })
+    }
+}
+
+/// Get the permission bits a chmod mode may grant (`755`, `u+x,go-w`, `a=rwx`, `+t`).
+///
+/// Returns `None` if the argument is not a mode.
+fn granted_bits(mode: &str) -> Option<u32> {
+    if !mode.is_empty() && mode.len() <= 4 && mode.chars().all(|c| c.is_digit(8)) {
+        return u32::from_str_radix(mode, 8).ok();
+    }
+
+    let mut granted = 0;
+    for clause in mode.split(',') {
+        let who_end = clause.find(|c| !"ugoa".contains(c)).unwrap_or(clause.len());
+        let (who, actions) = clause.split_at(who_end);
+        let (user, group, other) = match who {
+            // Without a class, the umask (assumed 022) keeps write access to the owner
+            "" => (0o700, 0o050, 0o005),
+            _ if who.contains('a') => (0o700, 0o070, 0o007),
+            _ => (
+                if who.contains('u') { 0o700 } else { 0 },
+                if who.contains('g') { 0o070 } else { 0 },
+                if who.contains('o') { 0o007 } else { 0 },
+            ),
+        };
+        let classes = user | group | other;
+
+        if actions.is_empty() {
+            return None;
+        }
+        let mut chars = actions.chars().peekable();
+        while let Some(op) = chars.next() {
+            if !"+-=".contains(op) {
+                return None;
+            }
+            let mut bits = 0;
+            while let Some(&perm) = chars.peek().filter(|c| !"+-=".contains(**c)) {
+                bits |= match perm {
+                    'r' => 0o444 & classes,
+                    'w' => 0o222 & classes,
+                    'x' | 'X' => 0o111 & classes,
+                    's' => {
+                        (if user != 0 { 0o4000 } else { 0 }) | (if group != 0 { 0o2000 } else { 0 })
+                    }
+                    't' => 0o1000,
+                    // Copying another class's permissions may grant any of them
+                    'u' | 'g' | 'o' => 0o777 & classes,
+                    _ => return None,
+                };
+                chars.next();
+            }
+            if op != '-' {
+                granted |= bits;
+            }
+        }
+    }
+    Some(granted)
+}
+
+/// Resolve a target to an absolute path, using the directory a glob expands in.
+fn resolve_target(workspace: &Workspace, target: &str) -> Option<PathBuf> {
+    // `src/*.sh` only matches entries of `src`
+    let directory = match target.find(GLOB_CHARS) {
+        Some(i) => match target[..i].rfind('/') {
+            Some(slash) => &target[..=slash],
+            None => ".",
+        },
+        None => target,
+    };
+    workspace.resolve(directory)
+}
+
+impl Filter for PermissionFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && self.contains_unsafe_change(ctx.commands(), ctx.input().cwd.as_deref())
+    }
+
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
+        Decision::Block {
+            message: self.message.clone(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        21 // Between rm (20) and git (22)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::filters::test_repo::TestRepo;
+    use crate::domain::parser::ShellParser;
+
+    #[test]
+    fn test_granted_bits() {
+        assert_eq!(granted_bits("755"), Some(0o755));
+        assert_eq!(granted_bits("0777"), Some(0o777));
+        assert_eq!(granted_bits("u+x"), Some(0o100));
+        assert_eq!(granted_bits("+x"), Some(0o111));
+        assert_eq!(granted_bits("+w"), Some(0o200));
+        assert_eq!(granted_bits("a+w"), Some(0o222));
+        assert_eq!(granted_bits("go-w,u=rwx"), Some(0o700));
+        assert_eq!(granted_bits("o+rw"), Some(0o006));
+        assert_eq!(granted_bits("u+s"), Some(0o4000));
+        assert_eq!(granted_bits("+t"), Some(0o1000));
+        assert_eq!(granted_bits("g=u"), Some(0o070));
+        assert_eq!(granted_bits("-w"), Some(0));
+        assert_eq!(granted_bits("scripts"), None);
+        assert_eq!(granted_bits("u"), None);
+    }
+
+    #[test]
+    fn test_modes() {
+        let filter = PermissionFilter::new(true, None);
+        let blocked = |cmd: &str| {
+            filter.contains_unsafe_change(&ShellParser::new().parse(cmd), Some("/nonexistent"))
+        };
+
+        assert!(blocked("chmod 777 file"));
+        assert!(blocked("chmod o+w file"));
+        assert!(blocked("chmod a+rwx file"));
+        assert!(blocked("chmod u+s /usr/local/bin/tool"));
+        assert!(blocked("chmod 4755 tool"));
+        assert!(blocked("find . -type d -exec chmod 777 {} +"));
+        assert!(blocked("chattr +i config.yml"));
+
+        assert!(!blocked("chmod +x script.sh"));
+        assert!(!blocked("chmod 755 script.sh"));
+        assert!(!blocked("chmod u+w,go-w file"));
+        assert!(!blocked("chmod -w file"));
+        assert!(!blocked("chown app file"));
+        assert!(!blocked("lsattr file"));
+
+        let strict = PermissionFilter::new(true, None).with_max_mode("755");
+        let commands = ShellParser::new().parse("chmod 775 file");
+        assert!(strict.contains_unsafe_change(&commands, None));
+        assert!(!filter.contains_unsafe_change(&commands, None));
+    }
+
+    #[test]
+    fn test_recursive_changes() {
+        let repo = TestRepo::new("permission-filter-test");
+        repo.create_dir("scripts");
+        let cwd = repo.cwd();
+        let filter = PermissionFilter::new(true, None);
+        let blocked =
+            |cmd: &str| filter.contains_unsafe_change(&ShellParser::new().parse(cmd), cwd);
+
+        // Recursive changes inside the project are allowed
+        assert!(!blocked("chmod -R u+w ."));
+        assert!(!blocked("chmod -R 755 scripts"));
+        assert!(!blocked("chown -R app:app scripts build"));
+        assert!(!blocked("chmod -R +x scripts/*.sh"));
+
+        // World-writable modes are blocked even inside the project
+        assert!(blocked("chmod -R 777 ."));
+        assert!(blocked("chmod -R a+w scripts"));
+
+        // Recursive changes outside the project or with unresolvable targets
+        assert!(blocked("chmod -R 755 /"));
+        assert!(blocked("chown -R app /var/www"));
+        assert!(blocked("chgrp --recursive staff .."));
+        assert!(blocked("chown -Rh app ~/projects"));
+        assert!(blocked("chmod -R 755 $DIR"));
+        assert!(blocked("cd /etc && chmod -R 755 ."));
+        assert!(blocked("find . | xargs chown -R app"));
+
+        // Non-recursive changes outside the project only follow the mode limit
+        assert!(!blocked("chmod 644 /tmp/notes.txt"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Privilege escalation filter implementation.
+
+use regex::Regex;
+
+use super::{glob_regex, Filter, FilterContext};
+use crate::domain::parser::{normalize_command_name, ParsedCommand, WrapperRegistry};
+use crate::domain::Decision;
+
+/// Default message for blocking privilege escalation.
+const DEFAULT_PRIVILEGE_MESSAGE: &str = "🚫 Privilege escalation (sudo, doas, su, pkexec) is blocked. Ask the user to run the command. Configure privilege_allowed_commands in config.toml to allow specific commands.";
+
+/// Filter for blocking commands that run with elevated privileges, wherever they
+/// appear in the command (`sudo apt install x`, `bash -c 'doas reboot'`, `su -c x`).
+///
+/// Commands run through `sudo`, `doas`, `pkexec` or `run0` that match an allowed
+/// pattern (`sudo apt-get update`) pass.
+pub struct PrivilegeFilter {
+    enabled: bool,
+    message: String,
+    allowed_commands: Vec<Regex>,
+}
+
+impl PrivilegeFilter {
+    /// Create a new PrivilegeFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            message: custom_message.unwrap_or_else(|| DEFAULT_PRIVILEGE_MESSAGE.to_string()),
+            allowed_commands: Vec::new(),
+        }
+    }
+
+    /// Allow escalated commands matching any of the globs (`apt-get update`, `systemctl status *`).
+    ///
+    /// Matched against the command the wrapper runs, with its arguments separated by spaces.
+    pub fn with_allowed_commands(mut self, patterns: &[String]) -> Self {
+        self.allowed_commands.extend(
+            patterns
+                .iter()
+                .filter_map(|pattern| glob_regex(pattern).ok()),
+        );
+        self
+    }
+
+    /// Wrappers that run the command in their arguments as another user
+    const ESCALATION_WRAPPERS: &'static [&'static str] = &[
+        "sudo",   // Unix
+        "doas",   // BSD/Linux
+        "pkexec", // polkit
+        "run0",   // systemd
+    ];
+
+    /// Other commands that start privileged sessions or processes
+    const ESCALATION_COMMANDS: &'static [&'static str] = &[
+        "su",    // Unix
+        "runas", // Windows
+        "gsudo", // Windows
+    ];
+
+    /// Check if any parsed command escalates privileges for a command that is not allowed.
+    fn contains_escalation(&self, commands: &[ParsedCommand], wrappers: &WrapperRegistry) -> bool {
+        commands.iter().any(|cmd| {
+            let name = cmd.name();
+            if Self::ESCALATION_COMMANDS.contains(&name) {
+                return true;
+            }
+            if !Self::ESCALATION_WRAPPERS.contains(&name) {
+                return false;
+            }
+            // Without a command (`sudo -s`, `sudo -v`) the wrapper opens a root shell
+            // or caches credentials
+            let Some((_, argv)) = wrappers.split_command(name, cmd.args()) else {
+                return true;
+            };
+            !self.is_allowed(&argv)
+        })
+    }
+
+    /// Check if a command run with elevated privileges matches an allowed pattern.
+    fn is_allowed(&self, argv: &[String]) -> bool {
+        let Some((name, args)) = argv.split_first() else {
+            return false;
+        };
+        let command = std::iter::once(normalize_command_name(name))
+            .chain(args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.allowed_commands
+            .iter()
+            .any(|pattern| pattern.is_match(&command))
+    }
+}
+
+impl Filter for PrivilegeFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && self.contains_escalation(ctx.commands(), ctx.wrappers())
+    }
+
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
+        Decision::Block {
+            message: self.message.clone(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        5 // Before everything else: escalation is blocked whatever the command does
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::{ShellParser, Wrapper};
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    fn blocked(filter: &PrivilegeFilter, command: &str) -> bool {
+        filter.contains_escalation(
+            &ShellParser::new().parse(command),
+            &WrapperRegistry::default(),
+        )
+    }
+
+    fn contains_escalation(command: &str) -> bool {
+        blocked(&PrivilegeFilter::new(true, None), command)
+    }
+
+    #[test]
+    fn test_contains_escalation() {
+        assert!(contains_escalation("sudo apt install nginx"));
+        assert!(contains_escalation("sudo -u postgres psql"));
+        assert!(contains_escalation("doas reboot"));
+        assert!(contains_escalation("pkexec --user root make install"));
+        assert!(contains_escalation("su -c 'make install'"));
+        assert!(contains_escalation("su - root"));
+        assert!(contains_escalation("sudo -s"));
+        assert!(contains_escalation("runas /user:Administrator cmd"));
+        assert!(contains_escalation("/usr/bin/sudo make install"));
+
+        // Nested in other commands
+        assert!(contains_escalation("cd /srv && sudo make install"));
+        assert!(contains_escalation("bash -c 'sudo apt update'"));
+        assert!(contains_escalation("echo y | sudo tee /etc/motd"));
+        assert!(contains_escalation("nohup sudo ./server"));
+
+        assert!(!contains_escalation("echo sudo"));
+        assert!(!contains_escalation("apt list --installed"));
+        assert!(!contains_escalation("grep -r 'sudo ' docs"));
+        assert!(!contains_escalation("git commit -m 'run as su'"));
+    }
+
+    #[test]
+    fn test_allowed_commands() {
+        let filter = PrivilegeFilter::new(true, None).with_allowed_commands(&[
+            "apt-get update".to_string(),
+            "systemctl status *".to_string(),
+        ]);
+
+        assert!(!blocked(&filter, "sudo apt-get update"));
+        assert!(!blocked(&filter, "sudo /usr/bin/apt-get update"));
+        assert!(!blocked(&filter, "sudo -u root systemctl status nginx"));
+        assert!(!blocked(&filter, "doas systemctl status sshd"));
+
+        assert!(blocked(
+            &filter,
+            "sudo apt-get update && sudo apt-get upgrade"
+        ));
+        assert!(blocked(&filter, "sudo systemctl restart nginx"));
+        assert!(blocked(&filter, "sudo -s"));
+        assert!(blocked(&filter, "su -c 'apt-get update'"));
+    }
+
+    #[test]
+    fn test_allowed_commands_use_configured_wrappers() {
+        let filter =
+            PrivilegeFilter::new(true, None).with_allowed_commands(&["make install".to_string()]);
+        let command = "sudo --askpass-file pass.txt make install";
+        assert!(blocked(&filter, command));
+
+        let wrappers = BTreeMap::from([(
+            "sudo".to_string(),
+            Wrapper {
+                flags_with_values: vec!["--askpass-file".to_string()],
+                ..Wrapper::default()
+            },
+        )]);
+        let wrappers = Arc::new(WrapperRegistry::default().with_wrappers(&wrappers));
+        let mut parser = ShellParser::new().with_wrappers(Arc::clone(&wrappers));
+        assert!(!filter.contains_escalation(&parser.parse(command), &wrappers));
+    }
+}
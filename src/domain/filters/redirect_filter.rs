@@ -0,0 +1,315 @@
+//! Redirect filter implementation.
+
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+
+use super::{glob_regex, Filter, FilterContext};
+use crate::domain::parser::ParsedCommand;
+use crate::domain::{Decision, FilterAction};
+
+/// Default message for blocking redirects that overwrite protected paths.
+const DEFAULT_REDIRECT_MESSAGE: &str = "🚫 Redirect overwrites a protected system or device path. Write to a file in the project instead. Configure redirect_protected_paths in config.toml to customize the protected paths.";
+
+/// System, device and shell configuration paths that redirects must not overwrite.
+const DEFAULT_PROTECTED_PATHS: &[&str] = &[
+    "/etc/**",
+    "/boot/**",
+    "/bin/**",
+    "/sbin/**",
+    "/usr/**",
+    "/lib/**",
+    "/lib64/**",
+    "/dev/**",
+    "/System/**",
+    "~/.ssh/**",
+    "~/.bashrc",
+    "~/.bash_profile",
+    "~/.profile",
+    "~/.zshrc",
+    "~/.zprofile",
+    "~/.gitconfig",
+];
+
+/// Device paths that are safe to write to even though `/dev` is protected.
+const SAFE_DEVICE_PATHS: &[&str] = &[
+    "/dev/null",
+    "/dev/stdout",
+    "/dev/stderr",
+    "/dev/tty",
+    "/dev/fd/*",
+    "/dev/pts/*",
+];
+
+/// Filter for redirects that truncate or overwrite protected paths
+/// (`echo x > /etc/hosts`, `cat img > /dev/sda`, `: > ~/.bashrc`).
+///
+/// Appending redirects (`>>`) and file descriptor duplication (`2>&1`) pass.
+/// Targets only known at runtime (`> $FILE`) cannot be checked and pass.
+pub struct RedirectFilter {
+    enabled: bool,
+    message: String,
+    action: FilterAction,
+    protected: Vec<Regex>,
+    safe: Vec<Regex>,
+}
+
+impl RedirectFilter {
+    /// Create a new RedirectFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            message: custom_message.unwrap_or_else(|| DEFAULT_REDIRECT_MESSAGE.to_string()),
+            action: FilterAction::Block,
+            protected: DEFAULT_PROTECTED_PATHS
+                .iter()
+                .filter_map(|pattern| Self::path_glob(pattern))
+                .collect(),
+            safe: SAFE_DEVICE_PATHS
+                .iter()
+                .filter_map(|pattern| Self::path_glob(pattern))
+                .collect(),
+        }
+    }
+
+    /// Set whether matching redirects are blocked or need user confirmation.
+    pub fn with_action(mut self, action: FilterAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Protect additional path globs on top of the built-in list.
+    pub fn with_protected_paths(mut self, patterns: &[String]) -> Self {
+        self.protected.extend(
+            patterns
+                .iter()
+                .filter_map(|pattern| Self::path_glob(pattern)),
+        );
+        self
+    }
+
+    /// Compile a path glob to a regex.
+    ///
+    /// `~/` is the home directory and patterns without `/` match the file name.
+    fn path_glob(pattern: &str) -> Option<Regex> {
+        let pattern = match pattern.strip_prefix("~/") {
+            Some(rest) => format!("{}/{}", dirs::home_dir()?.display(), rest),
+            None if !pattern.contains('/') => format!("**/{}", pattern),
+            None => pattern.to_string(),
+        };
+        glob_regex(&pattern).ok()
+    }
+
+    /// Resolve a redirect target to the absolute paths it may write to:
+    /// the lexically normalized path and, if it exists, its canonical path
+    /// (a symlink writes to the file it points to).
+    ///
+    /// Returns no paths for targets that depend on runtime expansion.
+    fn resolve_target(target: &str, cwd: Option<&str>) -> Vec<PathBuf> {
+        let home_relative =
+            ["~", "$HOME", "${HOME}"]
+                .iter()
+                .find_map(|home| match target.strip_prefix(home) {
+                    Some(rest) if rest.is_empty() || rest.starts_with('/') => Some(rest),
+                    _ => None,
+                });
+        let path = match home_relative {
+            Some(rest) => match dirs::home_dir() {
+                Some(home) => home.join(rest.trim_start_matches('/')),
+                None => return Vec::new(),
+            },
+            None if target.is_empty() || target.contains(['$', '`']) => return Vec::new(),
+            None => PathBuf::from(target),
+        };
+        let path = if path.is_absolute() {
+            path
+        } else {
+            let cwd = match cwd {
+                Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+                _ => match env::current_dir() {
+                    Ok(dir) => dir,
+                    Err(_) => return Vec::new(),
+                },
+            };
+            cwd.join(path)
+        };
+
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                Component::CurDir => {}
+                other => normalized.push(other),
+            }
+        }
+
+        let mut paths = vec![normalized];
+        if let Ok(canonical) = fs::canonicalize(&paths[0]) {
+            if canonical != paths[0] {
+                paths.push(canonical);
+            }
+        }
+        paths
+    }
+
+    /// Check if a path matches any of the compiled globs.
+    fn matches(globs: &[Regex], path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        globs.iter().any(|glob| glob.is_match(&path))
+    }
+
+    /// Check if a redirect target resolves to a protected path.
+    ///
+    /// Every resolved path is checked, so a safe device path only passes
+    /// when the file it points to is safe or unprotected too.
+    fn is_protected(&self, target: &str, cwd: Option<&str>) -> bool {
+        Self::resolve_target(target, cwd)
+            .iter()
+            .any(|path| Self::matches(&self.protected, path) && !Self::matches(&self.safe, path))
+    }
+
+    /// Check if any redirect of the parsed commands overwrites a protected path.
+    fn overwrites_protected_path(&self, commands: &[ParsedCommand], cwd: Option<&str>) -> bool {
+        commands
+            .iter()
+            .flat_map(|cmd| &cmd.redirects)
+            .any(|redirect| redirect.overwrites() && self.is_protected(&redirect.target, cwd))
+    }
+}
+
+impl Filter for RedirectFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && self.overwrites_protected_path(ctx.commands(), ctx.input().cwd.as_deref())
+    }
+
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
+        self.action.decision(self.message.clone())
+    }
+
+    fn priority(&self) -> u32 {
+        25 // After rm so its message takes precedence for deletions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+    use crate::domain::{BashInput, HookInput, ToolInput};
+
+    fn overwrites_protected_path(
+        filter: &RedirectFilter,
+        command: &str,
+        cwd: Option<&str>,
+    ) -> bool {
+        filter.overwrites_protected_path(&ShellParser::new().parse(command), cwd)
+    }
+
+    fn overwrites(command: &str) -> bool {
+        overwrites_protected_path(&RedirectFilter::new(true, None), command, Some("/tmp"))
+    }
+
+    #[test]
+    fn test_overwriting_redirect_to_protected_path() {
+        assert!(overwrites("echo '127.0.0.1 x' > /etc/hosts"));
+        assert!(overwrites("cat disk.img >/dev/sda"));
+        assert!(overwrites("echo x &> /usr/local/bin/tool"));
+        assert!(overwrites("echo x 2> \"/etc/passwd\""));
+        assert!(overwrites(": > ~/.bashrc"));
+        assert!(overwrites("echo key > $HOME/.ssh/authorized_keys"));
+        assert!(overwrites("echo x > ../etc/hosts"));
+        assert!(overwrites("sudo bash -c 'echo x > /etc/hosts'"));
+    }
+
+    #[test]
+    #[cfg(feature = "ast-parser")]
+    fn test_redirect_target_resolved_through_variables() {
+        assert!(overwrites("F=/etc/hosts; echo x > $F"));
+        assert!(overwrites("bash <<'EOF'\necho x > /etc/hosts\nEOF"));
+        assert!(overwrites("{ echo a; echo b; } > /etc/hosts"));
+        assert!(overwrites("echo x >| /boot/grub.cfg"));
+        assert!(!overwrites("echo '> /etc/hosts'"));
+    }
+
+    #[test]
+    fn test_safe_redirects() {
+        assert!(!overwrites("echo x > /dev/null"));
+        assert!(!overwrites("make 2>/dev/null >/dev/stderr"));
+        assert!(!overwrites("echo x > out.txt"));
+        assert!(!overwrites("echo x 2>&1"));
+        assert!(!overwrites("echo x >> /etc/hosts"));
+        assert!(!overwrites("echo x > $FILE"));
+        assert!(!overwrites("cat < /etc/hosts"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_to_protected_path() {
+        assert!(overwrites(
+            "ln -sf ~/.bashrc /dev/shm/x; echo hi > /dev/shm/x"
+        ));
+
+        let dir = fs::canonicalize(env::temp_dir())
+            .unwrap()
+            .join(format!("claw-hooks-redirect-filter-{}", std::process::id()));
+        fs::create_dir_all(dir.join("protected")).unwrap();
+        fs::write(dir.join("protected/config"), "").unwrap();
+        std::os::unix::fs::symlink(dir.join("protected/config"), dir.join("link")).unwrap();
+        let filter = RedirectFilter::new(true, None)
+            .with_protected_paths(&[format!("{}/protected/**", dir.display())]);
+        let overwritten = overwrites_protected_path(&filter, "echo x > link", dir.to_str());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(overwritten);
+    }
+
+    #[test]
+    fn test_custom_protected_paths() {
+        let filter = RedirectFilter::new(true, None)
+            .with_protected_paths(&["/srv/data/**".to_string(), "*.pem".to_string()]);
+        assert!(overwrites_protected_path(
+            &filter,
+            "echo x > /srv/data/a/b",
+            None
+        ));
+        assert!(overwrites_protected_path(
+            &filter,
+            "echo x > keys/server.pem",
+            Some("/tmp")
+        ));
+        assert!(!overwrites_protected_path(
+            &filter,
+            "echo x > /srv/other",
+            None
+        ));
+    }
+
+    #[test]
+    fn test_action() {
+        let input = HookInput {
+            event: "PreToolUse".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: ToolInput::Bash(BashInput {
+                command: "echo x > /etc/hosts".to_string(),
+                timeout: None,
+            }),
+            session_id: None,
+            cwd: None,
+        };
+
+        let ctx = FilterContext::new(&input);
+
+        let filter = RedirectFilter::new(true, None);
+        assert!(filter.applies_to(&ctx));
+        assert!(matches!(filter.execute(&ctx), Decision::Block { .. }));
+
+        let filter = RedirectFilter::new(true, None).with_action(FilterAction::Ask);
+        assert!(matches!(filter.execute(&ctx), Decision::Ask { .. }));
+
+        assert!(!RedirectFilter::new(false, None).applies_to(&ctx));
+    }
+}
@@ -1,16 +1,23 @@
 //! RM command filter implementation.
 
-use super::Filter;
-use crate::domain::parser::ShellParser;
-use crate::domain::{Decision, HookInput, ToolInput};
+use super::{Filter, FilterContext};
+use crate::domain::parser::ParsedCommand;
+use crate::domain::workspace::Workspace;
+use crate::domain::Decision;
+use std::fs;
 
 /// Default message for rm blocking (generic, can be customized via config).
 const DEFAULT_RM_MESSAGE: &str = "🚫 rm/rmdir command blocked for safety. Configure rm_block_message in config.toml to customize this message.";
 
+/// Hint after the reason a deletion is blocked with rm_allow_untracked.
+const UNTRACKED_RM_HINT: &str = "Only untracked or git-ignored paths inside the project can be deleted, by rm, rmdir, unlink, rimraf, del-cli or trash with explicit paths.";
+
 /// Filter for blocking rm-related commands.
 pub struct RmFilter {
     enabled: bool,
-    message: String,
+    custom_message: Option<String>,
+    /// Allow deleting untracked or git-ignored paths inside the project root
+    allow_untracked: bool,
 }
 
 impl RmFilter {
@@ -18,52 +25,198 @@ impl RmFilter {
     pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
         Self {
             enabled,
-            message: custom_message.unwrap_or_else(|| DEFAULT_RM_MESSAGE.to_string()),
+            custom_message,
+            allow_untracked: false,
         }
     }
 
+    /// Allow deletions that only touch untracked or git-ignored paths inside the project root.
+    pub fn with_allow_untracked(mut self, allow_untracked: bool) -> Self {
+        self.allow_untracked = allow_untracked;
+        self
+    }
+
     /// RM command patterns for Unix and Windows
     const RM_COMMANDS: &'static [&'static str] = &[
-        "rm",    // Unix
-        "rmdir", // Unix/Windows
-        "del",   // Windows
-        "erase", // Windows (alias for del)
+        "rm",      // Unix
+        "rmdir",   // Unix/Windows
+        "unlink",  // Unix
+        "shred",   // Unix (overwrites and optionally removes files)
+        "del",     // Windows
+        "erase",   // Windows (alias for del)
+        "rimraf",  // Node.js (npx rimraf dist)
+        "del-cli", // Node.js
+        "trash",   // macOS/trash-cli (moves files to the trash)
     ];
 
-    /// Check if any command in the string is an rm-related command.
-    fn contains_rm_command(command: &str) -> bool {
-        let mut parser = ShellParser::new();
-        let commands = parser.extract_commands(command);
+    /// RM commands whose operands can be checked against the project workspace
+    const PATH_CHECKED_COMMANDS: &'static [&'static str] =
+        &["rm", "rmdir", "unlink", "rimraf", "del-cli", "trash"];
 
-        commands
-            .iter()
-            .any(|cmd| Self::RM_COMMANDS.contains(&cmd.as_str()))
+    /// Commands that make rm operands impossible to verify statically
+    const UNVERIFIABLE_CONTEXT_COMMANDS: &'static [&'static str] =
+        &["xargs", "cd", "pushd", "popd"];
+
+    /// Check if any parsed command is an rm-related command.
+    fn contains_rm_command(commands: &[ParsedCommand]) -> bool {
+        commands.iter().any(|cmd| Self::is_deletion(&cmd.argv))
     }
-}
 
-impl Filter for RmFilter {
-    fn applies_to(&self, input: &HookInput) -> bool {
-        if !self.enabled {
-            return false;
+    /// Check if an argument vector deletes files, either as an rm command
+    /// or as an rm-equivalent form of another command.
+    fn is_deletion(argv: &[String]) -> bool {
+        let args = &argv[1..];
+        match argv[0].as_str() {
+            cmd if Self::RM_COMMANDS.contains(&cmd) => true,
+            // PowerShell cmdlet names are case-insensitive
+            cmd if cmd.eq_ignore_ascii_case("Remove-Item") => true,
+            "find" => args.iter().any(|arg| arg == "-delete"),
+            "truncate" => Self::truncates_contents(args),
+            "rsync" => args
+                .iter()
+                .any(|arg| arg.starts_with("--delete") || arg == "--remove-source-files"),
+            _ => false,
         }
+    }
 
-        // Only applies to Bash tool in PreToolUse event
-        if input.event != "PreToolUse" || input.tool_name != "Bash" {
-            return false;
+    /// Check if truncate arguments shrink files (e.g. `-s 0`, `--size=-10K`).
+    fn truncates_contents(args: &[String]) -> bool {
+        let mut sizes: Vec<&str> = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "-s" || arg == "--size" {
+                sizes.extend(iter.next().map(String::as_str));
+            } else if let Some(size) = arg.strip_prefix("--size=") {
+                sizes.push(size);
+            } else if let Some(size) = arg.strip_prefix("-s") {
+                sizes.push(size);
+            }
         }
 
-        // Extract command from tool input
-        if let ToolInput::Bash(bash) = &input.tool_input {
-            return Self::contains_rm_command(&bash.command);
+        sizes.iter().any(|size| {
+            let digits = size.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            size.starts_with(['-', '<', '/'])
+                || (!digits.is_empty() && digits.chars().all(|c| c == '0'))
+        })
+    }
+
+    /// Find why the command deletes something outside the allowed set of paths,
+    /// or `None` if it is safe.
+    ///
+    /// Every rm invocation must name its operands explicitly, and every operand must
+    /// resolve inside the project root without containing git-tracked files.
+    fn unsafe_deletion(commands: &[ParsedCommand], cwd: Option<&str>) -> Option<String> {
+        let rm_commands: Vec<&ParsedCommand> = commands
+            .iter()
+            .filter(|cmd| Self::is_deletion(&cmd.argv))
+            .collect();
+
+        // SECURITY: Fail closed if the operands could not be extracted
+        if rm_commands.is_empty() {
+            return Some("the paths to delete could not be determined".to_string());
         }
 
-        false
+        // Operands supplied at runtime through xargs cannot be verified, and a directory
+        // change makes relative operands resolve somewhere other than the project
+        if let Some(cmd) = commands
+            .iter()
+            .find(|cmd| Self::UNVERIFIABLE_CONTEXT_COMMANDS.contains(&cmd.name()))
+        {
+            return Some(format!(
+                "`{}` makes the paths to delete unknown before the command runs",
+                cmd.name()
+            ));
+        }
+
+        let Some(workspace) = Workspace::detect(cwd) else {
+            return Some("the working directory is not inside a git repository".to_string());
+        };
+
+        let mut paths = Vec::new();
+        for cmd in rm_commands {
+            if !Self::PATH_CHECKED_COMMANDS.contains(&cmd.name()) {
+                return Some(format!(
+                    "the paths `{}` deletes cannot be checked",
+                    cmd.name()
+                ));
+            }
+            let operands = match Self::operands(cmd.args()) {
+                Some(operands) if !operands.is_empty() => operands,
+                Some(_) => return Some(format!("`{}` has no paths to check", cmd.name())),
+                None => {
+                    return Some("--no-preserve-root disables the safety checks of rm".to_string())
+                }
+            };
+            for operand in operands {
+                let Some(path) = workspace.resolve(operand) else {
+                    return Some(format!("`{}` is only known when the command runs", operand));
+                };
+                // rm follows a symlink only when it is given with a trailing slash
+                // (`link/`); `rm -rf link` removes the link itself
+                let path = if operand.ends_with('/') {
+                    fs::canonicalize(&path).unwrap_or(path)
+                } else {
+                    path
+                };
+                if !workspace.contains(&path) {
+                    return Some(format!("`{}` is outside the project", operand));
+                }
+                paths.push(path);
+            }
+        }
+
+        workspace
+            .has_tracked_files(&paths)
+            .then(|| "the paths contain files tracked by git".to_string())
     }
 
-    fn execute(&self, _input: &HookInput) -> Decision {
-        Decision::Block {
-            message: self.message.clone(),
+    /// Collect path operands, skipping option flags.
+    ///
+    /// Returns `None` if a flag disables rm's own safety checks.
+    fn operands(args: &[String]) -> Option<Vec<&str>> {
+        let mut operands = Vec::new();
+        let mut end_of_options = false;
+        for arg in args {
+            if end_of_options {
+                operands.push(arg.as_str());
+            } else if arg == "--" {
+                end_of_options = true;
+            } else if arg == "--no-preserve-root" {
+                return None;
+            } else if !arg.starts_with('-') || arg == "-" {
+                operands.push(arg.as_str());
+            }
+        }
+        Some(operands)
+    }
+}
+
+impl Filter for RmFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        if !self.enabled || !Self::contains_rm_command(ctx.commands()) {
+            return false;
         }
+        if self.allow_untracked {
+            return Self::unsafe_deletion(ctx.commands(), ctx.input().cwd.as_deref()).is_some();
+        }
+        true
+    }
+
+    fn execute(&self, ctx: &FilterContext) -> Decision {
+        let message = match &self.custom_message {
+            Some(message) => message.clone(),
+            None if self.allow_untracked => {
+                match Self::unsafe_deletion(ctx.commands(), ctx.input().cwd.as_deref()) {
+                    Some(reason) => {
+                        format!("🚫 Deletion blocked: {}. {}", reason, UNTRACKED_RM_HINT)
+                    }
+                    None => DEFAULT_RM_MESSAGE.to_string(),
+                }
+            }
+            None => DEFAULT_RM_MESSAGE.to_string(),
+        };
+        Decision::Block { message }
     }
 
     fn priority(&self) -> u32 {
@@ -74,24 +227,248 @@ impl Filter for RmFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::filters::test_repo::TestRepo;
+    use crate::domain::parser::ShellParser;
+    use crate::domain::{BashInput, HookInput, ToolInput};
+
+    fn contains_rm_command(command: &str) -> bool {
+        RmFilter::contains_rm_command(&ShellParser::new().parse(command))
+    }
 
     #[test]
     fn test_contains_rm_command() {
         // Simple Unix commands
-        assert!(RmFilter::contains_rm_command("rm file.txt"));
-        assert!(RmFilter::contains_rm_command("rm -rf /tmp/test"));
-        assert!(RmFilter::contains_rm_command("rmdir empty_dir"));
-        assert!(!RmFilter::contains_rm_command("ls -la"));
-        assert!(!RmFilter::contains_rm_command("echo rm"));
+        assert!(contains_rm_command("rm file.txt"));
+        assert!(contains_rm_command("rm -rf /tmp/test"));
+        assert!(contains_rm_command("rmdir empty_dir"));
+        assert!(!contains_rm_command("ls -la"));
+        assert!(!contains_rm_command("echo rm"));
 
         // Windows commands
-        assert!(RmFilter::contains_rm_command("del file.txt"));
-        assert!(RmFilter::contains_rm_command("del /F /Q temp.log"));
-        assert!(RmFilter::contains_rm_command("erase old_file.bak"));
+        assert!(contains_rm_command("del file.txt"));
+        assert!(contains_rm_command("del /F /Q temp.log"));
+        assert!(contains_rm_command("erase old_file.bak"));
 
         // Chained commands
-        assert!(RmFilter::contains_rm_command("cd /tmp && rm -rf test"));
-        assert!(RmFilter::contains_rm_command("echo done; rmdir old"));
-        assert!(RmFilter::contains_rm_command("dir && del *.tmp"));
+        assert!(contains_rm_command("cd /tmp && rm -rf test"));
+        assert!(contains_rm_command("echo done; rmdir old"));
+        assert!(contains_rm_command("dir && del *.tmp"));
+
+        // Compound statements
+        assert!(contains_rm_command("for f in x; do rm $f; done"));
+        assert!(contains_rm_command("if true; then rm x; fi"));
+        assert!(contains_rm_command("! { rm x; }"));
+        assert!(contains_rm_command("coproc rm x"));
+
+        // Path-qualified, escaped and wrapped command names
+        assert!(contains_rm_command("/bin/rm -rf x"));
+        assert!(contains_rm_command("\\rm x"));
+        assert!(contains_rm_command("\"r\"m x"));
+        assert!(contains_rm_command("command rm x"));
+        assert!(contains_rm_command("exec rm x"));
+        assert!(!contains_rm_command("command -v rm"));
+    }
+
+    #[test]
+    fn test_contains_rm_equivalent_command() {
+        // find deletion forms
+        assert!(contains_rm_command("find . -name '*.log' -delete"));
+        assert!(contains_rm_command("find . -exec rm {} +"));
+        assert!(contains_rm_command(
+            "find /tmp -type f -execdir rm -f {} \\;"
+        ));
+        assert!(!contains_rm_command("find . -name '*.rs'"));
+        assert!(!contains_rm_command("find . -exec grep -l rm {} +"));
+
+        // Deletion tools run by package runners
+        assert!(contains_rm_command("npx rimraf dist"));
+        assert!(contains_rm_command("pnpm exec rimraf src"));
+        assert!(contains_rm_command("npx del-cli 'dist/**'"));
+        assert!(contains_rm_command("trash notes.txt"));
+
+        // unlink and shred
+        assert!(contains_rm_command("unlink file.txt"));
+        assert!(contains_rm_command("shred -u secret.key"));
+
+        // truncate to zero or shrinking sizes
+        assert!(contains_rm_command("truncate -s 0 app.log"));
+        assert!(contains_rm_command("truncate -s0 app.log"));
+        assert!(contains_rm_command("truncate --size=0 app.log"));
+        assert!(contains_rm_command("truncate -s -10K app.log"));
+        assert!(!contains_rm_command("truncate -s 10M disk.img"));
+        assert!(!contains_rm_command("truncate -s +1G disk.img"));
+
+        // rsync with deletion of destination or source files
+        assert!(contains_rm_command("rsync -a --delete src/ dst/"));
+        assert!(contains_rm_command(
+            "rsync -av --delete-after src/ host:dst/"
+        ));
+        assert!(contains_rm_command("rsync --remove-source-files a b"));
+        assert!(!contains_rm_command("rsync -av src/ dst/"));
+
+        // git clean is left to the git filter
+        assert!(!contains_rm_command("git clean -fdx"));
+
+        // PowerShell
+        assert!(contains_rm_command("Remove-Item -Recurse -Force build"));
+        assert!(contains_rm_command("remove-item foo.txt"));
+
+        // Inline interpreter code
+        assert!(contains_rm_command(
+            "python3 -c \"import shutil; shutil.rmtree('build')\""
+        ));
+        assert!(contains_rm_command(
+            "node -e \"require('fs').rmSync('dist', { recursive: true })\""
+        ));
+        assert!(contains_rm_command("perl -e 'unlink glob \"*.log\"'"));
+        assert!(contains_rm_command("ruby -e 'system(\"rm -rf tmp\")'"));
+        assert!(!contains_rm_command(
+            "python3 -c \"print('shutil.rmtree')\""
+        ));
+    }
+
+    /// Create a git repository with a tracked file, an ignored directory and an untracked file.
+    fn create_test_repo() -> TestRepo {
+        let repo = TestRepo::new("rm-filter-test");
+        repo.write(".gitignore", "target/\n");
+        repo.write("src/main.rs", "fn main() {}\n");
+        repo.write("target/debug/app", "");
+        repo.write("notes.tmp", "");
+        repo.git(&["add", ".gitignore", "src/main.rs"]);
+        repo
+    }
+
+    #[test]
+    fn test_allow_untracked_paths_in_project() {
+        let repo = create_test_repo();
+        let cwd = repo.cwd();
+        let unsafe_deletion =
+            |cmd: &str| RmFilter::unsafe_deletion(&ShellParser::new().parse(cmd), cwd).is_some();
+
+        // Untracked or ignored paths inside the project are allowed
+        assert!(!unsafe_deletion("rm notes.tmp"));
+        assert!(!unsafe_deletion("rm -rf target"));
+        assert!(!unsafe_deletion("rm -rf ./target/debug notes.tmp"));
+        assert!(!unsafe_deletion("cargo build && rm -f target/debug/app"));
+        assert!(!unsafe_deletion("unlink notes.tmp"));
+        assert!(!unsafe_deletion("npx rimraf target"));
+        assert!(!unsafe_deletion(
+            "python3 -c \"import shutil; shutil.rmtree('target')\""
+        ));
+
+        // Tracked files and directories containing them are blocked
+        assert!(unsafe_deletion("rm src/main.rs"));
+        assert!(unsafe_deletion("rm -rf src"));
+        assert!(unsafe_deletion("rm notes.tmp src/main.rs"));
+        assert!(unsafe_deletion("npx rimraf src/main.rs"));
+        assert!(unsafe_deletion("pnpm exec rimraf src"));
+
+        // Paths outside the project or not statically known are blocked
+        assert!(unsafe_deletion("rm -rf /"));
+        assert!(unsafe_deletion("rm -rf ~"));
+        assert!(unsafe_deletion("rm -rf ."));
+        assert!(unsafe_deletion("rm -rf .git"));
+        assert!(unsafe_deletion("rm -rf ../other"));
+        assert!(unsafe_deletion("rm -rf /tmp/test"));
+        assert!(unsafe_deletion("rm *.tmp"));
+        assert!(unsafe_deletion("rm -rf $DIR"));
+        assert!(unsafe_deletion("rm -rf --no-preserve-root target"));
+        assert!(unsafe_deletion("rm -rf"));
+        assert!(unsafe_deletion("ls | xargs rm"));
+        assert!(unsafe_deletion("cd /etc && rm notes.tmp"));
+        assert!(unsafe_deletion("del notes.tmp"));
+        assert!(unsafe_deletion("find target -delete"));
+        assert!(unsafe_deletion("find target -exec rm {} +"));
+        assert!(unsafe_deletion(
+            "python3 -c \"import os; os.remove('src/main.rs')\""
+        ));
+        assert!(unsafe_deletion(
+            "python3 -c \"import os, sys; os.remove(sys.argv[1])\" notes.tmp"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allow_untracked_follows_symlinked_directories() {
+        let repo = create_test_repo();
+        let outside = TestRepo::new("rm-filter-test-outside");
+        outside.write("data.txt", "");
+        std::os::unix::fs::symlink(outside.path(), repo.path().join("link")).unwrap();
+        std::os::unix::fs::symlink(repo.path().join("target"), repo.path().join("build")).unwrap();
+        let cwd = repo.cwd();
+        let unsafe_deletion =
+            |cmd: &str| RmFilter::unsafe_deletion(&ShellParser::new().parse(cmd), cwd).is_some();
+
+        // Deleting the contents of a directory outside the project through a symlink
+        assert!(unsafe_deletion("rm -rf link/"));
+        assert!(unsafe_deletion("rm -r link/"));
+        // `..` after a symlink leaves its target, not the link's directory
+        assert!(unsafe_deletion("rm -rf link/../victim"));
+        assert!(unsafe_deletion("rm -f ./link/../../victim.txt"));
+        // Removing the link itself, or following it into the project, is allowed
+        assert!(!unsafe_deletion("rm link"));
+        assert!(!unsafe_deletion("rm -rf link"));
+        assert!(!unsafe_deletion("rm -rf build/"));
+    }
+
+    #[test]
+    fn test_allow_untracked_message_gives_reason() {
+        let repo = create_test_repo();
+        let filter = RmFilter::new(true, None).with_allow_untracked(true);
+        let message = |command: &str| {
+            let input = HookInput {
+                event: "PreToolUse".to_string(),
+                tool_name: "Bash".to_string(),
+                tool_input: ToolInput::Bash(BashInput {
+                    command: command.to_string(),
+                    timeout: None,
+                }),
+                session_id: None,
+                cwd: repo.cwd().map(String::from),
+            };
+            match filter.execute(&FilterContext::new(&input)) {
+                Decision::Block { message } => message,
+                decision => panic!("{:?}", decision),
+            }
+        };
+
+        assert!(message("rm src/main.rs").contains("tracked by git"));
+        assert!(message("rm -rf ../other").contains("`../other` is outside the project"));
+        assert!(message("rm -rf $DIR").contains("`$DIR` is only known when the command runs"));
+        assert!(message("ls | xargs rm").contains("`xargs`"));
+        assert!(message("del notes.tmp").contains("`del` deletes cannot be checked"));
+
+        let filter = RmFilter::new(true, Some("custom".to_string())).with_allow_untracked(true);
+        let input = HookInput {
+            event: "PreToolUse".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: ToolInput::Bash(BashInput {
+                command: "rm src/main.rs".to_string(),
+                timeout: None,
+            }),
+            session_id: None,
+            cwd: repo.cwd().map(String::from),
+        };
+        assert!(matches!(
+            filter.execute(&FilterContext::new(&input)),
+            Decision::Block { message } if message == "custom"
+        ));
+    }
+
+    #[test]
+    fn test_allow_untracked_requires_git_repository() {
+        let dir = std::env::temp_dir();
+        let filter = RmFilter::new(true, None).with_allow_untracked(true);
+        let input = HookInput {
+            event: "PreToolUse".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: ToolInput::Bash(BashInput {
+                command: "rm notes.tmp".to_string(),
+                timeout: None,
+            }),
+            session_id: None,
+            cwd: dir.to_str().map(String::from),
+        };
+        assert!(filter.applies_to(&FilterContext::new(&input)));
     }
 }
@@ -0,0 +1,739 @@
+//! SQL statement filter implementation.
+
+use std::path::Path;
+
+use super::{Filter, FilterContext};
+use crate::domain::parser::{read_script_file, ParsedCommand, Stdin};
+use crate::domain::{Decision, FilterAction};
+
+/// Classes of SQL statements that get their own action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlClass {
+    /// Statements that destroy schema objects or all of their data
+    /// (`DROP`, `TRUNCATE`, `ALTER ... DROP`)
+    Destructive,
+    /// `DELETE` or `UPDATE` without a `WHERE` clause
+    Unbounded,
+    /// Queries that do not modify the database (`SELECT`, `SHOW`, `EXPLAIN`)
+    ReadOnly,
+    /// SQL that cannot be inspected (a file that cannot be read, another command's
+    /// output, a dollar-quoted body). Gets the action for unbounded statements
+    Unchecked,
+}
+
+impl SqlClass {
+    /// Default message for the class.
+    fn default_message(self) -> &'static str {
+        match self {
+            Self::Destructive => "🚫 SQL statement drops or truncates data (DROP, TRUNCATE, ALTER ... DROP). Ask the user to run it.",
+            Self::Unbounded => "⚠️ DELETE/UPDATE without a WHERE clause changes every row. Add a WHERE clause or confirm with the user.",
+            Self::ReadOnly => "SQL queries are restricted by sql_read_only_action in config.toml.",
+            Self::Unchecked => "⚠️ SQL could not be checked (an unreadable file, another command's output or a $$-quoted body). Pass plain statements with -c/-e or confirm with the user.",
+        }
+    }
+}
+
+/// Database clients whose SQL arguments are inspected
+const SQL_CLIENTS: &[&str] = &["psql", "mysql", "mariadb", "sqlite3"];
+
+/// sqlite3 options that take a separate value that is not SQL
+pub(super) const SQLITE_OPTIONS_WITH_VALUES: &[&str] = &[
+    "-separator",
+    "-newline",
+    "-nullvalue",
+    "-vfs",
+    "-maxsize",
+    "-mmap",
+    "-escape",
+];
+
+/// Filter for SQL run by database clients (`psql -c`, `psql -f`, `mysql -e`,
+/// `sqlite3 app.db "..."`), with an action per class of statement.
+pub struct SqlFilter {
+    enabled: bool,
+    custom_message: Option<String>,
+    destructive_action: FilterAction,
+    unbounded_action: FilterAction,
+    read_only_action: FilterAction,
+}
+
+impl SqlFilter {
+    /// Create a new SqlFilter with optional custom message.
+    ///
+    /// Destructive statements are blocked, unbounded DML asks and read-only queries pass.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            custom_message,
+            destructive_action: FilterAction::Block,
+            unbounded_action: FilterAction::Ask,
+            read_only_action: FilterAction::Allow,
+        }
+    }
+
+    /// Set the action for a class of statements.
+    pub fn with_action(mut self, class: SqlClass, action: FilterAction) -> Self {
+        match class {
+            SqlClass::Destructive => self.destructive_action = action,
+            SqlClass::Unbounded | SqlClass::Unchecked => self.unbounded_action = action,
+            SqlClass::ReadOnly => self.read_only_action = action,
+        }
+        self
+    }
+
+    fn action(&self, class: SqlClass) -> FilterAction {
+        match class {
+            SqlClass::Destructive => self.destructive_action,
+            SqlClass::Unbounded | SqlClass::Unchecked => self.unbounded_action,
+            SqlClass::ReadOnly => self.read_only_action,
+        }
+    }
+
+    /// Find the class of the statement that gets the most restrictive action,
+    /// if that action is not to allow it.
+    fn restricted_class(&self, commands: &[ParsedCommand], cwd: Option<&str>) -> Option<SqlClass> {
+        commands
+            .iter()
+            .enumerate()
+            .filter(|(_, cmd)| SQL_CLIENTS.contains(&cmd.name()))
+            .flat_map(|(i, cmd)| Self::classes(cmd, &commands[..i], cwd))
+            .min_by_key(|class| self.action(*class).strictness())
+            .filter(|class| self.action(*class) != FilterAction::Allow)
+    }
+
+    /// Classify the statements a database client runs, from its arguments or,
+    /// without SQL arguments, its standard input.
+    fn classes(cmd: &ParsedCommand, earlier: &[ParsedCommand], cwd: Option<&str>) -> Vec<SqlClass> {
+        let mut sql = Self::sql_arguments(cmd, cwd);
+        if sql.is_empty() {
+            sql.extend(Self::stdin_sql(cmd, earlier, cwd));
+        }
+        if cmd.name() == "sqlite3" {
+            sql = sql
+                .into_iter()
+                .flat_map(|sql| match sql {
+                    Ok(sql) => sqlite_dot_commands(&sql, cwd, false),
+                    Err(()) => vec![Err(())],
+                })
+                .collect();
+        }
+        sql.iter()
+            .flat_map(|sql| match sql {
+                Ok(sql) => statements(sql).into_iter().flat_map(classify).collect(),
+                Err(()) => vec![SqlClass::Unchecked],
+            })
+            .collect()
+    }
+
+    /// Get the SQL a database client reads from standard input: a heredoc,
+    /// here-string or `<` file, or the output of `echo`, `printf` or `cat`
+    /// piped to it. `Some(Err(()))` if the input cannot be inspected.
+    fn stdin_sql(
+        cmd: &ParsedCommand,
+        earlier: &[ParsedCommand],
+        cwd: Option<&str>,
+    ) -> Option<Result<String, ()>> {
+        match &cmd.context.stdin {
+            Some(Stdin::Text(text)) => return Some(Ok(text.clone())),
+            Some(Stdin::File(file)) => return Some(read_file(file, cwd).ok_or(())),
+            // `<&3`, `<>` and other redirects that are not a file or text
+            None if cmd.context.stdin_redirected => return Some(Err(())),
+            None => {}
+        }
+
+        let position = cmd.context.pipeline_position.filter(|p| *p > 0)?;
+        // The closest earlier command in the previous stage, after any wrapper
+        let upstream = earlier
+            .iter()
+            .rev()
+            .find(|other| other.context.pipeline_position == Some(position - 1));
+        let Some(upstream) = upstream else {
+            return Some(Err(()));
+        };
+        let args = upstream.args();
+        let output = match upstream.name() {
+            "echo" => Some(
+                args.iter()
+                    .skip_while(|arg| matches!(arg.as_str(), "-n" | "-e" | "-E"))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            "printf" => Some(args.join(" ")),
+            "cat" => {
+                let files: Vec<&String> = args.iter().filter(|arg| !arg.starts_with('-')).collect();
+                // `cat` without files passes its own input on
+                match files.is_empty() {
+                    true => None,
+                    false => files.iter().map(|file| read_file(file, cwd)).collect(),
+                }
+            }
+            _ => None,
+        };
+        Some(output.ok_or(()))
+    }
+
+    /// Extract the SQL a database client runs from its arguments and `-f` files,
+    /// with `Err(())` for each file that cannot be read.
+    fn sql_arguments(cmd: &ParsedCommand, cwd: Option<&str>) -> Vec<Result<String, ()>> {
+        let mut sql = Vec::new();
+        let mut files = Vec::new();
+        let args = cmd.args();
+        match cmd.name() {
+            "psql" => {
+                let mut iter = args.iter();
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "-c" | "--command" => sql.extend(iter.next().cloned()),
+                        "-f" | "--file" => files.extend(iter.next().cloned()),
+                        _ => {
+                            if let Some(value) = arg.strip_prefix("--command=") {
+                                sql.push(value.to_string());
+                            } else if let Some(path) = arg.strip_prefix("--file=") {
+                                files.push(path.to_string());
+                            } else if let Some(value) = arg.strip_prefix("-c") {
+                                sql.push(value.to_string());
+                            } else if let Some(path) = arg.strip_prefix("-f") {
+                                files.push(path.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            "mysql" | "mariadb" => {
+                let mut iter = args.iter();
+                while let Some(arg) = iter.next() {
+                    if arg == "-e" || arg == "--execute" {
+                        sql.extend(iter.next().cloned());
+                    } else if let Some(value) = arg
+                        .strip_prefix("--execute=")
+                        .or_else(|| arg.strip_prefix("-e"))
+                    {
+                        sql.push(value.to_string());
+                    }
+                }
+            }
+            "sqlite3" => {
+                // sqlite3 [options] DATABASE [SQL...]
+                let mut operands = Vec::new();
+                let mut iter = args.iter();
+                while let Some(arg) = iter.next() {
+                    let option = arg.strip_prefix('-').map(|o| o.trim_start_matches('-'));
+                    match option {
+                        Some("cmd") => sql.extend(iter.next().cloned()),
+                        Some("init") => files.extend(iter.next().cloned()),
+                        Some(o)
+                            if SQLITE_OPTIONS_WITH_VALUES.contains(&format!("-{}", o).as_str()) =>
+                        {
+                            iter.next();
+                        }
+                        Some(_) => {}
+                        None => operands.push(arg.clone()),
+                    }
+                }
+                sql.extend(operands.into_iter().skip(1));
+            }
+            _ => {}
+        }
+
+        // Files that do not exist yet or are not regular files cannot be inspected
+        let files = files.iter().map(|file| read_file(file, cwd).ok_or(()));
+        sql.into_iter().map(Ok).chain(files).collect()
+    }
+}
+
+/// Read a file relative to the working directory of the command, if it is
+/// a regular file small enough to inspect.
+fn read_file(file: &str, cwd: Option<&str>) -> Option<String> {
+    let path = match cwd {
+        Some(dir) if !dir.is_empty() => Path::new(dir).join(file),
+        _ => Path::new(file).to_path_buf(),
+    };
+    read_script_file(&path)
+}
+
+/// Replace the `.read FILE` commands in the input of sqlite3 with the SQL of the
+/// file and drop its other dot-commands. Files that cannot be read, `.read |command`
+/// and `.read` in a file that was itself read give `Err(())`.
+fn sqlite_dot_commands(sql: &str, cwd: Option<&str>, nested: bool) -> Vec<Result<String, ()>> {
+    let mut statements = String::new();
+    let mut files = Vec::new();
+    for line in sql.lines() {
+        match line.trim_start().strip_prefix('.') {
+            Some(command) => {
+                if let Some(file) = command
+                    .strip_prefix("read")
+                    .filter(|rest| rest.starts_with(char::is_whitespace))
+                {
+                    files.push(file.trim().trim_matches(['\'', '"']));
+                }
+            }
+            None => {
+                statements.push_str(line);
+                statements.push('\n');
+            }
+        }
+    }
+
+    let mut sql = vec![Ok(statements)];
+    for file in files {
+        match read_file(file, cwd) {
+            Some(text) if !nested && !file.starts_with('|') => {
+                sql.extend(sqlite_dot_commands(&text, cwd, true))
+            }
+            _ => sql.push(Err(())),
+        }
+    }
+    sql
+}
+
+/// A keyword or identifier of a SQL statement with its parenthesis depth.
+type Word = (String, usize);
+
+/// Word standing in for a dollar-quoted body (`DO $$ ... $$`, `CREATE FUNCTION ... AS $f$ ... $f$`),
+/// whose statements are not inspected
+const DOLLAR_QUOTED: &str = "$$";
+
+/// Split SQL into statements of upper-cased words, skipping string literals,
+/// quoted identifiers and comments. Dollar-quoted bodies become [`DOLLAR_QUOTED`].
+fn statements(sql: &str) -> Vec<Vec<Word>> {
+    let mut statements = Vec::new();
+    let mut current: Vec<Word> = Vec::new();
+    let mut depth = 0usize;
+    let mut chars = sql.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                // Doubled quotes are an escaped quote and simply reopen the literal
+                for (_, next) in chars.by_ref() {
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek().is_some_and(|(_, next)| *next == '-') => {
+                for (_, next) in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek().is_some_and(|(_, next)| *next == '*') => {
+                chars.next();
+                let mut previous = ' ';
+                for (_, next) in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            // PostgreSQL dollar quoting: $$...$$ or $tag$...$tag$
+            '$' => {
+                let rest = &sql[i + 1..];
+                let tag_len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                if rest[tag_len..].starts_with('$') {
+                    let delimiter = &sql[i..i + tag_len + 2];
+                    let body = i + delimiter.len();
+                    let end = sql[body..]
+                        .find(delimiter)
+                        .map_or(sql.len(), |j| body + j + delimiter.len());
+                    while chars.peek().is_some_and(|(j, _)| *j < end) {
+                        chars.next();
+                    }
+                    current.push((DOLLAR_QUOTED.to_string(), depth));
+                }
+            }
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ';' => {
+                if !current.is_empty() {
+                    statements.push(std::mem::take(&mut current));
+                }
+                depth = 0;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut word = c.to_ascii_uppercase().to_string();
+                while let Some((_, next)) =
+                    chars.next_if(|(_, next)| next.is_ascii_alphanumeric() || *next == '_')
+                {
+                    word.push(next.to_ascii_uppercase());
+                }
+                current.push((word, depth));
+            }
+            _ => {}
+        }
+    }
+    if !current.is_empty() {
+        statements.push(current);
+    }
+    statements
+}
+
+/// Classify a statement and the data-modifying queries in its `WITH` clause.
+/// Statements without a class (`INSERT`, `CREATE`) give no classes.
+///
+/// Statements with a dollar-quoted body are unchecked, as the body may run any
+/// statement (`DO $$ BEGIN DROP TABLE x; END $$`).
+fn classify(words: Vec<Word>) -> Vec<SqlClass> {
+    if words.iter().any(|(word, _)| word == DOLLAR_QUOTED) {
+        return vec![SqlClass::Unchecked];
+    }
+    let top_level = |keyword: &str| {
+        words
+            .iter()
+            .any(|(word, depth)| word == keyword && *depth == 0)
+    };
+    let Some((first, _)) = words.first() else {
+        return Vec::new();
+    };
+    let mut classes = Vec::new();
+    // `WITH x AS (...) DELETE FROM ...` and `EXPLAIN ANALYZE DELETE ...` run the main statement
+    let main = match first.as_str() {
+        "WITH" => {
+            classes.extend(
+                common_table_expressions(&words)
+                    .into_iter()
+                    .flat_map(classify),
+            );
+            words
+                .iter()
+                .find(|(word, depth)| {
+                    *depth == 0
+                        && matches!(
+                            word.as_str(),
+                            "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "MERGE"
+                        )
+                })
+                .map_or(first, |(word, _)| word)
+        }
+        "EXPLAIN" if top_level("ANALYZE") => {
+            let rest: Vec<Word> = words
+                .iter()
+                .skip_while(|(word, _)| matches!(word.as_str(), "EXPLAIN" | "ANALYZE" | "VERBOSE"))
+                .cloned()
+                .collect();
+            return classify(rest);
+        }
+        _ => first,
+    };
+
+    classes.extend(match main.as_str() {
+        "DROP" | "TRUNCATE" => Some(SqlClass::Destructive),
+        "ALTER" if top_level("DROP") => Some(SqlClass::Destructive),
+        "DELETE" | "UPDATE" if !top_level("WHERE") => Some(SqlClass::Unbounded),
+        "SELECT" | "SHOW" | "EXPLAIN" | "DESCRIBE" | "DESC" | "VALUES" | "TABLE" => {
+            Some(SqlClass::ReadOnly)
+        }
+        _ => None,
+    });
+    classes
+}
+
+/// Get the `DELETE` and `UPDATE` queries of a `WITH` clause (`WITH d AS (DELETE FROM t
+/// RETURNING *) SELECT ...`) as statements of their own, one level less deep.
+fn common_table_expressions(words: &[Word]) -> Vec<Vec<Word>> {
+    let mut queries = Vec::new();
+    for (i, window) in words.windows(2).enumerate() {
+        let [(keyword, keyword_depth), (word, depth)] = window else {
+            continue;
+        };
+        if !matches!(keyword.as_str(), "AS" | "MATERIALIZED")
+            || *keyword_depth != 0
+            || *depth != 1
+            || !matches!(word.as_str(), "DELETE" | "UPDATE")
+        {
+            continue;
+        }
+        queries.push(
+            words[i + 1..]
+                .iter()
+                .take_while(|(_, depth)| *depth > 0)
+                .map(|(word, depth)| (word.clone(), depth - 1))
+                .collect(),
+        );
+    }
+    queries
+}
+
+impl Filter for SqlFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled
+            && ctx
+                .commands()
+                .iter()
+                .any(|cmd| SQL_CLIENTS.contains(&cmd.name()))
+    }
+
+    fn execute(&self, ctx: &FilterContext) -> Decision {
+        match self.restricted_class(ctx.commands(), ctx.input().cwd.as_deref()) {
+            Some(class) => self.action(class).decision(
+                self.custom_message
+                    .clone()
+                    .unwrap_or_else(|| class.default_message().to_string()),
+            ),
+            None => Decision::allow(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        26 // After the filters for shell commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+
+    fn class_of(command: &str) -> Option<SqlClass> {
+        let filter = SqlFilter::new(true, None)
+            .with_action(SqlClass::Unbounded, FilterAction::Block)
+            .with_action(SqlClass::ReadOnly, FilterAction::Ask);
+        filter.restricted_class(&ShellParser::new().parse(command), Some("/tmp"))
+    }
+
+    #[test]
+    fn test_classify_statements() {
+        assert_eq!(
+            class_of("psql -c 'DROP TABLE users'"),
+            Some(SqlClass::Destructive)
+        );
+        assert_eq!(
+            class_of("mysql -u root -e 'truncate table logs'"),
+            Some(SqlClass::Destructive)
+        );
+        assert_eq!(
+            class_of("psql -c 'ALTER TABLE users DROP COLUMN email'"),
+            Some(SqlClass::Destructive)
+        );
+        assert_eq!(
+            class_of("sqlite3 app.db 'DELETE FROM sessions'"),
+            Some(SqlClass::Unbounded)
+        );
+        assert_eq!(
+            class_of("psql --command=\"UPDATE users SET admin = (SELECT true WHERE 1 = 1)\""),
+            Some(SqlClass::Unbounded)
+        );
+        assert_eq!(
+            class_of("psql -c 'SELECT count(*) FROM users'"),
+            Some(SqlClass::ReadOnly)
+        );
+        assert_eq!(
+            class_of("mysql --execute='SHOW TABLES'"),
+            Some(SqlClass::ReadOnly)
+        );
+
+        // Statements without a class, strings and comments
+        assert_eq!(class_of("psql -c 'DELETE FROM x WHERE id = 1'"), None);
+        assert_eq!(class_of("psql -c 'INSERT INTO x VALUES (1)'"), None);
+        assert_eq!(
+            class_of("psql -c \"INSERT INTO notes VALUES ('DROP TABLE x')\""),
+            None
+        );
+        assert_eq!(
+            class_of("psql -c '-- DROP TABLE x\nCREATE TABLE y (id int)'"),
+            None
+        );
+        assert_eq!(class_of("sqlite3 app.db"), None);
+        assert_eq!(class_of("echo 'DROP TABLE x'"), None);
+    }
+
+    #[test]
+    fn test_multiple_statements() {
+        // The most restrictive statement wins
+        assert_eq!(
+            class_of("psql -c 'SELECT 1; DELETE FROM x WHERE id = 1; DROP TABLE y'"),
+            Some(SqlClass::Destructive)
+        );
+        assert_eq!(
+            class_of("mysql -e 'SELECT 1; DELETE FROM x'"),
+            Some(SqlClass::Unbounded)
+        );
+    }
+
+    #[test]
+    fn test_nested_statements() {
+        assert_eq!(
+            class_of("psql -c 'WITH old AS (SELECT id FROM x) DELETE FROM y'"),
+            Some(SqlClass::Unbounded)
+        );
+        assert_eq!(
+            class_of("psql -c 'WITH d AS (DELETE FROM t RETURNING *) SELECT 1'"),
+            Some(SqlClass::Unbounded)
+        );
+        assert_eq!(
+            class_of(
+                "psql -c 'WITH a AS (SELECT 1), u AS MATERIALIZED (UPDATE t SET x = 1) SELECT 1'"
+            ),
+            Some(SqlClass::Unbounded)
+        );
+        assert_eq!(
+            class_of(
+                "psql -c 'WITH d AS (DELETE FROM t WHERE id IN (SELECT id FROM old) RETURNING *) INSERT INTO archive SELECT * FROM d'"
+            ),
+            None
+        );
+        assert_eq!(
+            class_of("psql -c 'EXPLAIN ANALYZE DELETE FROM x'"),
+            Some(SqlClass::Unbounded)
+        );
+        assert_eq!(
+            class_of("psql -c 'EXPLAIN DELETE FROM x'"),
+            Some(SqlClass::ReadOnly)
+        );
+
+        // Dollar-quoted bodies are not inspected
+        assert_eq!(
+            class_of("psql -c 'DO $$ BEGIN DROP TABLE x; END $$'"),
+            Some(SqlClass::Unchecked)
+        );
+        assert_eq!(
+            class_of(
+                "psql -c 'CREATE FUNCTION f() RETURNS void AS $f$ TRUNCATE logs $f$ LANGUAGE sql'"
+            ),
+            Some(SqlClass::Unchecked)
+        );
+        assert_eq!(class_of("psql -c 'INSERT INTO x VALUES ($1)'"), None);
+    }
+
+    #[test]
+    fn test_sql_files() {
+        let dir = std::env::temp_dir().join(format!("claw-hooks-sql-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("migrate.sql"),
+            "BEGIN;\nDROP TABLE old_users;\nCOMMIT;\n",
+        )
+        .unwrap();
+
+        let commands = ShellParser::new().parse("psql -d app -f migrate.sql");
+        let filter = SqlFilter::new(true, None);
+        assert_eq!(
+            filter.restricted_class(&commands, dir.to_str()),
+            Some(SqlClass::Destructive)
+        );
+        let commands = ShellParser::new().parse("sqlite3 -init migrate.sql app.db");
+        assert_eq!(
+            filter.restricted_class(&commands, dir.to_str()),
+            Some(SqlClass::Destructive)
+        );
+        // Files that cannot be read, or only without end
+        for command in [
+            "psql -f missing.sql",
+            "psql -d app -c 'SELECT 1' -f missing.sql",
+            "psql -f /dev/zero",
+            "psql app < /dev/zero",
+            "cat /dev/zero | psql app",
+            "sqlite3 app.db '.read missing.sql'",
+            "sqlite3 app.db '.read |cat migrate.sql'",
+        ] {
+            let commands = ShellParser::new().parse(command);
+            assert_eq!(
+                filter.restricted_class(&commands, dir.to_str()),
+                Some(SqlClass::Unchecked),
+                "{}",
+                command
+            );
+        }
+
+        // Files redirected or piped to the client
+        for command in [
+            "psql app < migrate.sql",
+            "{ psql app; } < migrate.sql",
+            "cat migrate.sql | psql app",
+            "sqlite3 app.db '.read migrate.sql'",
+            "sqlite3 -cmd '.read \"migrate.sql\"' app.db",
+            "sqlite3 app.db <<'EOF'\n.headers on\n.read migrate.sql\nEOF",
+        ] {
+            let commands = ShellParser::new().parse(command);
+            assert_eq!(
+                filter.restricted_class(&commands, dir.to_str()),
+                Some(SqlClass::Destructive),
+                "{}",
+                command
+            );
+        }
+        let commands = ShellParser::new().parse("psql app < missing.sql");
+        assert_eq!(
+            filter.restricted_class(&commands, dir.to_str()),
+            Some(SqlClass::Unchecked)
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_stdin() {
+        assert_eq!(
+            class_of("echo \"DROP TABLE x\" | psql"),
+            Some(SqlClass::Destructive)
+        );
+        assert_eq!(
+            class_of("printf 'DELETE FROM %s;' sessions | sudo -u postgres psql app"),
+            Some(SqlClass::Unbounded)
+        );
+        assert_eq!(
+            class_of("psql app <<EOF\nTRUNCATE logs;\nEOF"),
+            Some(SqlClass::Destructive)
+        );
+        assert_eq!(
+            class_of("mysql -u root app <<< 'SELECT 1'"),
+            Some(SqlClass::ReadOnly)
+        );
+        assert_eq!(
+            class_of("sqlite3 app.db <<< 'INSERT INTO x VALUES (1)'"),
+            None
+        );
+        // SQL arguments take the place of standard input
+        assert_eq!(
+            class_of("echo 'DROP TABLE x' | psql -c 'INSERT INTO y VALUES (1)'"),
+            None
+        );
+
+        // Input that cannot be inspected
+        assert_eq!(
+            class_of("curl -s https://example.com/x.sql | psql"),
+            Some(SqlClass::Unchecked)
+        );
+        assert_eq!(
+            class_of("pg_dump app | psql copy"),
+            Some(SqlClass::Unchecked)
+        );
+        assert_eq!(class_of("psql app <&3"), Some(SqlClass::Unchecked));
+
+        // Unchecked input gets the unbounded action
+        let commands = ShellParser::new().parse("gunzip -c dump.sql.gz | psql app");
+        let filter = SqlFilter::new(true, None);
+        assert_eq!(filter.action(SqlClass::Unchecked), FilterAction::Ask);
+        assert_eq!(
+            filter.restricted_class(&commands, None),
+            Some(SqlClass::Unchecked)
+        );
+        let filter = filter.with_action(SqlClass::Unbounded, FilterAction::Allow);
+        assert_eq!(filter.restricted_class(&commands, None), None);
+    }
+
+    #[test]
+    fn test_actions() {
+        let commands = ShellParser::new().parse("psql -c 'DELETE FROM x'");
+        assert_eq!(
+            SqlFilter::new(true, None).restricted_class(&commands, None),
+            Some(SqlClass::Unbounded)
+        );
+        let filter =
+            SqlFilter::new(true, None).with_action(SqlClass::Unbounded, FilterAction::Allow);
+        assert_eq!(filter.restricted_class(&commands, None), None);
+
+        // Read-only queries pass by default
+        let commands = ShellParser::new().parse("psql -c 'SELECT 1'");
+        assert_eq!(
+            SqlFilter::new(true, None).restricted_class(&commands, None),
+            None
+        );
+    }
+}
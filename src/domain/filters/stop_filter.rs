@@ -3,9 +3,9 @@
 use std::process::Command;
 use tracing::{debug, warn};
 
-use super::Filter;
+use super::{Filter, FilterContext};
 use crate::config::StopHook;
-use crate::domain::{Decision, HookInput};
+use crate::domain::Decision;
 
 /// Filter for Stop event hooks.
 pub struct StopHookFilter {
@@ -48,12 +48,12 @@ impl StopHookFilter {
 }
 
 impl Filter for StopHookFilter {
-    fn applies_to(&self, input: &HookInput) -> bool {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
         // Applies only to Stop events
-        input.event == "Stop"
+        ctx.input().event == "Stop"
     }
 
-    fn execute(&self, _input: &HookInput) -> Decision {
+    fn execute(&self, _ctx: &FilterContext) -> Decision {
         // Execute all stop hooks
         for hook in &self.hooks {
             if let Err(e) = self.execute_hook(hook) {
@@ -73,7 +73,7 @@ impl Filter for StopHookFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::ToolInput;
+    use crate::domain::{HookInput, ToolInput};
 
     #[test]
     fn test_stop_hook_filter_applies_to_stop_event() {
@@ -87,9 +87,10 @@ mod tests {
             tool_name: "Stop".to_string(),
             tool_input: ToolInput::Stop(crate::domain::StopInput::default()),
             session_id: None,
+            cwd: None,
         };
 
-        assert!(filter.applies_to(&stop_input));
+        assert!(filter.applies_to(&FilterContext::new(&stop_input)));
     }
 
     #[test]
@@ -107,9 +108,10 @@ mod tests {
                 timeout: None,
             }),
             session_id: None,
+            cwd: None,
         };
 
-        assert!(!filter.applies_to(&bash_input));
+        assert!(!filter.applies_to(&FilterContext::new(&bash_input)));
     }
 
     #[test]
@@ -124,9 +126,10 @@ mod tests {
             tool_name: "Stop".to_string(),
             tool_input: ToolInput::Stop(crate::domain::StopInput::default()),
             session_id: None,
+            cwd: None,
         };
 
-        let decision = filter.execute(&stop_input);
+        let decision = filter.execute(&FilterContext::new(&stop_input));
         assert!(matches!(decision, Decision::Allow { .. }));
     }
 }
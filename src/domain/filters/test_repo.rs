@@ -0,0 +1,69 @@
+//! Temporary git repositories for the tests of filters that check project paths.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A git repository in a new temporary directory, removed when dropped.
+pub(super) struct TestRepo {
+    dir: PathBuf,
+}
+
+impl TestRepo {
+    /// Create an empty git repository in a unique directory named after `name`.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "claw-hooks-{}-{}-{}",
+            name,
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let repo = Self { dir };
+        repo.git(&["init", "-q"]);
+        repo
+    }
+
+    /// The repository directory.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The repository directory as the working directory of a hook input.
+    pub fn cwd(&self) -> Option<&str> {
+        self.dir.to_str()
+    }
+
+    /// Create a directory in the repository, along with its parents.
+    pub fn create_dir(&self, path: &str) {
+        fs::create_dir_all(self.dir.join(path)).unwrap();
+    }
+
+    /// Write a file in the repository, creating its parent directories.
+    pub fn write(&self, path: &str, contents: &str) {
+        let path = self.dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Run git in the repository.
+    pub fn git(&self, args: &[&str]) {
+        Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .output()
+            .unwrap();
+    }
+}
+
+impl Drop for TestRepo {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
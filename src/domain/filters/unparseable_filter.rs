@@ -0,0 +1,145 @@
+//! Unparseable command filter implementation.
+
+use super::{Filter, FilterContext};
+use crate::domain::parser::ParseConfidence;
+use crate::domain::{Decision, FilterAction};
+
+/// Default message for commands the parser cannot fully understand, after the reason.
+const DEFAULT_UNPARSEABLE_MESSAGE: &str = "so its commands cannot all be checked. Fix the syntax or split it into simpler commands. Configure unparseable_action in config.toml to change this behavior.";
+
+/// Filter for commands the parser cannot fully understand: syntax errors
+/// (`echo "unterminated`, `(cd x`) and command strings nested beyond the
+/// maximum depth (`bash -c "bash -c '...'"`).
+///
+/// The other filters only see the commands the parser extracted, so without
+/// this filter such commands would usually be allowed.
+pub struct UnparseableFilter {
+    enabled: bool,
+    custom_message: Option<String>,
+    action: FilterAction,
+}
+
+impl UnparseableFilter {
+    /// Create a new UnparseableFilter with optional custom message.
+    pub fn new(enabled: bool, custom_message: Option<String>) -> Self {
+        Self {
+            enabled,
+            custom_message,
+            action: FilterAction::Ask,
+        }
+    }
+
+    /// Set whether unparseable commands are blocked, need user confirmation or pass.
+    pub fn with_action(mut self, action: FilterAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    fn message(&self, reason: &str) -> String {
+        if let Some(message) = &self.custom_message {
+            return message.clone();
+        }
+        format!(
+            "🚫 Unparseable command: {}, {}",
+            reason, DEFAULT_UNPARSEABLE_MESSAGE
+        )
+    }
+}
+
+impl Filter for UnparseableFilter {
+    fn applies_to(&self, ctx: &FilterContext) -> bool {
+        // Commands are only parsed for the Bash tool in PreToolUse events
+        self.enabled && ctx.confidence() != &ParseConfidence::Complete
+    }
+
+    fn execute(&self, ctx: &FilterContext) -> Decision {
+        match ctx.confidence() {
+            ParseConfidence::Uncertain(reason) => self.action.decision(self.message(reason)),
+            ParseConfidence::Complete => Decision::allow(),
+        }
+    }
+
+    fn priority(&self) -> u32 {
+        45 // After the filters that name what they block, before custom filters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parser::ShellParser;
+    use crate::domain::ToolInput;
+    use crate::domain::{BashInput, HookInput};
+
+    fn bash_input(command: &str) -> HookInput {
+        HookInput {
+            event: "PreToolUse".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: ToolInput::Bash(BashInput {
+                command: command.to_string(),
+                timeout: None,
+            }),
+            session_id: None,
+            cwd: None,
+        }
+    }
+
+    fn decide(filter: &UnparseableFilter, command: &str) -> Option<Decision> {
+        let input = bash_input(command);
+        let ctx = FilterContext::new(&input);
+        filter.applies_to(&ctx).then(|| filter.execute(&ctx))
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let filter = UnparseableFilter::new(true, None);
+
+        match decide(&filter, "echo \"unterminated; rm -rf /") {
+            Some(Decision::Ask { message }) => {
+                assert!(message.starts_with("🚫 Unparseable command: syntax error near"))
+            }
+            other => panic!("expected Ask, got {:?}", other),
+        }
+        assert!(decide(&filter, "(cd build && make").is_some());
+        assert!(decide(&filter, "if true; then rm x").is_some());
+
+        assert!(decide(&filter, "cd build && make").is_none());
+        assert!(decide(&filter, "for f in *.rs; do echo \"$f\"; done").is_none());
+        assert!(decide(&filter, "bash -c \"bash -c 'ls'\"").is_none());
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let nested = "bash -c \"bash -c 'bash -c ls'\"";
+        let mut parser = ShellParser::new().with_max_depth(2);
+        parser.parse(nested);
+        assert_eq!(
+            parser.confidence(),
+            &ParseConfidence::Uncertain(
+                "commands are nested beyond the maximum depth of 2".to_string()
+            )
+        );
+
+        let mut parser = ShellParser::new();
+        parser.parse(nested);
+        assert_eq!(parser.confidence(), &ParseConfidence::Complete);
+    }
+
+    #[test]
+    fn test_action() {
+        let command = "echo $(";
+        let filter = UnparseableFilter::new(true, None).with_action(FilterAction::Block);
+        assert!(matches!(
+            decide(&filter, command),
+            Some(Decision::Block { .. })
+        ));
+
+        let filter = UnparseableFilter::new(true, Some("custom".to_string()));
+        assert!(matches!(
+            decide(&filter, command),
+            Some(Decision::Ask { message }) if message == "custom"
+        ));
+
+        assert!(decide(&UnparseableFilter::new(false, None), command).is_none());
+    }
+}
@@ -5,15 +5,17 @@
 //! - Filter trait and implementations
 //! - Shell command parser
 //! - Logger with rotation
+//! - Project workspace detection
 
 mod error;
 pub mod filters;
 pub mod logger;
 pub mod parser;
 mod types;
+pub mod workspace;
 
 pub use filters::FilterChain;
-pub use types::{Decision, HookInput, ToolInput};
+pub use types::{Decision, FilterAction, HookInput, ToolInput};
 
 // Allow unused for potential future use / library API
 #[allow(unused)]
@@ -23,3 +25,5 @@ pub use error::ClawError;
 pub use types::{BashInput, FileOperationInput, HookOutput, StopInput};
 
 pub use parser::parse_shell_tokens;
+pub use parser::ShellDialect;
+pub use parser::Wrapper;
@@ -3,21 +3,102 @@
 //! Provides functionality to extract commands from shell command strings.
 //! Uses tree-sitter-bash for accurate AST-based parsing when the `ast-parser` feature is enabled.
 
+mod command;
+mod deobfuscate;
+mod fallback;
+mod interpreter;
+mod windows;
+mod wrappers;
+
+use command::{CommandContext, ShellInvocation};
+pub use command::{ParsedCommand, Redirect, ScriptSource, Stdin};
+pub use deobfuscate::DECODERS;
+pub use windows::ShellDialect;
+pub use wrappers::{Wrapper, WrapperRegistry};
+
+#[cfg(feature = "ast-parser")]
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 #[cfg(feature = "ast-parser")]
 use tree_sitter::{Node, Parser};
 
-/// Wrappers that execute another command
-const COMMAND_WRAPPERS: &[&str] = &[
-    "sudo", "env", "nohup", "nice", "ionice", "time", "timeout", "strace", "ltrace", "doas",
-];
-
 /// Shells that can execute command strings via -c flag
 const SHELL_COMMANDS: &[&str] = &["bash", "sh", "zsh", "ksh", "csh", "tcsh", "fish", "dash"];
 
+/// Commands that run a script file in the current shell
+const SOURCE_COMMANDS: &[&str] = &["source", "."];
+
+/// Reserved words left in front of a command where tree-sitter-bash does not
+/// recognize the compound command (`! { rm x; }`, `time { rm x; }`)
+const RESERVED_PREFIXES: &[&str] = &["!", "{", "}"];
+
+/// Default maximum nesting of command strings (`bash -c "bash -c '...'"`)
+const DEFAULT_MAX_DEPTH: usize = 8;
+
+/// Largest script file read for inspection (sourced files, `psql -f` files)
+pub const MAX_SCRIPT_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Commands that assign the variables named in their arguments at runtime
+#[cfg(feature = "ast-parser")]
+const VARIABLE_ASSIGNING_COMMANDS: &[&str] =
+    &["read", "mapfile", "readarray", "unset", "getopts", "printf"];
+
 /// Shell command parser using tree-sitter-bash for AST-based analysis.
 pub struct ShellParser {
+    /// Shell the parsed command strings are written for
+    dialect: ShellDialect,
     #[cfg(feature = "ast-parser")]
     parser: Parser,
+    /// Variables and aliases defined earlier in the command string being parsed
+    #[cfg(feature = "ast-parser")]
+    symbols: SymbolTable,
+    /// Nesting depth of the command string being parsed (shell -c, eval, sourced files)
+    depth: usize,
+    /// Nesting depth beyond which command strings are not parsed
+    max_depth: usize,
+    /// Directory the command string runs in, for relative paths of sourced files
+    cwd: Option<PathBuf>,
+    /// Wrappers and launchers whose command is unwrapped (sudo, timeout, pnpm exec)
+    wrappers: Arc<WrapperRegistry>,
+    /// How completely the command string passed to `parse` was understood
+    confidence: ParseConfidence,
+    /// Parse with the fallback parser only, to compare it with tree-sitter
+    #[cfg(all(test, feature = "ast-parser"))]
+    fallback_only: bool,
+}
+
+/// How completely a command string was understood by the parser.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ParseConfidence {
+    /// Every statement was parsed
+    #[default]
+    Complete,
+    /// Part of the string could not be parsed, so the commands may be incomplete;
+    /// holds the reason (`syntax error near ...`, `missing "fi"`)
+    Uncertain(String),
+}
+
+/// Variables and aliases defined by a command string, used to resolve
+/// invocations such as `X=rm; $X` and `alias d=rm; d`.
+///
+/// Function bodies need no entry: they are analysed where they are defined.
+#[cfg(feature = "ast-parser")]
+#[derive(Default)]
+struct SymbolTable {
+    /// Variable name to its (unquoted) value
+    variables: HashMap<String, String>,
+    /// Alias name to its (unquoted) value
+    aliases: HashMap<String, String>,
+}
+
+/// Redirects and context inherited by the commands beneath a node.
+#[derive(Clone, Default)]
+struct Scope {
+    context: CommandContext,
+    redirects: Vec<Redirect>,
 }
 
 impl ShellParser {
@@ -29,15 +110,70 @@ impl ShellParser {
             parser
                 .set_language(&tree_sitter_bash::LANGUAGE.into())
                 .expect("Failed to load tree-sitter-bash grammar");
-            Self { parser }
+            Self {
+                dialect: ShellDialect::Bash,
+                parser,
+                symbols: SymbolTable::default(),
+                depth: 0,
+                max_depth: DEFAULT_MAX_DEPTH,
+                cwd: None,
+                wrappers: WrapperRegistry::builtin(),
+                confidence: ParseConfidence::Complete,
+                #[cfg(test)]
+                fallback_only: false,
+            }
         }
         #[cfg(not(feature = "ast-parser"))]
         {
-            Self {}
+            Self {
+                dialect: ShellDialect::Bash,
+                depth: 0,
+                max_depth: DEFAULT_MAX_DEPTH,
+                cwd: None,
+                wrappers: WrapperRegistry::builtin(),
+                confidence: ParseConfidence::Complete,
+            }
         }
     }
 
-    /// Extract commands from a shell command string.
+    /// Parse command strings written for PowerShell or cmd.exe instead of bash.
+    pub fn with_dialect(mut self, dialect: ShellDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Set how deeply command strings may nest (`bash -c`, `eval`, sourced files,
+    /// including files that source themselves) before the rest is left unparsed
+    /// and the parse is uncertain.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the directory the command string runs in, which relative paths of
+    /// sourced files resolve against (`source ./env.sh`).
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    /// Set the wrappers whose command is unwrapped, in place of the built-ins.
+    pub fn with_wrappers(mut self, wrappers: Arc<WrapperRegistry>) -> Self {
+        self.wrappers = wrappers;
+        self
+    }
+
+    /// The wrappers whose command is unwrapped.
+    pub fn wrappers(&self) -> &Arc<WrapperRegistry> {
+        &self.wrappers
+    }
+
+    /// How completely the command string of the last [`ShellParser::parse`] was understood.
+    pub fn confidence(&self) -> &ParseConfidence {
+        &self.confidence
+    }
+
+    /// Parse a shell command string into the commands it runs.
     ///
     /// Handles:
     /// - Pipelines (|)
@@ -46,201 +182,433 @@ impl ShellParser {
     /// - Command wrappers (sudo, env, nohup, etc.)
     /// - Subshells (bash -c, sh -c, etc.)
     /// - xargs with commands
-    #[cfg(feature = "ast-parser")]
-    pub fn extract_commands(&mut self, command: &str) -> Vec<String> {
-        let tree = match self.parser.parse(command, None) {
-            Some(tree) => tree,
-            None => return self.extract_commands_fallback(command),
-        };
-
-        let root = tree.root_node();
-        let mut commands = Vec::new();
-        // Now handles wrappers and subshells directly within extract_commands_from_node
-        // using AST-based argument extraction instead of string search
-        self.extract_commands_from_node(root, command, &mut commands);
+    /// - Heredoc/here-string scripts read by shells and interpreters
+    /// - Variables, aliases and functions defined earlier in the same string
+    ///
+    /// Commands run by other commands (wrappers, xargs, find -exec, shell -c, eval,
+    /// sourced files) are returned as separate entries after the command running them.
+    ///
+    /// PowerShell and cmd.exe strings are translated into equivalent shell commands first,
+    /// so cmdlets and builtins are reported as the commands they act like
+    /// (`Remove-Item` and `rd /s` as `rm`, `Stop-Process` as `kill`).
+    pub fn parse(&mut self, command: &str) -> Vec<ParsedCommand> {
+        self.confidence = ParseConfidence::Complete;
+        if self.dialect == ShellDialect::Bash {
+            return self.parse_bash(command);
+        }
 
+        // Parse all statements at once so that variables carry over between them,
+        // then give each command the range of the statement it comes from
+        let mut script = String::new();
+        let mut statements = Vec::new();
+        for (span, code) in windows::translate(self.dialect, command) {
+            statements.push((script.len(), span));
+            script.push_str(&code);
+            script.push('\n');
+        }
+        let mut commands = self.parse_bash(&script);
+        for cmd in &mut commands {
+            let i = statements.partition_point(|(offset, _)| *offset <= cmd.span.start);
+            cmd.span = statements[i.saturating_sub(1)].1.clone();
+        }
         commands
     }
 
-    #[cfg(not(feature = "ast-parser"))]
-    pub fn extract_commands(&self, command: &str) -> Vec<String> {
-        self.extract_commands_fallback(command)
+    /// Parse a bash command string, unless it is nested in more command strings
+    /// (shell -c, eval, sourced files, alias expansions) than the maximum depth.
+    fn parse_bash(&mut self, command: &str) -> Vec<ParsedCommand> {
+        if self.depth > self.max_depth {
+            self.mark_uncertain(format!(
+                "commands are nested beyond the maximum depth of {}",
+                self.max_depth
+            ));
+            return Vec::new();
+        }
+        self.depth += 1;
+        let commands = self.parse_script(command);
+        self.depth -= 1;
+        commands
     }
 
-    /// Extract full command strings (command name + arguments) from a shell command string.
-    /// Used by custom filters to match patterns like "npm install".
+    /// Parse a bash command string with tree-sitter.
     #[cfg(feature = "ast-parser")]
-    pub fn extract_command_strings(&mut self, command: &str) -> Vec<String> {
+    fn parse_script(&mut self, command: &str) -> Vec<ParsedCommand> {
+        // Nested strings (shell -c, alias expansion) share the symbol table of the outermost one
+        if self.depth == 1 {
+            self.symbols = SymbolTable::default();
+        }
+        #[cfg(test)]
+        if self.fallback_only {
+            return self.parse_fallback(command);
+        }
         let tree = match self.parser.parse(command, None) {
             Some(tree) => tree,
-            None => return self.extract_command_strings_fallback(command),
+            None => {
+                self.mark_uncertain("the shell parser gave up".to_string());
+                return self.parse_fallback(command);
+            }
         };
 
         let root = tree.root_node();
-        let mut command_strings = Vec::new();
-        self.extract_command_strings_from_node(root, command, &mut command_strings);
+        if let Some(error) = syntax_error(root, command) {
+            self.mark_uncertain(error);
+        }
+        let mut commands = Vec::new();
+        self.parse_node(root, command, &Scope::default(), &mut commands);
 
-        command_strings
+        commands
     }
 
     #[cfg(not(feature = "ast-parser"))]
-    pub fn extract_command_strings(&self, command: &str) -> Vec<String> {
-        self.extract_command_strings_fallback(command)
+    fn parse_script(&mut self, command: &str) -> Vec<ParsedCommand> {
+        self.parse_fallback(command)
+    }
+
+    /// Record why the command string is not fully understood, keeping the first reason.
+    fn mark_uncertain(&mut self, reason: String) {
+        if self.confidence == ParseConfidence::Complete {
+            self.confidence = ParseConfidence::Uncertain(reason);
+        }
     }
 
-    /// Extract full command strings from AST node recursively
-    /// Uses raw arguments with quotes preserved for accurate pattern matching.
+    /// Parse commands from AST node recursively
     #[cfg(feature = "ast-parser")]
-    fn extract_command_strings_from_node(
+    fn parse_node(
         &mut self,
         node: Node,
         source: &str,
-        command_strings: &mut Vec<String>,
+        scope: &Scope,
+        commands: &mut Vec<ParsedCommand>,
     ) {
+        self.record_symbols(node, source);
+        if let Some((alias, expanded)) = self.expand_alias(node, source) {
+            let nested = self.with_alias_suspended(&alias, |parser| parser.parse_bash(&expanded));
+            // The alias is replaced in place, so its commands keep the invocation's redirects
+            for mut cmd in nested {
+                cmd.nest_in(&scope.context, &node.byte_range(), None);
+                cmd.redirects.splice(..0, scope.redirects.iter().cloned());
+                commands.push(cmd);
+            }
+            return;
+        }
+
         match node.kind() {
             "command" | "simple_command" => {
-                if let Some(cmd_name) = self.get_command_name(node, source) {
-                    if !cmd_name.is_empty() {
-                        // Build full command string: command + arguments (with quotes preserved)
-                        let args_raw = self.get_command_arguments_raw(node, source);
-                        let full_cmd = if args_raw.is_empty() {
-                            cmd_name.clone()
-                        } else {
-                            format!("{} {}", cmd_name, args_raw.join(" "))
-                        };
-                        command_strings.push(full_cmd);
-
-                        // Handle shell -c "command" - extract nested command strings
-                        // Use stripped args for shell command extraction
-                        if SHELL_COMMANDS.contains(&cmd_name.as_str()) {
-                            let args = self.get_command_arguments(node, source);
-                            if let Some(shell_cmd) = Self::extract_shell_c_from_args(&args) {
-                                let nested = self.extract_command_strings(&shell_cmd);
-                                command_strings.extend(nested);
-                            }
-                        }
+                let name = self
+                    .get_command_name(node, source)
+                    .filter(|name| !name.is_empty());
+                let mut script = None;
+                if let Some(name) = &name {
+                    let mut argv = vec![name.clone()];
+                    argv.extend(self.get_command_arguments(node, source));
+                    // Heredoc/here-string scripts read by shells and interpreters
+                    let stdin = Self::stdin_redirect(node, source);
+                    script = match &stdin {
+                        Some(Stdin::Text(input)) => self.stdin_script(argv.clone(), input.clone()),
+                        _ => None,
+                    };
+                    let mut cmd = ParsedCommand::new(argv, node.byte_range());
+                    if let Some(word) = node.child_by_field_name("name") {
+                        cmd.command_word = unquote(&source[word.byte_range()]);
+                    }
+                    cmd.env = Self::get_env_assignments(node, source);
+                    cmd.redirects = scope.redirects.clone();
+                    cmd.context = scope.context.clone();
+                    // Here-strings attach to the command itself
+                    cmd.context.stdin_redirected |= Self::redirects_stdin(node);
+                    if stdin.is_some() {
+                        cmd.context.stdin = stdin;
+                    }
+                    cmd.context.obfuscated |= Self::is_obfuscated(node, source);
+                    self.push_command(cmd, commands);
+                }
 
-                        // Handle xargs - extract the command being run
-                        if cmd_name == "xargs" {
-                            let args = self.get_command_arguments(node, source);
-                            // Build xargs target command string
-                            let xargs_args: Vec<_> =
-                                args.iter().filter(|a| !a.starts_with('-')).collect();
-                            if !xargs_args.is_empty() {
-                                let xargs_cmd = xargs_args
-                                    .iter()
-                                    .map(|s| s.as_str())
-                                    .collect::<Vec<_>>()
-                                    .join(" ");
-                                command_strings.push(xargs_cmd);
-                            }
-                        }
+                if let Some(script) = script {
+                    for mut cmd in self.parse_bash(&script) {
+                        cmd.nest_in(&scope.context, &node.byte_range(), name.as_deref());
+                        commands.push(cmd);
                     }
                 }
-                // Recurse into children for command substitutions
+
+                // Command substitutions in arguments and assignments
+                // e.g., echo $(yarn --version) - need to find yarn inside $()
+                // A coprocess runs its subshell in the background (`coproc (rm x)`)
+                let mut inner = scope.clone();
+                inner.context.background |= name.as_deref() == Some("coproc");
                 for child in node.children(&mut node.walk()) {
-                    self.extract_command_strings_from_node(child, source, command_strings);
+                    self.parse_node(child, source, &inner, commands);
                 }
             }
-            "subshell" | "command_substitution" => {
+            "redirected_statement" => {
+                let mut inner = scope.clone();
+                inner.context.stdin_redirected |= Self::redirects_stdin(node);
+                if let Some(stdin) = Self::stdin_redirect(node, source) {
+                    inner.context.stdin = Some(stdin);
+                }
+                inner.redirects.extend(
+                    Self::statement_redirects(node)
+                        .into_iter()
+                        .filter(|child| child.kind() == "file_redirect")
+                        .filter_map(|child| self.get_file_redirect(child, source)),
+                );
+
+                // A redirect without a command (`> file`) truncates its target like `: > file`
+                let has_body = node
+                    .children(&mut node.walk())
+                    .any(|child| child.is_named() && !child.kind().ends_with("_redirect"));
+                if !has_body && !inner.redirects.is_empty() {
+                    let mut cmd = ParsedCommand::new(vec![":".to_string()], node.byte_range());
+                    cmd.redirects = inner.redirects.clone();
+                    cmd.context = scope.context.clone();
+                    commands.push(cmd);
+                }
+
                 for child in node.children(&mut node.walk()) {
-                    self.extract_command_strings_from_node(child, source, command_strings);
+                    let child_scope = match child.kind() {
+                        kind if kind.ends_with("_redirect") => scope,
+                        _ => &inner,
+                    };
+                    self.parse_node(child, source, child_scope, commands);
                 }
             }
-            _ => {
+            "pipeline" => {
+                let stages: Vec<Node> = node
+                    .children(&mut node.walk())
+                    .filter(|child| child.is_named())
+                    .collect();
+                self.parse_pipeline(
+                    &stages,
+                    scope,
+                    commands,
+                    |parser, stage, inner, commands| {
+                        parser.parse_node(*stage, source, inner, commands)
+                    },
+                );
+            }
+            "subshell" => {
+                let mut inner = scope.clone();
+                inner.context.subshell = true;
+                self.parse_statements(node, source, &inner, commands);
+            }
+            "command_substitution" | "process_substitution" => {
+                let inner = Self::substitution_scope(scope);
                 for child in node.children(&mut node.walk()) {
-                    self.extract_command_strings_from_node(child, source, command_strings);
+                    self.parse_node(child, source, &inner, commands);
                 }
             }
+            _ => self.parse_statements(node, source, scope, commands),
         }
     }
 
-    /// Fallback parser for extract_command_strings
-    fn extract_command_strings_fallback(&self, command: &str) -> Vec<String> {
-        let mut command_strings = Vec::new();
+    /// Parse the children of a node, marking statements followed by `&` as run in the background.
+    ///
+    /// tree-sitter-bash splits a brace group after `coproc` into statements
+    /// (`coproc { a; b; }` gives `coproc { a`, `b` and `}`), so the statements up to
+    /// the closing brace are marked as run in the coprocess too.
+    #[cfg(feature = "ast-parser")]
+    fn parse_statements(
+        &mut self,
+        node: Node,
+        source: &str,
+        scope: &Scope,
+        commands: &mut Vec<ParsedCommand>,
+    ) {
+        let mut in_coprocess = false;
+        for child in node.children(&mut node.walk()) {
+            let words = Self::statement_words(child, source);
+            if words.first() == Some(&"}") {
+                in_coprocess = false;
+            }
 
-        for segment in command.split(';') {
-            for part in Self::split_by_logical_ops(segment.trim()) {
-                for pipe_part in part.split('|') {
-                    let cmd = pipe_part.trim();
-                    if !cmd.is_empty() {
-                        // Extract full command string (not just command name)
-                        let (cmd_name, args) = self.extract_command_with_args_fallback(cmd);
-                        if !cmd_name.is_empty() {
-                            let full_cmd = if args.is_empty() {
-                                cmd_name
-                            } else {
-                                format!("{} {}", cmd_name, args.join(" "))
-                            };
-                            command_strings.push(full_cmd);
-                        }
-                    }
-                }
+            let mut inner = scope.clone();
+            inner.context.background |=
+                in_coprocess || child.next_sibling().is_some_and(|next| next.kind() == "&");
+            inner.context.subshell |= in_coprocess;
+            self.parse_node(child, source, &inner, commands);
+
+            if words.first() == Some(&"coproc") && words.contains(&"{") {
+                in_coprocess = true;
+            }
+        }
+    }
+
+    /// Get the source words of a simple command statement (`coproc`, `{`, `rm`, `x`).
+    #[cfg(feature = "ast-parser")]
+    fn statement_words<'a>(node: Node, source: &'a str) -> Vec<&'a str> {
+        if node.kind() != "command" {
+            return Vec::new();
+        }
+        node.children(&mut node.walk())
+            .filter(|child| child.is_named())
+            .map(|child| &source[child.byte_range()])
+            .collect()
+    }
+
+    /// Get the children of a node along with the redirects tree-sitter-bash nests
+    /// in a heredoc redirect (`cat <<EOF > out` gives `> out` inside `<<EOF`).
+    #[cfg(feature = "ast-parser")]
+    fn statement_redirects(node: Node) -> Vec<Node> {
+        let mut redirects = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            redirects.push(child);
+            if child.kind() == "heredoc_redirect" {
+                redirects.extend(
+                    child
+                        .children(&mut child.walk())
+                        .filter(|nested| nested.kind().ends_with("_redirect")),
+                );
             }
         }
+        redirects
+    }
 
-        command_strings
+    /// Check if a command or redirected statement node reads stdin from a file,
+    /// heredoc or here-string.
+    #[cfg(feature = "ast-parser")]
+    fn redirects_stdin(node: Node) -> bool {
+        Self::statement_redirects(node)
+            .iter()
+            .any(|child| match child.kind() {
+                "heredoc_redirect" | "herestring_redirect" => true,
+                "file_redirect" => child
+                    .children(&mut child.walk())
+                    .any(|op| !op.is_named() && op.kind().starts_with('<')),
+                _ => false,
+            })
     }
 
-    /// Extract commands from AST node recursively
+    /// Check if the name of a command node hides the command it runs
+    /// (`$'\x72\x6d'`, `r{m,}`, `$(printf '\162\155')`).
     #[cfg(feature = "ast-parser")]
-    fn extract_commands_from_node(&mut self, node: Node, source: &str, commands: &mut Vec<String>) {
-        match node.kind() {
-            "command" | "simple_command" => {
-                // Find the command_name child
-                if let Some(cmd_name) = self.get_command_name(node, source) {
-                    if !cmd_name.is_empty() {
-                        commands.push(cmd_name.clone());
-                    }
+    fn is_obfuscated(node: Node, source: &str) -> bool {
+        node.children(&mut node.walk())
+            .find(|child| child.kind() == "command_name")
+            .is_some_and(|name| deobfuscate::is_encoded(&source[name.byte_range()]))
+    }
 
-                    // Get arguments for further processing
-                    let args = self.get_command_arguments(node, source);
+    /// Get the environment assignments prefixed to a command node (`FOO=1 cmd`)
+    #[cfg(feature = "ast-parser")]
+    fn get_env_assignments(node: Node, source: &str) -> Vec<(String, String)> {
+        node.children(&mut node.walk())
+            .filter(|child| child.kind() == "variable_assignment")
+            .filter_map(|assignment| {
+                let name = assignment.child_by_field_name("name")?;
+                let value = assignment
+                    .child_by_field_name("value")
+                    .map(|value| unquote(&source[value.byte_range()]))
+                    .unwrap_or_default();
+                Some((source[name.byte_range()].to_string(), value))
+            })
+            .collect()
+    }
 
-                    // Handle command wrappers at AST level (sudo, env, etc.)
-                    if COMMAND_WRAPPERS.contains(&cmd_name.as_str()) {
-                        self.process_wrapper_args(&args, commands);
-                    }
+    /// Get the redirect described by a `file_redirect` node, if it writes to a file
+    #[cfg(feature = "ast-parser")]
+    fn get_file_redirect(&self, node: Node, source: &str) -> Option<Redirect> {
+        let mut operator = None;
+        let mut target = None;
+        for child in node.children(&mut node.walk()) {
+            match child.kind() {
+                "file_descriptor" => {}
+                _ if child.is_named() => target = target.or(Some(child)),
+                kind => operator = Some(kind),
+            }
+        }
 
-                    // Handle shell -c "command" at AST level
-                    if SHELL_COMMANDS.contains(&cmd_name.as_str()) {
-                        if let Some(shell_cmd) = Self::extract_shell_c_from_args(&args) {
-                            let nested = self.extract_commands(&shell_cmd);
-                            for nested_cmd in nested {
-                                if !commands.contains(&nested_cmd) {
-                                    commands.push(nested_cmd);
-                                }
-                            }
-                        }
-                    }
+        let operator = operator?;
+        let target = unquote(&source[target?.byte_range()]);
+        if !operator.contains('>') || Self::duplicates_descriptor(operator, &target) {
+            return None;
+        }
+        let target = match is_dynamic_command_name(&target) {
+            true => self.expand_variables(&target).unwrap_or(target),
+            false => target,
+        };
 
-                    // Handle xargs at AST level
-                    if cmd_name == "xargs" {
-                        if let Some(xargs_cmd) = Self::extract_xargs_from_args(&args) {
-                            if !commands.contains(&xargs_cmd) {
-                                commands.push(xargs_cmd);
-                            }
-                        }
-                    }
-                }
-                // Also recurse into children to find command substitutions in arguments
-                // e.g., echo $(yarn --version) - need to find yarn inside $()
-                for child in node.children(&mut node.walk()) {
-                    self.extract_commands_from_node(child, source, commands);
-                }
+        Some(Redirect {
+            operator: operator.to_string(),
+            target,
+        })
+    }
+
+    /// Check if a redirect duplicates or closes a file descriptor (`2>&1`, `>&-`)
+    /// instead of opening a file.
+    fn duplicates_descriptor(operator: &str, target: &str) -> bool {
+        operator.ends_with('&') && (target == "-" || target.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    /// Push a command along with the commands it runs
+    /// (wrapped commands, shell -c/eval strings, sourced files and xargs targets).
+    fn push_command(&mut self, mut cmd: ParsedCommand, commands: &mut Vec<ParsedCommand>) {
+        // Reserved words in front of the command (`{ rm x`)
+        if RESERVED_PREFIXES.contains(&cmd.name()) {
+            cmd.argv.remove(0);
+            if !cmd.argv.is_empty() {
+                cmd.command_word = cmd.argv[0].clone();
+                self.push_command(cmd, commands);
             }
-            "subshell" | "command_substitution" => {
-                // Parse contents of subshell/command substitution
-                for child in node.children(&mut node.walk()) {
-                    self.extract_commands_from_node(child, source, commands);
-                }
+            return;
+        }
+
+        let mut words = self.resolve_command_words(&cmd.argv[0]);
+        words.extend(cmd.argv.drain(1..));
+        cmd.argv = words;
+        let name = cmd.name();
+        let args = cmd.args();
+        let mut nested = Vec::new();
+
+        if name == "coproc" {
+            // A name is only given before a compound command (`coproc NAME { ...; }`)
+            let start = match args {
+                [_, open, ..] if open == "{" => 1,
+                _ => 0,
+            };
+            if start < args.len() {
+                let mut coprocess = cmd.wrapped(args[start..].to_vec(), Vec::new());
+                coprocess.context.subshell = true;
+                coprocess.context.background = true;
+                self.push_command(coprocess, &mut nested);
             }
-            _ => {
-                // Recurse into children
-                for child in node.children(&mut node.walk()) {
-                    self.extract_commands_from_node(child, source, commands);
-                }
+        }
+
+        if let Some((options, argv)) = self.wrappers.split_command(name, args) {
+            let env = options
+                .iter()
+                .filter(|arg| !arg.starts_with('-'))
+                .filter_map(|arg| arg.split_once('='))
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            self.push_command(cmd.wrapped(argv, env), &mut nested);
+        }
+
+        // Options the shell may read differently could hide the code it runs
+        if SHELL_COMMANDS.contains(&name) {
+            if let Some(option) = ShellInvocation::parse(args).unknown_option {
+                self.mark_uncertain(format!("unrecognized option `{}` for {}", option, name));
+            }
+        }
+
+        if let Some(script) = self.nested_script(name, args) {
+            for mut nested_cmd in self.parse_bash(&script) {
+                nested_cmd.nest_in(&cmd.context, &cmd.span, Some(name));
+                nested.push(nested_cmd);
+            }
+        }
+
+        if name == "xargs" {
+            if let Some(i) = Self::find_xargs_command(args) {
+                self.push_command(cmd.wrapped(args[i..].to_vec(), Vec::new()), &mut nested);
+            }
+        }
+
+        if name == "find" {
+            for target in Self::extract_find_exec_from_args(args) {
+                self.push_command(cmd.wrapped(target, Vec::new()), &mut nested);
             }
         }
+
+        commands.push(cmd);
+        commands.extend(nested);
     }
 
     /// Get command arguments from AST node (excludes the command name itself)
@@ -271,18 +639,40 @@ impl ShellParser {
             match child.kind() {
                 "command_name" => {
                     found_command_name = true;
+                    // Words a variable command name expands to beyond the name itself
+                    let words = self.resolve_command_words(&source[child.byte_range()]);
+                    args.extend(words.into_iter().skip(1));
                 }
-                "word" | "string" | "raw_string" | "simple_expansion" | "expansion"
-                | "concatenation" => {
-                    if found_command_name {
-                        let text = if strip_quotes {
-                            source[child.byte_range()]
-                                .trim_matches(|c| c == '"' || c == '\'')
-                                .to_string()
-                        } else {
-                            source[child.byte_range()].to_string()
-                        };
-                        args.push(text);
+                "word"
+                | "number"
+                | "string"
+                | "raw_string"
+                | "ansi_c_string"
+                | "translated_string"
+                | "simple_expansion"
+                | "expansion"
+                | "concatenation"
+                | "command_substitution"
+                | "process_substitution"
+                | "arithmetic_expansion"
+                    if found_command_name =>
+                {
+                    let text = &source[child.byte_range()];
+                    if strip_quotes && matches!(child.kind(), "concatenation" | "ansi_c_string") {
+                        // Quotes may open inside the word (`--command="DROP TABLE x"`),
+                        // and braces expand to several words (`/{etc,usr}`)
+                        args.extend(
+                            deobfuscate::expand_braces(text)
+                                .iter()
+                                .map(|word| unquote(word)),
+                        );
+                    } else if strip_quotes && matches!(child.kind(), "string" | "raw_string") {
+                        // Quotes inside the string belong to it (`"bash -c 'rm x'"`)
+                        args.push(unquote(text));
+                    } else if strip_quotes {
+                        args.push(text.trim_matches(|c| c == '"' || c == '\'').to_string());
+                    } else {
+                        args.push(text.to_string());
                     }
                 }
                 _ => {}
@@ -292,51 +682,162 @@ impl ShellParser {
         args
     }
 
-    /// Extract command from shell -c arguments
-    #[cfg(feature = "ast-parser")]
+    /// Extract the code a shell runs with -c, alone or combined with other
+    /// options (`bash -lc '...'`, `sh -ec '...'`, `bash -c -- '...'`)
     fn extract_shell_c_from_args(args: &[String]) -> Option<String> {
-        for (i, arg) in args.iter().enumerate() {
-            if arg == "-c" && i + 1 < args.len() {
-                return Some(args[i + 1].clone());
-            }
+        match ShellInvocation::parse(args).source {
+            ScriptSource::Code(code) => Some(code),
+            _ => None,
         }
-        None
     }
 
-    /// Extract command from xargs arguments
+    /// Get the shell code a command runs from its arguments:
+    /// the -c string of a shell, the arguments of `eval` joined by spaces,
+    /// or the shell equivalent of inline interpreter code (`python -c`, `node -e`)
+    /// and of PowerShell or cmd.exe code (`powershell -Command`, `cmd /c`).
+    fn inline_script(name: &str, args: &[String]) -> Option<String> {
+        if SHELL_COMMANDS.contains(&name) {
+            return Self::extract_shell_c_from_args(args);
+        }
+        if name == "eval" && !args.is_empty() {
+            return Some(args.join(" "));
+        }
+        interpreter::shell_equivalent(name, args).or_else(|| windows::shell_equivalent(name, args))
+    }
+
+    /// Get the shell code a command runs: inline code (`bash -c`, `eval`)
+    /// or the contents of a file run with `source` or `.`.
+    ///
+    /// A sourced file that cannot be read in full makes the parse uncertain.
+    fn nested_script(&mut self, name: &str, args: &[String]) -> Option<String> {
+        if let Some(script) = Self::inline_script(name, args) {
+            return Some(script);
+        }
+        if !is_source_command(name) {
+            return None;
+        }
+        let path = sourced_script_path(args, self.cwd.as_deref())?;
+        let script = read_script_file(&path);
+        if script.is_none() {
+            self.mark_uncertain(format!("sourced file `{}` cannot be read", path.display()));
+        }
+        script
+    }
+
+    /// Get the shell code a command reads from a heredoc or here-string (`input`):
+    /// the input itself for a shell (`bash <<'EOF'`), or the shell equivalent
+    /// of the program for an interpreter (`python3 - <<EOF`).
+    fn stdin_script(&self, mut argv: Vec<String>, input: String) -> Option<String> {
+        let mut name = argv.remove(0);
+        let mut args = argv;
+
+        // Look through wrappers (sudo bash <<EOF)
+        while self.wrappers.contains(&name) {
+            let (_, argv) = self.wrappers.split_command(&name, &args)?;
+            name = normalize_command_name(&argv[0]);
+            args = argv[1..].to_vec();
+        }
+
+        if SHELL_COMMANDS.contains(&name.as_str()) {
+            // A shell reads its script from stdin unless given -c or a script file
+            let reads_stdin = ShellInvocation::parse(&args).source == ScriptSource::Stdin;
+            return reads_stdin.then_some(input);
+        }
+        interpreter::stdin_shell_equivalent(&name, &args, &input)
+    }
+
+    /// Get the text or file of the last heredoc, here-string or `<` redirect of
+    /// a command or redirected statement node.
     #[cfg(feature = "ast-parser")]
-    fn extract_xargs_from_args(args: &[String]) -> Option<String> {
-        args.iter().find(|arg| !arg.starts_with('-')).cloned()
+    fn stdin_redirect(node: Node, source: &str) -> Option<Stdin> {
+        let mut redirects = Self::statement_redirects(node);
+        // Heredocs and files attach to the statement wrapping the command
+        if let Some(parent) = node
+            .parent()
+            .filter(|parent| parent.kind() == "redirected_statement")
+        {
+            redirects.extend(Self::statement_redirects(parent));
+        }
+
+        redirects
+            .iter()
+            .rev()
+            .find_map(|redirect| match redirect.kind() {
+                "heredoc_redirect" => {
+                    let body = redirect
+                        .children(&mut redirect.walk())
+                        .find(|child| child.kind() == "heredoc_body")?;
+                    let strip_tabs = redirect
+                        .children(&mut redirect.walk())
+                        .any(|child| child.kind() == "<<-");
+                    Some(Stdin::Text(heredoc_text(
+                        &source[body.byte_range()],
+                        strip_tabs,
+                    )))
+                }
+                "herestring_redirect" => {
+                    let value = redirect.named_child(0)?;
+                    Some(Stdin::Text(unquote(&source[value.byte_range()])))
+                }
+                "file_redirect" => {
+                    let mut operator = None;
+                    let mut target = None;
+                    for child in redirect.children(&mut redirect.walk()) {
+                        match child.kind() {
+                            "file_descriptor" => {}
+                            _ if child.is_named() => target = target.or(Some(child)),
+                            kind => operator = Some(kind),
+                        }
+                    }
+                    let target = unquote(&source[target?.byte_range()]);
+                    (operator? == "<").then_some(Stdin::File(target))
+                }
+                _ => None,
+            })
+    }
+
+    /// Find the index of the command run by xargs
+    fn find_xargs_command(args: &[String]) -> Option<usize> {
+        args.iter().position(|arg| !arg.starts_with('-'))
     }
 
-    /// Get command name from a command node
+    /// Extract the commands run by find -exec, -execdir, -ok and -okdir.
+    /// Each command runs until the terminating `;` or `+` argument.
+    fn extract_find_exec_from_args(args: &[String]) -> Vec<Vec<String>> {
+        const EXEC_ACTIONS: &[&str] = &["-exec", "-execdir", "-ok", "-okdir"];
+        const TERMINATORS: &[&str] = &[";", "\\;", "+"];
+
+        let mut targets = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if !EXEC_ACTIONS.contains(&arg.as_str()) {
+                continue;
+            }
+            let target: Vec<String> = iter
+                .by_ref()
+                .take_while(|a| !TERMINATORS.contains(&a.as_str()))
+                .cloned()
+                .collect();
+            if !target.is_empty() {
+                targets.push(target);
+            }
+        }
+        targets
+    }
+
+    /// Get command name from a command node, normalized with [`normalize_command_name`]
+    /// and resolved through the variables defined earlier in the command string
     #[cfg(feature = "ast-parser")]
     fn get_command_name(&self, node: Node, source: &str) -> Option<String> {
         for child in node.children(&mut node.walk()) {
             match child.kind() {
                 "command_name" => {
-                    // Get the actual word inside command_name
-                    for inner in child.children(&mut child.walk()) {
-                        if inner.kind() == "word" {
-                            return Some(
-                                source[inner.byte_range()]
-                                    .trim_matches(|c| c == '"' || c == '\'')
-                                    .to_string(),
-                            );
-                        }
-                    }
-                    // Fallback: use the command_name text directly
-                    return Some(
-                        source[child.byte_range()]
-                            .trim_matches(|c| c == '"' || c == '\'')
-                            .to_string(),
-                    );
+                    let mut words = self.resolve_command_words(&source[child.byte_range()]);
+                    return Some(words.swap_remove(0));
                 }
                 "word" => {
                     // First word in simple_command might be the command
-                    let text = source[child.byte_range()]
-                        .trim_matches(|c| c == '"' || c == '\'')
-                        .to_string();
+                    let text = normalize_command_name(&source[child.byte_range()]);
                     if !text.starts_with('-') && !text.contains('=') {
                         return Some(text);
                     }
@@ -347,244 +848,754 @@ impl ShellParser {
         None
     }
 
-    /// Process wrapper arguments to find the actual command
-    /// Recursively handles nested wrappers (e.g., sudo bash -c 'rm')
+    /// Record variables and aliases defined by a node in the symbol table.
+    ///
+    /// Variables assigned at runtime (`read`, `for`) are removed so that a stale
+    /// value never hides what a later `$X` runs.
     #[cfg(feature = "ast-parser")]
-    fn process_wrapper_args(&mut self, args: &[String], commands: &mut Vec<String>) {
-        let mut skip_next = false;
-        for (i, arg) in args.iter().enumerate() {
-            if skip_next {
-                skip_next = false;
-                continue;
-            }
-            if arg.starts_with('-') {
-                if Self::flag_takes_arg(arg) {
-                    skip_next = true;
+    fn record_symbols(&mut self, node: Node, source: &str) {
+        match node.kind() {
+            "variable_assignment" => {
+                // Prefix assignments (`X=rm cmd`) only set the environment of that command
+                if node
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == "command")
+                {
+                    return;
+                }
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let name = source[name.byte_range()].to_string();
+                let appends = node
+                    .children(&mut node.walk())
+                    .any(|child| child.kind() == "+=");
+                match node.child_by_field_name("value") {
+                    Some(value) if !appends => {
+                        let value = unquote(&source[value.byte_range()]);
+                        let value = self.expand_variables(&value).unwrap_or(value);
+                        self.symbols.variables.insert(name, value);
+                    }
+                    Some(_) => {
+                        self.symbols.variables.remove(&name);
+                    }
+                    None => {
+                        self.symbols.variables.insert(name, String::new());
+                    }
                 }
-                continue;
-            }
-            if arg.contains('=') {
-                continue;
             }
-            // Found the actual command
-            if !commands.contains(arg) {
-                commands.push(arg.clone());
+            "for_statement" => {
+                if let Some(variable) = node.child_by_field_name("variable") {
+                    self.symbols
+                        .variables
+                        .remove(&source[variable.byte_range()]);
+                }
             }
-
-            // Get remaining arguments after this command
-            let remaining_args: Vec<String> = args[i + 1..].to_vec();
-
-            // If the found command is a shell, check for -c argument
-            if SHELL_COMMANDS.contains(&arg.as_str()) {
-                if let Some(shell_cmd) = Self::extract_shell_c_from_args(&remaining_args) {
-                    let nested = self.extract_commands(&shell_cmd);
-                    for nested_cmd in nested {
-                        if !commands.contains(&nested_cmd) {
-                            commands.push(nested_cmd);
+            "command" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    return;
+                };
+                let name = normalize_command_name(&source[name.byte_range()]);
+                let args: Vec<String> = self
+                    .get_command_arguments_raw(node, source)
+                    .iter()
+                    .map(|arg| unquote(arg))
+                    .collect();
+                match name.as_str() {
+                    "alias" => {
+                        for arg in &args {
+                            if let Some((alias, value)) = arg.split_once('=') {
+                                self.symbols
+                                    .aliases
+                                    .insert(alias.to_string(), value.to_string());
+                            }
                         }
                     }
+                    "unalias" => {
+                        for arg in &args {
+                            self.symbols.aliases.remove(arg);
+                        }
+                    }
+                    _ if VARIABLE_ASSIGNING_COMMANDS.contains(&name.as_str()) => {
+                        for arg in &args {
+                            self.symbols.variables.remove(arg);
+                        }
+                    }
+                    _ => {}
                 }
             }
+            _ => {}
+        }
+    }
 
-            // If the found command is also a wrapper, process its remaining args
-            if COMMAND_WRAPPERS.contains(&arg.as_str()) {
-                self.process_wrapper_args(&remaining_args, commands);
-            }
+    /// Expand a command that invokes an alias defined earlier in the command string.
+    ///
+    /// Returns the alias name and the command text with the alias replaced by its value.
+    /// Like bash, quoted or escaped names are not expanded.
+    #[cfg(feature = "ast-parser")]
+    fn expand_alias(&self, node: Node, source: &str) -> Option<(String, String)> {
+        if node.kind() != "command" {
+            return None;
+        }
+        let name = node.child_by_field_name("name")?;
+        let alias = &source[name.byte_range()];
+        let value = self.symbols.aliases.get(alias)?;
+        let rest = &source[name.end_byte()..node.end_byte()];
+        Some((alias.to_string(), format!("{}{}", value, rest)))
+    }
 
-            break;
+    /// Run an extraction of an alias expansion with the alias itself disabled,
+    /// so that self-referencing aliases (`alias ls='ls -la'`) terminate.
+    #[cfg(feature = "ast-parser")]
+    fn with_alias_suspended<T>(&mut self, alias: &str, extract: impl FnOnce(&mut Self) -> T) -> T {
+        let value = self.symbols.aliases.remove(alias);
+        let result = extract(self);
+        if let Some(value) = value {
+            self.symbols.aliases.insert(alias.to_string(), value);
         }
+        result
     }
 
-    /// Flags that take an argument (value) for common wrappers
-    const FLAGS_WITH_ARGS: &[&str] = &[
-        // sudo flags
-        "-u", "-g", "-C", "-D", "-R", "-T", "-h", "-p", "-r", "-t", "-U", // env flags
-        "-S", // timeout flags
-        "-k", "-s", // nice/ionice flags
-        "-n", "-c",
-    ];
+    /// Resolve a command name through the variables defined earlier in the command string.
+    ///
+    /// Returns the words the name expands to, the first being the normalized command name.
+    /// Names that cannot be resolved are returned as-is and stay dynamic.
+    fn resolve_command_words(&self, name: &str) -> Vec<String> {
+        if let Some(words) = Self::expand_name_braces(name) {
+            return words;
+        }
+        let raw = name;
+        let name = normalize_command_name(name);
+        if !is_dynamic_command_name(&name) {
+            return vec![name];
+        }
+        // Substitutions are expanded from the source text, whose quotes belong to the inner command
+        let substitution = raw
+            .strip_prefix('"')
+            .and_then(|raw| raw.strip_suffix('"'))
+            .unwrap_or(raw);
+        let expanded = self
+            .expand_variables(&name)
+            .or_else(|| Self::expand_substitution(substitution));
+        let mut words: Vec<String> = match expanded {
+            Some(expanded) => expanded.split_whitespace().map(String::from).collect(),
+            None => Vec::new(),
+        };
+        if words.is_empty() {
+            return vec![name];
+        }
+        words[0] = normalize_command_name(&words[0]);
+        words
+    }
 
-    /// Check if a flag takes an argument
-    fn flag_takes_arg(flag: &str) -> bool {
-        if flag.contains('=') {
-            return false;
+    /// Expand a brace expression in a command name into the words it runs
+    /// (`r{m,}` runs `rm r`), the first being the normalized command name.
+    fn expand_name_braces(name: &str) -> Option<Vec<String>> {
+        let words = deobfuscate::expand_braces(name);
+        if words.len() < 2 {
+            return None;
         }
-        Self::FLAGS_WITH_ARGS.contains(&flag)
+        Some(
+            words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| match i {
+                    0 => normalize_command_name(word),
+                    _ => unquote(word),
+                })
+                .collect(),
+        )
     }
 
-    /// Fallback parser using string manipulation
-    fn extract_commands_fallback(&self, command: &str) -> Vec<String> {
-        let mut commands = Vec::new();
+    /// Expand a command substitution whose output is known statically:
+    /// `$(echo rm)`, `$(printf rm)`, `$(printf '\162\155')`, `$(which rm)`,
+    /// `$(command -v rm)`, `$(type -P rm)` and decoding pipelines (`$(echo cm0= | base64 -d)`).
+    fn expand_substitution(word: &str) -> Option<String> {
+        let inner = word
+            .strip_prefix("$(")
+            .and_then(|w| w.strip_suffix(')'))
+            .or_else(|| word.strip_prefix('`').and_then(|w| w.strip_suffix('`')))?;
+        if inner.contains('|') {
+            let stages: Vec<Vec<String>> = inner.split('|').map(parse_shell_tokens).collect();
+            let output = deobfuscate::decode_pipeline(&stages)?;
+            // Substitutions drop trailing newlines
+            return Some(output.trim_end_matches('\n').to_string());
+        }
+        let tokens = parse_shell_tokens(inner);
+        if tokens.iter().any(|token| is_dynamic_command_name(token)) {
+            return None;
+        }
+        let (name, args) = tokens.split_first()?;
+
+        match (name.as_str(), args) {
+            ("echo" | "printf", _) => deobfuscate::decode_pipeline(std::slice::from_ref(&tokens))
+                .map(|output| output.trim_end_matches('\n').to_string()),
+            ("which", [target]) => Some(target.clone()),
+            ("command", [flag, target]) if flag == "-v" => Some(target.clone()),
+            ("type", [flag, target]) if flag == "-P" => Some(target.clone()),
+            _ => None,
+        }
+    }
 
-        for segment in command.split(';') {
-            for part in Self::split_by_logical_ops(segment.trim()) {
-                for pipe_part in part.split('|') {
-                    let cmd = pipe_part.trim();
-                    if !cmd.is_empty() {
-                        commands.extend(self.extract_commands_from_segment_fallback(cmd));
+    /// Expand `$NAME` and `${NAME}` references in a word using known variables.
+    ///
+    /// Returns `None` if the word references an unknown variable or uses any other expansion.
+    #[cfg(feature = "ast-parser")]
+    fn expand_variables(&self, word: &str) -> Option<String> {
+        let mut expanded = String::with_capacity(word.len());
+        let mut chars = word.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '$' => {
+                    let braced = chars.next_if_eq(&'{').is_some();
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                        name.push(c);
+                    }
+                    if braced && chars.next() != Some('}') {
+                        return None;
                     }
+                    expanded.push_str(self.symbols.variables.get(&name)?);
                 }
+                '`' => return None,
+                _ => expanded.push(c),
             }
         }
 
-        commands
+        Some(expanded)
     }
 
-    /// Extract commands from a single segment (fallback)
-    fn extract_commands_from_segment_fallback(&self, segment: &str) -> Vec<String> {
-        let mut commands = Vec::new();
-        let (cmd, args) = self.extract_command_with_args_fallback(segment);
+    #[cfg(not(feature = "ast-parser"))]
+    fn expand_variables(&self, _word: &str) -> Option<String> {
+        // Variables are only tracked with tree-sitter
+        None
+    }
 
-        if cmd.is_empty() {
-            return commands;
+    /// Parse a command string with the quote- and nesting-aware fallback splitter,
+    /// used without tree-sitter or when it gives up.
+    fn parse_fallback(&mut self, command: &str) -> Vec<ParsedCommand> {
+        let script = fallback::split(command);
+        if let Some(error) = &script.error {
+            self.mark_uncertain(error.clone());
         }
+        let mut commands = Vec::new();
+        self.parse_list_fallback(
+            &script,
+            &script.list,
+            command,
+            &Scope::default(),
+            &mut commands,
+        );
+        commands
+    }
 
-        commands.push(cmd.clone());
-
-        // Handle command wrappers
-        if COMMAND_WRAPPERS.contains(&cmd.as_str()) {
-            let mut skip_next = false;
-            for (i, arg) in args.iter().enumerate() {
-                if skip_next {
-                    skip_next = false;
-                    continue;
-                }
-                if arg.starts_with('-') {
-                    if Self::flag_takes_arg(arg) {
-                        skip_next = true;
-                    }
-                    continue;
-                }
-                if cmd == "env" && arg.contains('=') {
-                    continue;
-                }
-                commands.push(arg.clone());
-                let remaining: Vec<String> = args[i..].to_vec();
-                if !remaining.is_empty() {
-                    let remaining_str = remaining.join(" ");
-                    commands.extend(self.extract_commands_from_segment_fallback(&remaining_str));
-                }
-                break;
+    /// Parse the pipelines of a list split by the fallback splitter.
+    fn parse_list_fallback(
+        &mut self,
+        script: &fallback::Script,
+        list: &[fallback::Pipeline],
+        source: &str,
+        scope: &Scope,
+        commands: &mut Vec<ParsedCommand>,
+    ) {
+        for pipeline in list {
+            let mut scope = scope.clone();
+            scope.context.background |= pipeline.background;
+            match pipeline.stages.as_slice() {
+                [stage] => self.parse_stage_fallback(script, stage, source, &scope, commands),
+                stages => self.parse_pipeline(
+                    stages,
+                    &scope,
+                    commands,
+                    |parser, stage, inner, commands| {
+                        parser.parse_stage_fallback(script, stage, source, inner, commands)
+                    },
+                ),
             }
         }
+    }
 
-        // Handle shell -c "command"
-        if SHELL_COMMANDS.contains(&cmd.as_str()) {
-            for (i, arg) in args.iter().enumerate() {
-                if arg == "-c" && i + 1 < args.len() {
-                    let shell_cmd = &args[i + 1];
-                    commands.extend(self.extract_commands_fallback(shell_cmd));
-                    break;
-                }
+    /// Parse a pipeline stage split by the fallback splitter.
+    fn parse_stage_fallback(
+        &mut self,
+        script: &fallback::Script,
+        stage: &fallback::Stage,
+        source: &str,
+        scope: &Scope,
+        commands: &mut Vec<ParsedCommand>,
+    ) {
+        let compound = match stage {
+            fallback::Stage::Simple(simple) => {
+                return self.parse_simple_fallback(script, simple, source, scope, commands)
             }
+            fallback::Stage::Compound(compound) => compound,
+        };
+
+        self.parse_substitutions_fallback(
+            script,
+            &compound.header_substitutions,
+            source,
+            scope,
+            commands,
+        );
+        let mut inner = scope.clone();
+        inner.context.subshell |= compound.subshell;
+        inner.context.stdin_redirected |= compound
+            .redirects
+            .iter()
+            .any(|redirect| redirect.operator.starts_with('<'));
+        if let Some(stdin) = Self::stdin_redirect_fallback(script, &compound.redirects, source) {
+            inner.context.stdin = Some(stdin);
         }
+        inner
+            .redirects
+            .extend(Self::file_redirects_fallback(&compound.redirects, source));
+        self.parse_list_fallback(script, &compound.body, source, &inner, commands);
+        self.parse_substitutions_fallback(script, &compound.substitutions, source, scope, commands);
+    }
 
-        // Handle xargs
-        if cmd == "xargs" {
-            for arg in &args {
-                if arg.starts_with('-') {
-                    continue;
-                }
-                commands.push(arg.clone());
-                break;
+    /// Parse a simple command split by the fallback splitter, along with the
+    /// script it reads from a heredoc or here-string and its substitutions.
+    fn parse_simple_fallback(
+        &mut self,
+        script: &fallback::Script,
+        simple: &fallback::Simple,
+        source: &str,
+        scope: &Scope,
+        commands: &mut Vec<ParsedCommand>,
+    ) {
+        let file_redirects = Self::file_redirects_fallback(&simple.redirects, source);
+        let mut redirects = scope.redirects.clone();
+        redirects.extend(file_redirects.iter().cloned());
+
+        let assignments = simple
+            .words
+            .iter()
+            .take_while(|word| Self::is_env_assignment(&source[(*word).clone()]))
+            .count();
+        let (assignments, words) = simple.words.split_at(assignments);
+        let Some((name, args)) = words.split_first() else {
+            // A redirect without a command (`> file`) truncates its target like `: > file`
+            if !file_redirects.is_empty() {
+                let mut cmd = ParsedCommand::new(vec![":".to_string()], simple.span.clone());
+                cmd.redirects = redirects;
+                cmd.context = scope.context.clone();
+                commands.push(cmd);
+            }
+            return self.parse_substitutions_fallback(
+                script,
+                &simple.substitutions,
+                source,
+                scope,
+                commands,
+            );
+        };
+
+        let name = &source[name.clone()];
+        let mut argv = vec![name.to_string()];
+        argv.extend(
+            args.iter()
+                .flat_map(|word| Self::word_arguments(&source[word.clone()])),
+        );
+
+        // Heredoc/here-string scripts read by shells and interpreters
+        let stdin = Self::stdin_redirect_fallback(script, &simple.redirects, source);
+        let nested_script = match &stdin {
+            Some(Stdin::Text(input)) => {
+                let mut words = self.resolve_command_words(name);
+                words.extend(argv[1..].iter().cloned());
+                self.stdin_script(words, input.clone())
             }
+            _ => None,
+        };
+
+        let mut cmd = ParsedCommand::new(argv, simple.span.clone());
+        cmd.env = assignments
+            .iter()
+            .filter_map(|word| {
+                let (name, value) = source[word.clone()].split_once('=')?;
+                Some((name.to_string(), unquote(value)))
+            })
+            .collect();
+        cmd.redirects = redirects;
+        cmd.context = scope.context.clone();
+        cmd.context.stdin_redirected |= simple
+            .redirects
+            .iter()
+            .any(|redirect| redirect.operator.starts_with('<'));
+        if stdin.is_some() {
+            cmd.context.stdin = stdin;
         }
+        cmd.context.obfuscated |= deobfuscate::is_encoded(name);
+        let runner = normalize_command_name(name);
+        self.push_command(cmd, commands);
+
+        if let Some(nested_script) = nested_script {
+            for mut cmd in self.parse_bash(&nested_script) {
+                cmd.nest_in(&scope.context, &simple.span, Some(&runner));
+                commands.push(cmd);
+            }
+        }
+        for redirect in &simple.redirects {
+            if let Some(heredoc) = redirect.heredoc {
+                let substitutions = &script.heredocs[heredoc].substitutions;
+                self.parse_substitutions_fallback(script, substitutions, source, scope, commands);
+            }
+        }
+        self.parse_substitutions_fallback(script, &simple.substitutions, source, scope, commands);
+    }
 
-        commands
+    /// Parse command and process substitutions split by the fallback splitter.
+    fn parse_substitutions_fallback(
+        &mut self,
+        script: &fallback::Script,
+        substitutions: &[Vec<fallback::Pipeline>],
+        source: &str,
+        scope: &Scope,
+        commands: &mut Vec<ParsedCommand>,
+    ) {
+        let inner = Self::substitution_scope(scope);
+        for list in substitutions {
+            self.parse_list_fallback(script, list, source, &inner, commands);
+        }
     }
 
-    /// Split by && and || operators
-    fn split_by_logical_ops(s: &str) -> Vec<&str> {
-        let mut result = Vec::new();
-        let mut current_start = 0;
-        let chars: Vec<char> = s.chars().collect();
-        let len = chars.len();
-        let mut i = 0;
-
-        while i < len {
-            if i + 1 < len {
-                let two_chars: String = chars[i..=i + 1].iter().collect();
-                if two_chars == "&&" || two_chars == "||" {
-                    let part = &s[current_start..i];
-                    if !part.trim().is_empty() {
-                        result.push(part.trim());
-                    }
-                    current_start = i + 2;
-                    i += 2;
-                    continue;
+    /// Get the text or file of the last heredoc, here-string or `<` redirect
+    /// from fallback redirections.
+    fn stdin_redirect_fallback(
+        script: &fallback::Script,
+        redirections: &[fallback::Redirection],
+        source: &str,
+    ) -> Option<Stdin> {
+        redirections
+            .iter()
+            .rev()
+            .find_map(|redirect| match redirect.operator {
+                "<<" | "<<-" => {
+                    let heredoc = &script.heredocs[redirect.heredoc?];
+                    let body = &source[heredoc.body.clone()];
+                    Some(Stdin::Text(heredoc_text(body, redirect.operator == "<<-")))
                 }
+                "<<<" => Some(Stdin::Text(unquote(&source[redirect.target.clone()?]))),
+                "<" => Some(Stdin::File(unquote(&source[redirect.target.clone()?]))),
+                _ => None,
+            })
+    }
+
+    /// Get the redirects that write to files from fallback redirections.
+    fn file_redirects_fallback(
+        redirections: &[fallback::Redirection],
+        source: &str,
+    ) -> Vec<Redirect> {
+        redirections
+            .iter()
+            .filter(|redirect| redirect.operator.contains('>'))
+            .filter_map(|redirect| {
+                let target = unquote(&source[redirect.target.clone()?]);
+                (!Self::duplicates_descriptor(redirect.operator, &target)).then(|| Redirect {
+                    operator: redirect.operator.to_string(),
+                    target,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the arguments a word passes, the way tree-sitter nodes are read:
+    /// substitutions and expansions stay as written, other words are brace
+    /// expanded and unquoted if they contain quotes.
+    fn word_arguments(word: &str) -> Vec<String> {
+        let substitution = (word.starts_with("$(") && word.ends_with(')'))
+            || (word.starts_with('`') && word.ends_with('`'))
+            || (word.starts_with("${") && word.ends_with('}'));
+        if substitution {
+            return vec![word.to_string()];
+        }
+        deobfuscate::expand_braces(word)
+            .into_iter()
+            .map(|word| match word.contains(['\'', '"']) {
+                true => unquote(&word),
+                false => word,
+            })
+            .collect()
+    }
+
+    /// Parse the stages of a pipeline with `parse_stage`, giving each its position
+    /// and the names of the commands upstream of it.
+    fn parse_pipeline<T>(
+        &mut self,
+        stages: &[T],
+        scope: &Scope,
+        commands: &mut Vec<ParsedCommand>,
+        mut parse_stage: impl FnMut(&mut Self, &T, &Scope, &mut Vec<ParsedCommand>),
+    ) {
+        let mut upstream = scope.context.upstream.clone();
+        let mut upstream_argvs = Vec::new();
+        for (position, stage) in stages.iter().enumerate() {
+            let mut inner = scope.clone();
+            inner.context.pipeline_position = Some(position);
+            inner.context.upstream = upstream.clone();
+            // Only the last stage writes to the pipeline's redirects; the others write to the pipe
+            if position + 1 < stages.len() {
+                inner.redirects.clear();
             }
-            i += 1;
+            let start = commands.len();
+            parse_stage(self, stage, &inner, commands);
+            upstream.extend(Self::stage_commands(&commands[start..], &inner.context));
+            let decoded = self.parse_decoded_script(&upstream_argvs, &commands[start..]);
+            upstream_argvs.push(commands.get(start).map(|cmd| cmd.argv.clone()));
+            commands.extend(decoded);
         }
+    }
+
+    /// Get the scope of the commands in a command or process substitution.
+    /// The output is captured, so enclosing redirects and pipelines do not apply.
+    fn substitution_scope(scope: &Scope) -> Scope {
+        let mut inner = Scope::default();
+        inner.context.subshell = scope.context.subshell;
+        inner.context.substitution = true;
+        inner.context.nested_in = scope.context.nested_in.clone();
+        inner
+    }
+
+    /// Names of the commands of a pipeline stage that write to the pipe,
+    /// leaving out those in substitutions that only produce its arguments.
+    fn stage_commands<'a>(
+        commands: &'a [ParsedCommand],
+        context: &'a CommandContext,
+    ) -> impl Iterator<Item = String> + 'a {
+        commands
+            .iter()
+            .filter(|cmd| cmd.context.substitution == context.substitution)
+            .map(|cmd| cmd.name().to_string())
+    }
+
+    /// Parse the script that a pipeline of literal text and decoders feeds to a shell
+    /// in `stage` (`echo cm0gLXJmIC4= | base64 -d | sh`), given the argument vectors
+    /// of the earlier stages. Its commands are marked as obfuscated.
+    fn parse_decoded_script(
+        &mut self,
+        upstream: &[Option<Vec<String>>],
+        stage: &[ParsedCommand],
+    ) -> Vec<ParsedCommand> {
+        let Some(shell) = stage.iter().find(|cmd| {
+            SHELL_COMMANDS.contains(&cmd.name())
+                && !cmd.context.stdin_redirected
+                && cmd.script_source() == Some(ScriptSource::Stdin)
+        }) else {
+            return Vec::new();
+        };
+        let Some(upstream) = upstream.iter().cloned().collect::<Option<Vec<_>>>() else {
+            return Vec::new();
+        };
+        let Some(script) = deobfuscate::decode_pipeline(&upstream) else {
+            return Vec::new();
+        };
 
-        let remaining = &s[current_start..];
-        if !remaining.trim().is_empty() {
-            result.push(remaining.trim());
+        let mut commands = self.parse_bash(&script);
+        for cmd in &mut commands {
+            cmd.nest_in(&shell.context, &shell.span, Some(shell.name()));
+            cmd.context.obfuscated = true;
         }
+        commands
+    }
 
-        result
+    /// Check if a word is an environment assignment (`NAME=value`)
+    fn is_env_assignment(word: &str) -> bool {
+        word.split_once('=').is_some_and(|(name, _)| {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
     }
+}
 
-    /// Extract command with its arguments (fallback string-based parser).
-    fn extract_command_with_args_fallback(&self, command: &str) -> (String, Vec<String>) {
-        let mut parts = Vec::new();
-        let mut current = String::new();
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut escape_next = false;
+impl Default for ShellParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        for c in command.trim().chars() {
-            if escape_next {
-                current.push(c);
-                escape_next = false;
-                continue;
-            }
+/// Describe the first syntax error or missing token beneath a node, if any.
+#[cfg(feature = "ast-parser")]
+fn syntax_error(node: Node, source: &str) -> Option<String> {
+    if !node.has_error() {
+        return None;
+    }
+    if node.is_missing() {
+        return Some(format!("missing `{}`", node.kind()));
+    }
+    if node.is_error() {
+        let text = source[node.byte_range()].lines().next().unwrap_or_default();
+        let text: String = text.chars().take(40).collect();
+        return Some(format!("syntax error near `{}`", text.trim()));
+    }
+    node.children(&mut node.walk())
+        .find_map(|child| syntax_error(child, source))
+}
 
-            match c {
-                '\\' if !in_single_quote => {
-                    escape_next = true;
-                }
-                '\'' if !in_double_quote => {
-                    in_single_quote = !in_single_quote;
-                }
-                '"' if !in_single_quote => {
-                    in_double_quote = !in_double_quote;
-                }
-                ' ' | '\t' if !in_single_quote && !in_double_quote => {
-                    if !current.is_empty() {
-                        parts.push(current.clone());
-                        current.clear();
-                    }
-                }
-                _ => {
-                    current.push(c);
+/// Normalize a command name as written in the source to the command it runs.
+///
+/// Removes quoting and backslash escapes (`"r"m`, `\\rm`), resolves path-qualified
+/// names to their basename (`/bin/rm`) and drops a Windows `.exe` suffix.
+/// Names that depend on expansions (`$X`, `$(...)`) are only unquoted.
+pub fn normalize_command_name(name: &str) -> String {
+    let unquoted = unquote(name);
+
+    if is_dynamic_command_name(&unquoted) {
+        return unquoted;
+    }
+
+    let base = unquoted.rsplit(['/', '\\']).next().unwrap_or_default();
+    let base = match base.len().checked_sub(4) {
+        Some(i) if base.is_char_boundary(i) && base[i..].eq_ignore_ascii_case(".exe") => &base[..i],
+        _ => base,
+    };
+    if base.is_empty() {
+        return unquoted;
+    }
+    base.to_string()
+}
+
+/// Check if a command name is only known at runtime (`$X`, `$(...)`, backticks).
+pub fn is_dynamic_command_name(name: &str) -> bool {
+    name.contains(['$', '`'])
+}
+
+/// Get the text of a heredoc body, without the leading tabs `<<-` strips.
+fn heredoc_text(body: &str, strip_tabs: bool) -> String {
+    match strip_tabs {
+        true => body
+            .split_inclusive('\n')
+            .map(|line| line.trim_start_matches('\t'))
+            .collect(),
+        false => body.to_string(),
+    }
+}
+
+/// Remove shell quoting and backslash escapes from a word.
+fn unquote(word: &str) -> String {
+    let mut unquoted = String::with_capacity(word.len());
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+    let mut chars = word.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_double_quote => match chars.next() {
+                // Inside double quotes, backslash only escapes these characters
+                Some(next @ ('$' | '`' | '"' | '\\' | '\n')) => unquoted.push(next),
+                Some(next) => {
+                    unquoted.push(c);
+                    unquoted.push(next);
                 }
+                None => unquoted.push(c),
+            },
+            '\\' if !in_single_quote => {
+                unquoted.extend(chars.next());
+            }
+            '\'' if !in_double_quote => {
+                in_single_quote = !in_single_quote;
+            }
+            '"' if !in_single_quote => {
+                in_double_quote = !in_double_quote;
+            }
+            '$' if !in_single_quote && !in_double_quote && chars.clone().next() == Some('\'') => {
+                chars.next();
+                unquoted.push_str(&take_ansi_c_string(&mut chars));
             }
+            _ => unquoted.push(c),
         }
+    }
+
+    unquoted
+}
 
-        if !current.is_empty() {
-            parts.push(current);
+/// Take the body of an ANSI-C quoted string (`$'...'`) after its opening quote
+/// and return it with its escapes decoded.
+fn take_ansi_c_string(chars: &mut std::str::Chars) -> String {
+    let mut body = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                body.push(c);
+                body.extend(chars.next());
+            }
+            '\'' => break,
+            _ => body.push(c),
         }
+    }
+    deobfuscate::decode_escapes(&body)
+}
 
-        if parts.is_empty() {
-            return (String::new(), Vec::new());
-        }
+/// Check if a command runs a script file in the current shell (`source`, `.`).
+pub fn is_source_command(name: &str) -> bool {
+    SOURCE_COMMANDS.contains(&name)
+}
 
-        let cmd = parts.remove(0);
-        (cmd, parts)
+/// Get the path of the file run by `source`/`.` from its arguments.
+///
+/// Relative paths resolve against `cwd` (the current directory if `None`) and `~/`
+/// against the home directory. Returns `None` if the path depends on runtime expansion.
+pub fn sourced_script_path(args: &[String], cwd: Option<&Path>) -> Option<PathBuf> {
+    let path = args.first()?;
+    if path.contains(['$', '`']) {
+        return None;
     }
+    match (path.strip_prefix("~/"), cwd) {
+        (Some(rest), _) => Some(dirs::home_dir()?.join(rest)),
+        (None, Some(cwd)) => Some(cwd.join(path)),
+        (None, None) => Some(PathBuf::from(path)),
+    }
+}
 
-    /// Extract command with its arguments (public API).
-    #[allow(dead_code)]
-    pub fn extract_command_with_args(&self, command: &str) -> (String, Vec<String>) {
-        self.extract_command_with_args_fallback(command)
+/// Read a script file for inspection.
+///
+/// Only regular files of at most [`MAX_SCRIPT_FILE_SIZE`] bytes are read, so that
+/// devices and pipes (`/dev/zero`, `/dev/stdin`, FIFOs) can neither exhaust memory
+/// nor block. Returns `None` if the file cannot be read in full.
+pub fn read_script_file(path: &Path) -> Option<String> {
+    if !fs::metadata(path).ok()?.is_file() {
+        return None;
     }
+    let mut script = String::new();
+    fs::File::open(path)
+        .ok()?
+        .take(MAX_SCRIPT_FILE_SIZE + 1)
+        .read_to_string(&mut script)
+        .ok()?;
+    (script.len() as u64 <= MAX_SCRIPT_FILE_SIZE).then_some(script)
 }
 
-impl Default for ShellParser {
-    fn default() -> Self {
-        Self::new()
+/// Find the index of the git subcommand in git arguments, skipping global options
+/// such as `-C <path>` and `-c <name>=<value>`.
+pub fn git_subcommand_index(args: &[String]) -> Option<usize> {
+    const GIT_OPTIONS_WITH_ARGS: &[&str] = &[
+        "-C",
+        "-c",
+        "--git-dir",
+        "--work-tree",
+        "--namespace",
+        "--exec-path",
+        "--config-env",
+    ];
+
+    let mut skip_next = false;
+    for (i, arg) in args.iter().enumerate() {
+        if skip_next {
+            skip_next = false;
+            continue;
+        }
+        if GIT_OPTIONS_WITH_ARGS.contains(&arg.as_str()) {
+            skip_next = true;
+            continue;
+        }
+        if arg.starts_with('-') {
+            continue;
+        }
+        return Some(i);
     }
+    None
 }
 
 /// Parse a command string into tokens, respecting shell quoting rules.
@@ -592,6 +1603,8 @@ impl Default for ShellParser {
 ///
 /// # Examples
 /// ```
+/// use claw_hooks::domain::parse_shell_tokens;
+///
 /// let tokens = parse_shell_tokens("echo 'hello world'");
 /// assert_eq!(tokens, vec!["echo", "hello world"]);
 /// ```
@@ -601,8 +1614,9 @@ pub fn parse_shell_tokens(command: &str) -> Vec<String> {
     let mut in_single_quote = false;
     let mut in_double_quote = false;
     let mut escape_next = false;
+    let mut chars = command.trim().chars();
 
-    for c in command.trim().chars() {
+    while let Some(c) = chars.next() {
         if escape_next {
             current.push(c);
             escape_next = false;
@@ -619,6 +1633,10 @@ pub fn parse_shell_tokens(command: &str) -> Vec<String> {
             '"' if !in_single_quote => {
                 in_double_quote = !in_double_quote;
             }
+            '$' if !in_single_quote && !in_double_quote && chars.clone().next() == Some('\'') => {
+                chars.next();
+                current.push_str(&take_ansi_c_string(&mut chars));
+            }
             ' ' | '\t' if !in_single_quote && !in_double_quote => {
                 if !current.is_empty() {
                     parts.push(current.clone());
@@ -641,6 +1659,48 @@ pub fn parse_shell_tokens(command: &str) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
+
+    /// Views of the parsed commands the tests below assert on.
+    impl ShellParser {
+        /// Extract the names of the commands in a shell command string.
+        fn extract_commands(&mut self, command: &str) -> Vec<String> {
+            self.parse(command)
+                .into_iter()
+                .map(|cmd| cmd.argv.into_iter().next().unwrap_or_default())
+                .collect()
+        }
+
+        /// Extract the argument vector of every command in a shell command string.
+        fn extract_command_argvs(&mut self, command: &str) -> Vec<Vec<String>> {
+            self.parse(command)
+                .into_iter()
+                .map(|cmd| cmd.argv)
+                .collect()
+        }
+
+        /// Extract the distinct redirects that write to files from a shell command string.
+        fn extract_redirects(&mut self, command: &str) -> Vec<Redirect> {
+            let mut redirects = Vec::new();
+            for redirect in self
+                .parse(command)
+                .into_iter()
+                .flat_map(|cmd| cmd.redirects)
+            {
+                if !redirects.contains(&redirect) {
+                    redirects.push(redirect);
+                }
+            }
+            redirects
+        }
+
+        /// Extract the name and arguments of the first command in a shell command string.
+        fn extract_command_with_args(&mut self, command: &str) -> (String, Vec<String>) {
+            let mut argv = self.parse(command).swap_remove(0).argv;
+            let name = argv.remove(0);
+            (name, argv)
+        }
+    }
 
     #[test]
     fn test_extract_simple_command() {
@@ -676,15 +1736,19 @@ mod tests {
 
     #[test]
     fn test_extract_command_with_args() {
-        let parser = ShellParser::new();
+        let mut parser = ShellParser::new();
         let (cmd, args) = parser.extract_command_with_args("git commit -m \"Hello world\"");
         assert_eq!(cmd, "git");
         assert_eq!(args, vec!["commit", "-m", "Hello world"]);
+
+        // Quotes inside a word
+        let (_, args) = parser.extract_command_with_args("psql --command=\"SELECT 'a b'\"");
+        assert_eq!(args, vec!["--command=SELECT 'a b'"]);
     }
 
     #[test]
     fn test_extract_command_with_single_quotes() {
-        let parser = ShellParser::new();
+        let mut parser = ShellParser::new();
         let (cmd, args) = parser.extract_command_with_args("echo 'hello world'");
         assert_eq!(cmd, "echo");
         assert_eq!(args, vec!["hello world"]);
@@ -731,6 +1795,46 @@ mod tests {
         assert!(commands.contains(&"kill".to_string()));
     }
 
+    /// Shells given -c together with other options, each running `rm -rf x`
+    const SHELL_OPTION_CLUSTER_CORPUS: &[&str] = &[
+        "bash -lc \"rm -rf x\"",
+        "sh -ec \"rm -rf x\"",
+        "bash -xc 'rm -rf x'",
+        "bash -c -- \"rm -rf x\"",
+        "bash -o pipefail -c 'rm -rf x'",
+    ];
+
+    #[test]
+    fn test_extract_shell_c_option_clusters() {
+        for command in SHELL_OPTION_CLUSTER_CORPUS {
+            let mut parser = ShellParser::new();
+            let argvs = parser.extract_command_argvs(command);
+            assert!(
+                argvs.contains(&vec!["rm".to_string(), "-rf".to_string(), "x".to_string()]),
+                "{}: {:?}",
+                command,
+                argvs
+            );
+            assert_eq!(
+                parser.confidence(),
+                &ParseConfidence::Complete,
+                "{}",
+                command
+            );
+        }
+
+        // Options the parser does not know make the parse uncertain
+        let mut parser = ShellParser::new();
+        parser.parse("bash -Zc 'rm -rf x'");
+        assert_eq!(
+            parser.confidence(),
+            &ParseConfidence::Uncertain("unrecognized option `-Zc` for bash".to_string())
+        );
+        let mut parser = ShellParser::new();
+        parser.parse("sh --wordexp 'rm -rf x'");
+        assert!(matches!(parser.confidence(), ParseConfidence::Uncertain(_)));
+    }
+
     #[test]
     fn test_extract_xargs_command() {
         let mut parser = ShellParser::new();
@@ -765,6 +1869,30 @@ mod tests {
         assert!(commands.contains(&"kill".to_string()));
     }
 
+    #[test]
+    fn test_extract_launcher_wrappers() {
+        let mut parser = ShellParser::new();
+        let argvs = parser.extract_command_argvs("timeout 10 rm -rf dist && pnpm exec rimraf out");
+        assert!(argvs.contains(&vec![
+            "rm".to_string(),
+            "-rf".to_string(),
+            "dist".to_string()
+        ]));
+        assert!(argvs.contains(&vec!["rimraf".to_string(), "out".to_string()]));
+
+        let argvs = parser.extract_command_argvs("env -S 'rm -rf x' && stdbuf -o0 kill 1");
+        assert!(argvs.contains(&vec!["rm".to_string(), "-rf".to_string(), "x".to_string()]));
+        assert!(argvs.contains(&vec!["kill".to_string(), "1".to_string()]));
+
+        let wrappers = BTreeMap::from([("with-env".to_string(), Wrapper::default())]);
+        let mut parser = ShellParser::new().with_wrappers(Arc::new(
+            WrapperRegistry::default().with_wrappers(&wrappers),
+        ));
+        let commands = parser.parse("with-env kill 1");
+        let kill = commands.iter().find(|cmd| cmd.name() == "kill").unwrap();
+        assert_eq!(kill.wrappers, ["with-env"]);
+    }
+
     #[test]
     fn test_extract_semicolon_with_yarn() {
         let mut parser = ShellParser::new();
@@ -818,6 +1946,898 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_command_argvs() {
+        let mut parser = ShellParser::new();
+        let argvs = parser.extract_command_argvs("cd build && sudo rm -rf 'out dir' | cat");
+        assert!(argvs.contains(&vec!["cd".to_string(), "build".to_string()]));
+        assert!(argvs.contains(&vec![
+            "rm".to_string(),
+            "-rf".to_string(),
+            "out dir".to_string()
+        ]));
+        assert!(argvs.contains(&vec!["cat".to_string()]));
+
+        let argvs = parser.extract_command_argvs("bash -c 'rm target/x' && find . | xargs rm");
+        assert!(argvs.contains(&vec!["rm".to_string(), "target/x".to_string()]));
+        assert!(argvs.contains(&vec!["rm".to_string()]));
+    }
+
+    #[test]
+    fn test_extract_find_exec() {
+        let mut parser = ShellParser::new();
+        let commands = parser.extract_commands("find . -name '*.tmp' -exec rm -f {} \\;");
+        assert!(commands.contains(&"find".to_string()));
+        assert!(commands.contains(&"rm".to_string()));
+
+        let commands = parser.extract_commands("find . -execdir sh -c 'kill 1' ';' -print");
+        assert!(commands.contains(&"sh".to_string()));
+        assert!(commands.contains(&"kill".to_string()));
+
+        let argvs = parser.extract_command_argvs("find . -type f -exec shred -u {} + -print");
+        assert!(argvs.contains(&vec![
+            "shred".to_string(),
+            "-u".to_string(),
+            "{}".to_string()
+        ]));
+
+        // -name arguments are not commands
+        let commands = parser.extract_commands("find . -name rm");
+        assert!(!commands.contains(&"rm".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_command_name() {
+        assert_eq!(normalize_command_name("rm"), "rm");
+        assert_eq!(normalize_command_name("/bin/rm"), "rm");
+        assert_eq!(normalize_command_name("./node_modules/.bin/yarn"), "yarn");
+        assert_eq!(normalize_command_name("\\rm"), "rm");
+        assert_eq!(normalize_command_name("r\\m"), "rm");
+        assert_eq!(normalize_command_name("\"r\"m"), "rm");
+        assert_eq!(normalize_command_name("'rm'"), "rm");
+        assert_eq!(normalize_command_name("taskkill.exe"), "taskkill");
+        assert_eq!(
+            normalize_command_name("\"C:\\Windows\\System32\\TASKKILL.EXE\""),
+            "TASKKILL"
+        );
+        assert_eq!(normalize_command_name("$CMD"), "$CMD");
+        assert_eq!(normalize_command_name("$(which rm)"), "$(which rm)");
+    }
+
+    /// Path-qualified, quoted and wrapped invocations of `rm`
+    const NORMALIZED_NAME_CORPUS: &[&str] = &[
+        "/bin/rm -rf x",
+        "\\rm x",
+        "\"r\"m x",
+        "'rm' x",
+        "command rm x",
+        "exec rm x",
+        "sudo /usr/bin/rm x",
+        "bash -c '/bin/rm x'",
+        "find . -exec /bin/rm {} +",
+    ];
+
+    #[test]
+    fn test_extract_normalized_command_names() {
+        let mut parser = ShellParser::new();
+        for cmd in NORMALIZED_NAME_CORPUS {
+            let commands = parser.extract_commands(cmd);
+            assert!(
+                commands.contains(&"rm".to_string()),
+                "rm should be extracted from {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+
+        let commands = parser.extract_commands("builtin kill 1234");
+        assert!(commands.contains(&"kill".to_string()));
+
+        // command -v only looks the command up
+        let commands = parser.extract_commands("command -v rm");
+        assert!(!commands.contains(&"rm".to_string()));
+
+        let argvs = parser.extract_command_argvs("command /usr/local/bin/yarn install");
+        assert!(argvs.contains(&vec!["yarn".to_string(), "install".to_string()]));
+    }
+
+    /// Invocations of `rm` through variables, aliases and functions defined in the same string
+    #[cfg(feature = "ast-parser")]
+    const SYMBOL_CORPUS: &[&str] = &[
+        "X=rm; $X -rf dir",
+        "X=/bin/rm; \"$X\" -rf dir",
+        "export X=rm; ${X} -rf dir",
+        "A=r; B=m; $A$B -rf dir",
+        "X=\"rm -rf\"; Y=$X; $Y dir",
+        "alias d=rm; d x",
+        "alias d='cd /tmp; rm -rf'; d x",
+        "f(){ rm -rf \"$1\"; }; f dir",
+        "X=rm; sudo $X dir",
+    ];
+
+    #[test]
+    #[cfg(feature = "ast-parser")]
+    fn test_resolve_variables_aliases_and_functions() {
+        let mut parser = ShellParser::new();
+        for cmd in SYMBOL_CORPUS {
+            let commands = parser.extract_commands(cmd);
+            assert!(
+                commands.contains(&"rm".to_string()),
+                "rm should be resolved from {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+
+        let argvs = parser.extract_command_argvs("X=\"rm -rf\"; $X dir");
+        assert!(argvs.contains(&vec![
+            "rm".to_string(),
+            "-rf".to_string(),
+            "dir".to_string()
+        ]));
+
+        let argvs = parser.extract_command_argvs("alias ni='npm install'; ni lodash");
+        assert!(argvs.contains(&vec![
+            "npm".to_string(),
+            "install".to_string(),
+            "lodash".to_string()
+        ]));
+
+        // Self-referencing aliases terminate
+        let commands = parser.extract_commands("alias ls='ls -la'; ls");
+        assert!(commands.contains(&"ls".to_string()));
+    }
+
+    /// Command names whose value is only known at runtime
+    #[cfg(feature = "ast-parser")]
+    const DYNAMIC_NAME_CORPUS: &[&str] = &[
+        "$CMD -rf dir",
+        "X=$(which rm); $X dir",
+        "X=ls; read X; $X dir",
+        "X=ls; for X in rm; do $X dir; done",
+        "X=ls; X+=x; $X dir",
+        "X=rm $X dir",
+    ];
+
+    #[test]
+    #[cfg(feature = "ast-parser")]
+    fn test_unresolved_command_names_stay_dynamic() {
+        let mut parser = ShellParser::new();
+        for cmd in DYNAMIC_NAME_CORPUS {
+            let commands = parser.extract_commands(cmd);
+            assert!(
+                commands.iter().any(|c| is_dynamic_command_name(c)),
+                "a dynamic command name should remain in {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+
+        // Symbols do not leak between separate command strings
+        parser.extract_commands("X=rm");
+        let commands = parser.extract_commands("$X dir");
+        assert_eq!(commands, vec!["$X"]);
+    }
+
+    #[test]
+    fn test_extract_eval() {
+        let mut parser = ShellParser::new();
+        let commands = parser.extract_commands("eval \"rm -rf dir\"");
+        assert!(commands.contains(&"rm".to_string()));
+
+        let commands = parser.extract_commands("eval rm -rf dir");
+        assert!(commands.contains(&"rm".to_string()));
+
+        let commands = parser.extract_commands("sudo eval 'kill 1234'");
+        assert!(commands.contains(&"kill".to_string()));
+
+        let argvs = parser.extract_command_argvs("eval 'rm -rf dir'");
+        assert!(argvs.contains(&vec![
+            "rm".to_string(),
+            "-rf".to_string(),
+            "dir".to_string()
+        ]));
+    }
+
+    #[test]
+    fn test_extract_sourced_file() {
+        let script = std::env::temp_dir().join(format!(
+            "claw-hooks-parser-source-{}.sh",
+            std::process::id()
+        ));
+        std::fs::write(&script, "cd build\nrm -rf out\n. \"$0\"\n").unwrap();
+
+        let mut parser = ShellParser::new();
+        let commands = parser.extract_commands(&format!("source {}", script.display()));
+        let dot_commands = parser.extract_commands(&format!(". {}", script.display()));
+        let argvs = parser.extract_command_argvs(&format!("source {}", script.display()));
+        std::fs::remove_file(&script).unwrap();
+
+        assert!(commands.contains(&"rm".to_string()));
+        assert!(dot_commands.contains(&"rm".to_string()));
+        assert!(argvs.contains(&vec![
+            "rm".to_string(),
+            "-rf".to_string(),
+            "out".to_string()
+        ]));
+    }
+
+    #[test]
+    fn test_sourced_file_in_working_directory() {
+        let dir = std::env::temp_dir().join(format!(
+            "claw-hooks-parser-source-cwd-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("env.sh"),
+            "rm -rf out
+",
+        )
+        .unwrap();
+        // A file that sources itself nests until the maximum depth
+        std::fs::write(
+            dir.join("loop.sh"),
+            "echo again
+source ./loop.sh
+",
+        )
+        .unwrap();
+
+        let mut parser = ShellParser::new().with_cwd(&dir).with_max_depth(3);
+        let names = |commands: Vec<ParsedCommand>| {
+            commands
+                .iter()
+                .map(|cmd| cmd.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        let sourced = names(parser.parse("source ./env.sh"));
+        let looped = names(parser.parse(". ./loop.sh"));
+        let confidence = parser.confidence().clone();
+        let unresolved = names(ShellParser::new().parse("source ./env.sh"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(sourced, ["source", "rm"]);
+        assert_eq!(looped.iter().filter(|name| *name == "echo").count(), 3);
+        assert!(matches!(confidence, ParseConfidence::Uncertain(_)));
+        assert_eq!(unresolved, ["source"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_sourced_file_that_cannot_be_read() {
+        let dir = std::env::temp_dir().join(format!(
+            "claw-hooks-parser-source-unreadable-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let large = "echo hi\n".repeat(MAX_SCRIPT_FILE_SIZE as usize / 8 + 1);
+        std::fs::write(dir.join("large.sh"), large).unwrap();
+
+        let mut parser = ShellParser::new().with_cwd(&dir);
+        let mut results = Vec::new();
+        for command in [
+            "source /dev/zero",
+            ". /dev/stdin",
+            "source ./large.sh",
+            "source ./missing.sh",
+        ] {
+            let commands = parser.parse(command);
+            results.push((commands.len(), parser.confidence().clone()));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for (count, confidence) in results {
+            assert_eq!(count, 1);
+            assert!(matches!(confidence, ParseConfidence::Uncertain(_)));
+        }
+    }
+
+    /// Substitutions that always print `rm`, used as a command name
+    const STATIC_SUBSTITUTION_CORPUS: &[&str] = &[
+        "$(echo rm) -rf dir",
+        "`echo rm` -rf dir",
+        "\"$(printf rm)\" -rf dir",
+        "$(which rm) -rf dir",
+        "$(command -v rm) -rf dir",
+    ];
+
+    #[test]
+    fn test_resolve_static_substitution() {
+        let mut parser = ShellParser::new();
+        for cmd in STATIC_SUBSTITUTION_CORPUS {
+            let commands = parser.extract_commands(cmd);
+            assert!(
+                commands.contains(&"rm".to_string()),
+                "rm should be resolved from {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+
+        let commands = parser.extract_commands("$(cat cmd.txt) -rf dir");
+        assert!(commands.contains(&"$(cat cmd.txt)".to_string()));
+    }
+
+    /// Encoded or escaped invocations of `rm`
+    const OBFUSCATED_COMMAND_CORPUS: &[&str] = &[
+        "$'\\x72\\x6d' -rf dir",
+        "$'\\162\\155' -rf dir",
+        "r{m,} -rf dir",
+        "echo cm0gLXJmIC4= | base64 -d | sh",
+        "printf '\\162\\155 -rf dir' | sudo bash",
+    ];
+
+    #[test]
+    fn test_decode_obfuscated_commands() {
+        let mut parser = ShellParser::new();
+        for cmd in OBFUSCATED_COMMAND_CORPUS {
+            let commands = parser.parse(cmd);
+            assert!(
+                commands
+                    .iter()
+                    .any(|c| c.name() == "rm" && c.context.obfuscated),
+                "rm should be decoded from {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+
+        let commands = parser.parse("echo cm0gLXJmIC4= | base64 -d | sh");
+        assert_eq!(commands[3].argv, vec!["rm", "-rf", "."]);
+        assert_eq!(commands[3].context.nested_in.as_deref(), Some("sh"));
+
+        // Plain commands and undecodable payloads
+        assert!(!parser.parse("rm -rf dir")[0].context.obfuscated);
+        assert!(!parser.parse("echo $'a\\tb'")[0].context.obfuscated);
+        assert_eq!(parser.parse("cat payload | base64 -d | sh").len(), 3);
+    }
+
+    /// Substitutions that decode `rm`
+    const OBFUSCATED_SUBSTITUTION_CORPUS: &[&str] = &[
+        "$(printf '\\162\\155') -rf dir",
+        "\"$(echo cm0= | base64 -d)\" -rf dir",
+        "eval \"$(echo cm0gLXJmIC4= | base64 -d)\"",
+    ];
+
+    #[test]
+    fn test_decode_obfuscated_substitutions() {
+        let mut parser = ShellParser::new();
+        for cmd in OBFUSCATED_SUBSTITUTION_CORPUS {
+            let commands = parser.parse(cmd);
+            assert!(
+                commands
+                    .iter()
+                    .any(|c| c.name() == "rm" && c.context.obfuscated),
+                "rm should be decoded from {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+
+        assert_eq!(
+            parser.extract_command_argvs("rm -rf /{etc,usr}")[0],
+            vec!["rm", "-rf", "/etc", "/usr"]
+        );
+        assert_eq!(
+            parser.extract_commands("$(printf '\\x41')"),
+            vec!["A", "printf"]
+        );
+    }
+
+    /// Heredocs and here-strings run as scripts, each running `rm`
+    const STDIN_SCRIPT_CORPUS: &[&str] = &[
+        "bash <<'EOF'\nrm -rf build\nEOF",
+        "sh <<EOF\ncd /tmp && rm -rf x\nEOF",
+        "sudo bash -s <<-EOF\n\trm -rf build\nEOF",
+        "bash <<< 'rm -rf build'",
+        "sh <<< \"echo ok; rm x\"",
+        "python3 - <<'EOF'\nimport shutil\nshutil.rmtree('build')\nEOF",
+    ];
+
+    /// Heredocs and here-strings read as data, mentioning `rm`
+    const STDIN_DATA_CORPUS: &[&str] = &[
+        "cat <<'EOF'\nrm -rf build\nEOF",
+        "bash deploy.sh <<'EOF'\nrm -rf build\nEOF",
+        "bash -c 'read x' <<< 'rm -rf build'",
+        "grep rm <<< 'rm -rf build'",
+    ];
+
+    #[test]
+    fn test_extract_heredoc_and_herestring_scripts() {
+        let mut parser = ShellParser::new();
+        for cmd in STDIN_SCRIPT_CORPUS {
+            let commands = parser.extract_commands(cmd);
+            assert!(
+                commands.contains(&"rm".to_string()),
+                "rm should be extracted from {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+
+        let argvs = parser.extract_command_argvs("sh <<< 'kill -9 1234'");
+        assert!(argvs.contains(&vec![
+            "kill".to_string(),
+            "-9".to_string(),
+            "1234".to_string()
+        ]));
+
+        let argvs = parser.extract_command_argvs("bash <<'EOF'\nnpm install\nEOF");
+        assert!(argvs.contains(&vec!["npm".to_string(), "install".to_string()]));
+
+        // Heredocs read as data are not scripts
+        for cmd in STDIN_DATA_CORPUS {
+            let commands = parser.extract_commands(cmd);
+            assert!(
+                !commands.contains(&"rm".to_string()),
+                "rm should not be extracted from {:?}: {:?}",
+                cmd,
+                commands
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_command_model() {
+        let mut parser = ShellParser::new();
+        let source = "FOO=1 sudo -u app env BAR=2 yarn install 'a b' > out.log | cat";
+        let commands = parser.parse(source);
+
+        let yarn = commands.iter().find(|cmd| cmd.name() == "yarn").unwrap();
+        assert_eq!(yarn.args(), ["install", "a b"]);
+        assert_eq!(yarn.wrappers, ["sudo", "env"]);
+        assert!(yarn.env.contains(&("BAR".to_string(), "2".to_string())));
+        assert_eq!(yarn.context.pipeline_position, Some(0));
+        assert_eq!(
+            yarn.redirects,
+            [Redirect {
+                operator: ">".to_string(),
+                target: "out.log".to_string()
+            }]
+        );
+        assert!(
+            source[yarn.span.clone()].starts_with("FOO=1 sudo -u app env BAR=2 yarn install 'a b'")
+        );
+
+        let cat = commands.iter().find(|cmd| cmd.name() == "cat").unwrap();
+        assert_eq!(cat.context.pipeline_position, Some(1));
+        assert_eq!(cat.context.upstream, ["sudo", "env", "yarn"]);
+        assert!(yarn.context.upstream.is_empty());
+        assert!(cat.redirects.is_empty());
+        assert!(cat.wrappers.is_empty());
+
+        // The command word as written, before normalization
+        let commands =
+            parser.parse("\"./.venv/bin/pip\" list && sudo /usr/bin/rm x && { \\rm y; }");
+        let words: Vec<(&str, &str)> = commands
+            .iter()
+            .map(|cmd| (cmd.name(), cmd.command_word.as_str()))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                ("pip", "./.venv/bin/pip"),
+                ("sudo", "sudo"),
+                ("rm", "/usr/bin/rm"),
+                ("rm", "rm")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_command_context() {
+        let mut parser = ShellParser::new();
+        let source = "(cd x && echo $(date)) && bash -c 'rm y' && { a; b; } > f";
+        let commands = parser.parse(source);
+        let find = |name: &str| commands.iter().find(|cmd| cmd.name() == name).unwrap();
+
+        assert!(find("cd").context.subshell);
+        assert!(!find("cd").context.substitution);
+        assert!(find("date").context.substitution);
+        assert_eq!(find("rm").context.nested_in.as_deref(), Some("bash"));
+        assert_eq!(&source[find("rm").span.clone()], "bash -c 'rm y'");
+        assert_eq!(find("a").redirects, find("b").redirects);
+        assert_eq!(find("b").redirects[0].target, "f");
+
+        let env = &parser.parse("FOO=1 BAR=\"x y\" make")[0].env;
+        assert_eq!(
+            env,
+            &[
+                ("FOO".to_string(), "1".to_string()),
+                ("BAR".to_string(), "x y".to_string())
+            ]
+        );
+
+        let commands = parser.parse("curl $(cat url) | (tee log | sh)");
+        let sh = commands.iter().find(|cmd| cmd.name() == "sh").unwrap();
+        assert_eq!(sh.context.upstream, ["curl", "tee"]);
+
+        // Background jobs and redirected stdin
+        let commands = parser.parse("npm run dev & (tail -f log &) ; python3 <<EOF\nx\nEOF\nnode < a.js && sh <<< 'ls'; bash -c 'vite' &");
+        let find = |name: &str| commands.iter().find(|cmd| cmd.name() == name).unwrap();
+        assert!(find("npm").context.background);
+        assert!(find("tail").context.background);
+        assert!(find("vite").context.background);
+        assert!(!find("python3").context.background);
+        assert!(find("python3").context.stdin_redirected);
+        assert!(find("node").context.stdin_redirected);
+        assert!(find("sh").context.stdin_redirected);
+        assert!(!find("npm").context.stdin_redirected);
+        assert_eq!(
+            find("python3").context.stdin,
+            Some(Stdin::Text("x\n".to_string()))
+        );
+        assert_eq!(
+            find("node").context.stdin,
+            Some(Stdin::File("a.js".to_string()))
+        );
+        assert_eq!(
+            find("sh").context.stdin,
+            Some(Stdin::Text("ls".to_string()))
+        );
+        assert_eq!(find("npm").context.stdin, None);
+
+        // The last redirect is the one read, including those of enclosing groups
+        let commands = parser.parse("{ psql; } < drop.sql; mysql <<< 'SELECT 1' < a.sql");
+        assert_eq!(
+            commands[0].context.stdin,
+            Some(Stdin::File("drop.sql".to_string()))
+        );
+        assert_eq!(
+            commands[1].context.stdin,
+            Some(Stdin::File("a.sql".to_string()))
+        );
+        let commands = parser.parse("psql <<EOF < b.sql\nSELECT 1;\nEOF");
+        assert_eq!(
+            commands[0].context.stdin,
+            Some(Stdin::File("b.sql".to_string()))
+        );
+
+        // A redirect without a command truncates its target
+        let commands = parser.parse("> /etc/hosts");
+        assert_eq!(commands[0].name(), ":");
+        assert_eq!(commands[0].redirects[0].target, "/etc/hosts");
+    }
+
+    /// A command run by a compound statement and the context it runs in.
+    struct CompoundCase {
+        source: &'static str,
+        /// Arguments of the last command with this name
+        argv: &'static [&'static str],
+        subshell: bool,
+        substitution: bool,
+        background: bool,
+        pipeline_position: Option<usize>,
+    }
+
+    impl CompoundCase {
+        /// A command run in the foreground of the current shell, outside a pipeline
+        const PLAIN: Self = Self {
+            source: "",
+            argv: &[],
+            subshell: false,
+            substitution: false,
+            background: false,
+            pipeline_position: None,
+        };
+    }
+
+    /// Commands run by compound statements
+    const COMPOUND_CORPUS: &[CompoundCase] = &[
+        CompoundCase {
+            source: "for f in x; do rm $f; done",
+            argv: &["rm", "$f"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "for ((i = 0; i < 3; i++)); do rm x$i; done",
+            argv: &["rm", "x$i"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "while true; do rm -rf x; done",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "until false; do rm -rf x; done",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "select f in a b; do rm $f; done",
+            argv: &["rm", "$f"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "cat list | while read f; do rm \"$f\"; done",
+            argv: &["rm", "$f"],
+            pipeline_position: Some(1),
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "for f in x; do rm $f; done &",
+            argv: &["rm", "$f"],
+            background: true,
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "if test -d x; then rm -rf x; fi",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "if false; then :; elif true; then rm a; else rm b; fi",
+            argv: &["rm", "b"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "case $1 in clean) rm -rf dist;; esac",
+            argv: &["rm", "-rf", "dist"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "clean() { rm -rf dist; }; clean",
+            argv: &["rm", "-rf", "dist"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "function clean { rm -rf dist; }",
+            argv: &["rm", "-rf", "dist"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "coproc rm -rf x",
+            argv: &["rm", "-rf", "x"],
+            subshell: true,
+            background: true,
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "coproc (rm -rf x)",
+            argv: &["rm", "-rf", "x"],
+            subshell: true,
+            background: true,
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "coproc { rm a; rm -rf x; }",
+            argv: &["rm", "-rf", "x"],
+            subshell: true,
+            background: true,
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "coproc worker { rm -rf x; }",
+            argv: &["rm", "-rf", "x"],
+            subshell: true,
+            background: true,
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "diff <(rm -rf x) y",
+            argv: &["rm", "-rf", "x"],
+            substitution: true,
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "tee >(rm -rf x) < y",
+            argv: &["rm", "-rf", "x"],
+            substitution: true,
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "time rm -rf x",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "time -p rm -rf x",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "time { rm -rf x; }",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "time ls | rm -rf x",
+            argv: &["rm", "-rf", "x"],
+            pipeline_position: Some(1),
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "! rm -rf x",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "! { rm -rf x; }",
+            argv: &["rm", "-rf", "x"],
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "! ls | rm -rf x",
+            argv: &["rm", "-rf", "x"],
+            pipeline_position: Some(1),
+            ..CompoundCase::PLAIN
+        },
+        CompoundCase {
+            source: "[[ $(rm -rf x) ]]",
+            argv: &["rm", "-rf", "x"],
+            substitution: true,
+            ..CompoundCase::PLAIN
+        },
+    ];
+
+    #[test]
+    fn test_extract_compound_statements() {
+        for case in COMPOUND_CORPUS {
+            let source = case.source;
+            let commands = ShellParser::new().parse(source);
+            let cmd = commands
+                .iter()
+                .rfind(|cmd| cmd.name() == case.argv[0])
+                .unwrap_or_else(|| panic!("{} not found in `{}`", case.argv[0], source));
+            assert_eq!(cmd.argv, case.argv, "{}", source);
+            assert_eq!(
+                cmd.context.subshell, case.subshell,
+                "subshell in `{}`",
+                source
+            );
+            assert_eq!(
+                cmd.context.substitution, case.substitution,
+                "substitution in `{}`",
+                source
+            );
+            assert_eq!(
+                cmd.context.background, case.background,
+                "background in `{}`",
+                source
+            );
+            assert_eq!(
+                cmd.context.pipeline_position, case.pipeline_position,
+                "pipeline position in `{}`",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_windows_dialects() {
+        let mut parser = ShellParser::new().with_dialect(ShellDialect::PowerShell);
+        let source = "Set-Location build; Remove-Item -Recurse -Force out";
+        let commands = parser.parse(source);
+        let rm = commands.iter().find(|cmd| cmd.name() == "rm").unwrap();
+        assert_eq!(rm.args(), ["-r", "-f", "--", "out"]);
+        assert_eq!(&source[rm.span.clone()], "Remove-Item -Recurse -Force out");
+        assert!(commands.iter().any(|cmd| cmd.name() == "cd"));
+
+        let mut parser = ShellParser::new().with_dialect(ShellDialect::Cmd);
+        let commands = parser.parse("rd /s /q build & taskkill /F /PID 42");
+        assert!(commands.iter().any(|cmd| cmd.name() == "rm"));
+        assert!(commands.iter().any(|cmd| cmd.name() == "taskkill"));
+
+        // Windows shells invoked from Bash are analysed in every dialect
+        let commands = ShellParser::new().parse("pwsh -Command 'Remove-Item -Recurse x'");
+        let rm = commands.iter().find(|cmd| cmd.name() == "rm").unwrap();
+        assert_eq!(rm.context.nested_in.as_deref(), Some("pwsh"));
+    }
+
+    #[test]
+    fn test_script_source() {
+        let source = |command: &str| ShellParser::new().parse(command)[0].script_source();
+        assert_eq!(
+            source("bash -c 'make'"),
+            Some(ScriptSource::Code("make".to_string()))
+        );
+        assert_eq!(
+            source("bash -o pipefail install.sh"),
+            Some(ScriptSource::File("install.sh".to_string()))
+        );
+        assert_eq!(source("sh -s -- --prefix=/opt"), Some(ScriptSource::Stdin));
+        assert_eq!(
+            source("bash -lc 'make'"),
+            Some(ScriptSource::Code("make".to_string()))
+        );
+        assert_eq!(
+            source("sh -e -- install.sh"),
+            Some(ScriptSource::File("install.sh".to_string()))
+        );
+        assert_eq!(source("bash"), Some(ScriptSource::Stdin));
+        assert_eq!(
+            source("eval echo hi"),
+            Some(ScriptSource::Code("echo hi".to_string()))
+        );
+        assert_eq!(
+            source(". ./env.sh"),
+            Some(ScriptSource::File("./env.sh".to_string()))
+        );
+        assert_eq!(
+            source("python3 -W ignore setup.py"),
+            Some(ScriptSource::File("setup.py".to_string()))
+        );
+        assert_eq!(source("python3 -"), Some(ScriptSource::Stdin));
+        assert_eq!(
+            source("node -e 'run()'"),
+            Some(ScriptSource::Code("run()".to_string()))
+        );
+        assert_eq!(source("ls -la"), None);
+    }
+
+    #[test]
+    fn test_extract_redirects() {
+        let mut parser = ShellParser::new();
+        let redirect = |operator: &str, target: &str| Redirect {
+            operator: operator.to_string(),
+            target: target.to_string(),
+        };
+
+        assert_eq!(
+            parser.extract_redirects("echo x > /etc/hosts 2>&1 >> log.txt"),
+            vec![redirect(">", "/etc/hosts"), redirect(">>", "log.txt")]
+        );
+        assert_eq!(
+            parser.extract_redirects("bash -c 'cat a 2>/dev/null'"),
+            vec![redirect(">", "/dev/null")]
+        );
+        assert!(parser.extract_redirects("cat < input.txt").is_empty());
+        // Redirects after a heredoc operator
+        assert_eq!(
+            parser.extract_redirects("cat <<EOF > /etc/hosts\n127.0.0.1 x\nEOF"),
+            vec![redirect(">", "/etc/hosts")]
+        );
+        assert!(redirect("&>", "out").overwrites());
+        assert!(!redirect(">>", "out").overwrites());
+    }
+
+    #[test]
+    #[cfg(feature = "ast-parser")]
+    fn test_extract_redirects_resolves_nested_code() {
+        let mut parser = ShellParser::new();
+        let targets = |parser: &mut ShellParser, command: &str| -> Vec<String> {
+            parser
+                .extract_redirects(command)
+                .into_iter()
+                .map(|redirect| redirect.target)
+                .collect()
+        };
+
+        assert_eq!(
+            targets(&mut parser, "F=/etc/hosts; echo x > \"$F\""),
+            vec!["/etc/hosts"]
+        );
+        assert_eq!(
+            targets(&mut parser, "sudo sh -c 'echo x >/etc/a'"),
+            vec!["/etc/a"]
+        );
+        assert_eq!(
+            targets(&mut parser, "eval 'echo x > /etc/b'"),
+            vec!["/etc/b"]
+        );
+        assert_eq!(
+            targets(&mut parser, "bash <<'EOF'\necho x > /etc/c\nEOF"),
+            vec!["/etc/c"]
+        );
+        assert_eq!(targets(&mut parser, "{ echo a; } > /etc/d"), vec!["/etc/d"]);
+        assert!(targets(&mut parser, "echo x >&-").is_empty());
+        assert!(targets(&mut parser, "echo '> /etc/hosts'").is_empty());
+    }
+
+    #[test]
+    fn test_git_subcommand_index() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(git_subcommand_index(&args("clean -fdx")), Some(0));
+        assert_eq!(
+            git_subcommand_index(&args("-C dir -c k=v push -f")),
+            Some(4)
+        );
+        assert_eq!(git_subcommand_index(&args("--no-pager log")), Some(1));
+        assert_eq!(git_subcommand_index(&args("--version")), None);
+    }
+
     #[test]
     fn test_extract_subshell() {
         let mut parser = ShellParser::new();
@@ -825,4 +2845,104 @@ mod tests {
         assert!(commands.contains(&"cd".to_string()));
         assert!(commands.contains(&"yarn".to_string()));
     }
+
+    /// Why the parsers read an input of the test tables above differently
+    #[cfg(feature = "ast-parser")]
+    const UNRESOLVED_SYMBOLS: &str = "the fallback parser does not resolve variables and aliases";
+
+    /// Inputs of the test tables above that the parsers read differently, with the reason
+    #[cfg(feature = "ast-parser")]
+    const FALLBACK_GAPS: &[(&str, &str)] = &[
+        ("X=rm; $X -rf dir", UNRESOLVED_SYMBOLS),
+        ("X=/bin/rm; \"$X\" -rf dir", UNRESOLVED_SYMBOLS),
+        ("export X=rm; ${X} -rf dir", UNRESOLVED_SYMBOLS),
+        ("A=r; B=m; $A$B -rf dir", UNRESOLVED_SYMBOLS),
+        ("X=\"rm -rf\"; Y=$X; $Y dir", UNRESOLVED_SYMBOLS),
+        ("alias d=rm; d x", UNRESOLVED_SYMBOLS),
+        ("alias d='cd /tmp; rm -rf'; d x", UNRESOLVED_SYMBOLS),
+        ("X=rm; sudo $X dir", UNRESOLVED_SYMBOLS),
+        ("X=$(which rm); $X dir", UNRESOLVED_SYMBOLS),
+        ("X=ls; X+=x; $X dir", UNRESOLVED_SYMBOLS),
+        (
+            "python3 - <<'EOF'\nimport shutil\nshutil.rmtree('build')\nEOF",
+            "tree-sitter-bash drops a `-` operand in front of a heredoc",
+        ),
+        (
+            "coproc (rm -rf x)",
+            "tree-sitter-bash reads `coproc` in front of a subshell as a command",
+        ),
+        (
+            "coproc { rm a; rm -rf x; }",
+            "tree-sitter-bash reads `coproc {` and the first command as one command",
+        ),
+        (
+            "coproc worker { rm -rf x; }",
+            "tree-sitter-bash reads `coproc NAME {` and the first command as one command",
+        ),
+        (
+            "time { rm -rf x; }",
+            "tree-sitter-bash reads `time {` and the first command as one command",
+        ),
+        (
+            "! { rm -rf x; }",
+            "tree-sitter-bash starts the span of the first command at the brace",
+        ),
+    ];
+
+    #[test]
+    #[cfg(feature = "ast-parser")]
+    fn test_fallback_agrees_with_tree_sitter() {
+        let tables: &[&[&str]] = &[
+            SHELL_OPTION_CLUSTER_CORPUS,
+            NORMALIZED_NAME_CORPUS,
+            SYMBOL_CORPUS,
+            DYNAMIC_NAME_CORPUS,
+            STATIC_SUBSTITUTION_CORPUS,
+            OBFUSCATED_COMMAND_CORPUS,
+            OBFUSCATED_SUBSTITUTION_CORPUS,
+            STDIN_SCRIPT_CORPUS,
+            STDIN_DATA_CORPUS,
+        ];
+        let sources: Vec<&str> = tables
+            .iter()
+            .flat_map(|table| table.iter().copied())
+            .chain(COMPOUND_CORPUS.iter().map(|case| case.source))
+            .chain(
+                fallback::tests::SPLIT_CORPUS
+                    .iter()
+                    .map(|(source, _)| *source),
+            )
+            .chain(
+                fallback::tests::SPLIT_ERROR_CORPUS
+                    .iter()
+                    .map(|(source, _)| *source),
+            )
+            .collect();
+        for (source, _) in FALLBACK_GAPS {
+            assert!(
+                sources.contains(source),
+                "`{}` is not in a test table",
+                source
+            );
+        }
+
+        let gaps = |source: &&str| FALLBACK_GAPS.iter().any(|(gap, _)| gap == source);
+        for source in sources.into_iter().filter(|source| !gaps(source)) {
+            let mut tree_sitter = ShellParser::new();
+            let mut fallback = ShellParser::new();
+            fallback.fallback_only = true;
+            let commands = (fallback.parse(source), tree_sitter.parse(source));
+            let complete = tree_sitter.confidence() == &ParseConfidence::Complete;
+            assert_eq!(
+                fallback.confidence() == &ParseConfidence::Complete,
+                complete,
+                "confidence in `{}`",
+                source
+            );
+            // The parsers recover from syntax errors differently
+            if complete {
+                assert_eq!(commands.0, commands.1, "`{}`", source);
+            }
+        }
+    }
 }
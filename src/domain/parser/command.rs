@@ -0,0 +1,283 @@
+//! Structured command model produced by [`ShellParser::parse`](super::ShellParser::parse).
+
+use std::ops::Range;
+
+use super::{interpreter, is_source_command, unquote, SHELL_COMMANDS};
+
+/// A single command invocation found in a shell command string.
+///
+/// Commands run by other commands are separate entries: `sudo rm x` yields
+/// `sudo rm x` and `rm x` (with `sudo` in its wrapper chain), and `bash -c 'rm x'`
+/// yields `bash -c 'rm x'` and `rm x` (nested in `bash`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCommand {
+    /// Normalized command name followed by its unquoted arguments (never empty)
+    pub argv: Vec<String>,
+    /// Command word as written, unquoted but not normalized
+    /// (`./.venv/bin/pip` for `pip`, `$X` for a variable resolved to `rm`)
+    pub command_word: String,
+    /// Environment assignments for this command (`FOO=1 cmd`, `env FOO=1 cmd`)
+    pub env: Vec<(String, String)>,
+    /// Redirects applied to the command's output, including those of enclosing
+    /// groups (`{ a; b; } > f`) and wrappers (`sudo tee > f`)
+    pub redirects: Vec<Redirect>,
+    /// Commands that run this one with its argument vector, outermost first
+    /// (`sudo`, `env`, `xargs`, `find -exec`)
+    pub wrappers: Vec<String>,
+    /// Where the command runs in the command string
+    pub context: CommandContext,
+    /// Byte range in the parsed command string. Commands from nested code
+    /// (shell -c, eval, sourced files, heredocs, aliases) use the range of the
+    /// command that runs them.
+    pub span: Range<usize>,
+}
+
+impl ParsedCommand {
+    /// Create a command from its argument vector with an empty context.
+    pub fn new(argv: Vec<String>, span: Range<usize>) -> Self {
+        Self {
+            command_word: unquote(&argv[0]),
+            argv,
+            env: Vec::new(),
+            redirects: Vec::new(),
+            wrappers: Vec::new(),
+            context: CommandContext::default(),
+            span,
+        }
+    }
+
+    /// Normalized command name.
+    pub fn name(&self) -> &str {
+        &self.argv[0]
+    }
+
+    /// Unquoted arguments, without the command name.
+    pub fn args(&self) -> &[String] {
+        &self.argv[1..]
+    }
+
+    /// Get where the command reads the code it runs, if it is a shell, `eval`,
+    /// `source` or a script interpreter.
+    pub fn script_source(&self) -> Option<ScriptSource> {
+        let (name, args) = (self.name(), self.args());
+        if SHELL_COMMANDS.contains(&name) {
+            return Some(ShellInvocation::parse(args).source);
+        }
+        if name == "eval" {
+            return (!args.is_empty()).then(|| ScriptSource::Code(args.join(" ")));
+        }
+        if is_source_command(name) {
+            return args.first().cloned().map(ScriptSource::File);
+        }
+        interpreter::script_source(name, args)
+    }
+
+    /// Get the commands that run in the command and process substitutions of this
+    /// command's words (`curl` in `bash <(curl x)`), out of the commands parsed with it.
+    pub fn substitutions<'a>(
+        &'a self,
+        commands: &'a [ParsedCommand],
+    ) -> impl Iterator<Item = &'a ParsedCommand> + 'a {
+        commands.iter().filter(move |cmd| {
+            cmd.context.substitution
+                && !std::ptr::eq(*cmd, self)
+                && self.span.start <= cmd.span.start
+                && cmd.span.end <= self.span.end
+        })
+    }
+
+    /// Create the command run by this one with `argv`, inheriting its redirects,
+    /// context and span and adding this command to the wrapper chain.
+    pub(super) fn wrapped(&self, argv: Vec<String>, env: Vec<(String, String)>) -> Self {
+        let mut wrappers = self.wrappers.clone();
+        wrappers.push(self.name().to_string());
+        Self {
+            command_word: argv[0].clone(),
+            argv,
+            env: self.env.iter().cloned().chain(env).collect(),
+            redirects: self.redirects.clone(),
+            wrappers,
+            context: self.context.clone(),
+            span: self.span.clone(),
+        }
+    }
+
+    /// Move a command parsed from nested code into the context of the command running it.
+    pub(super) fn nest_in(
+        &mut self,
+        context: &CommandContext,
+        span: &Range<usize>,
+        runner: Option<&str>,
+    ) {
+        self.context.subshell |= context.subshell;
+        self.context.substitution |= context.substitution;
+        self.context.background |= context.background;
+        self.context.obfuscated |= context.obfuscated;
+        if self.context.nested_in.is_none() {
+            self.context.nested_in = runner
+                .map(String::from)
+                .or_else(|| context.nested_in.clone());
+        }
+        self.span = span.clone();
+    }
+}
+
+/// Nesting context of a [`ParsedCommand`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandContext {
+    /// Runs in a subshell (`(cd x && rm y)`)
+    pub subshell: bool,
+    /// Runs in a command or process substitution (`$(...)`, backticks, `<(...)`)
+    pub substitution: bool,
+    /// Zero-based position in a pipeline (`a | b` puts `b` at 1), if part of one
+    pub pipeline_position: Option<usize>,
+    /// Names of the commands in earlier stages of the enclosing pipelines, whose
+    /// output this command reads (`a | b | c` gives `c` the upstream `a`, `b`)
+    pub upstream: Vec<String>,
+    /// Runs in the background (`npm run dev &`)
+    pub background: bool,
+    /// Reads standard input from a file, heredoc or here-string (`< in`, `<<EOF`, `<<<`)
+    pub stdin_redirected: bool,
+    /// The text or file standard input is redirected from, when it is one
+    /// (`<<EOF` and `<<<` text, `< in`)
+    pub stdin: Option<Stdin>,
+    /// Decoded from text hidden from name-based checks (`$'\x72\x6d'`, `r{m,}`,
+    /// `$(printf '\162\155')`, `echo cm0gLXJmIC4= | base64 -d | sh`)
+    pub obfuscated: bool,
+    /// Command whose code string contains this command
+    /// (`bash` for `bash -c '...'`, `eval`, `source`, `python3` for `python3 -c '...'`)
+    pub nested_in: Option<String>,
+}
+
+/// Where a shell or interpreter reads the code it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptSource {
+    /// Code given as an argument (`bash -c '...'`, `python -c '...'`, `eval ...`)
+    Code(String),
+    /// A script file (`bash install.sh`, `python <(curl ...)`, `source env.sh`)
+    File(String),
+    /// Standard input (`curl ... | bash`, `python -`)
+    Stdin,
+}
+
+impl ScriptSource {
+    /// Check if the code or script path is produced by a command or process
+    /// substitution (`"$(curl x)"`, `<(wget -O- x)`).
+    pub fn is_substituted(&self) -> bool {
+        match self {
+            Self::Code(argument) | Self::File(argument) => {
+                argument.contains("$(") || argument.contains("<(") || argument.contains('`')
+            }
+            Self::Stdin => false,
+        }
+    }
+}
+
+/// The arguments of a shell invocation (`bash -lc '...'`, `sh -e install.sh`).
+pub(super) struct ShellInvocation {
+    /// Where the shell reads its script: the first operand with `-c`,
+    /// a script file, or standard input when there is neither (or with `-s`)
+    pub source: ScriptSource,
+    /// The first option the shell is given that is not recognized
+    pub unknown_option: Option<String>,
+}
+
+impl ShellInvocation {
+    /// Single-letter options, which may be combined (`-lc`, `-ec`, `+x`)
+    const SHORT_OPTIONS: &'static str = "abcefhiklmnprstuvxBCDEHPT";
+    /// Single-letter options that take the next argument as their value (`-o pipefail`)
+    const SHORT_OPTIONS_WITH_VALUES: &'static str = "oO";
+    const LONG_OPTIONS: &'static [&'static str] = &[
+        "--login",
+        "--noprofile",
+        "--norc",
+        "--posix",
+        "--restricted",
+        "--verbose",
+        "--noediting",
+        "--debugger",
+        "--dump-strings",
+        "--dump-po-strings",
+        "--pretty-print",
+        "--help",
+        "--version",
+    ];
+    const LONG_OPTIONS_WITH_VALUES: &'static [&'static str] = &["--rcfile", "--init-file"];
+
+    /// Parse the arguments of a shell the way bash does: options, including
+    /// combined short options, end at `--`, `-` or the first operand.
+    pub(super) fn parse(args: &[String]) -> Self {
+        let mut command = false;
+        let mut stdin = false;
+        let mut unknown_option = None;
+        let mut i = 0;
+        while let Some(arg) = args.get(i) {
+            if arg == "--" || arg == "-" {
+                i += 1;
+                break;
+            }
+            if arg.starts_with("--") {
+                if Self::LONG_OPTIONS_WITH_VALUES.contains(&arg.as_str()) {
+                    i += 1;
+                } else if !Self::LONG_OPTIONS.contains(&arg.as_str()) {
+                    unknown_option.get_or_insert_with(|| arg.clone());
+                }
+                i += 1;
+                continue;
+            }
+            let Some(letters) = arg.strip_prefix(['-', '+']) else {
+                break;
+            };
+            i += 1;
+            for letter in letters.chars() {
+                match letter {
+                    'c' => command = true,
+                    's' => stdin = true,
+                    letter if Self::SHORT_OPTIONS_WITH_VALUES.contains(letter) => i += 1,
+                    letter if Self::SHORT_OPTIONS.contains(letter) => {}
+                    _ => {
+                        unknown_option.get_or_insert_with(|| arg.clone());
+                    }
+                }
+            }
+        }
+
+        let source = match args.get(i) {
+            Some(code) if command => ScriptSource::Code(code.clone()),
+            Some(path) if !command && !stdin => ScriptSource::File(path.clone()),
+            _ => ScriptSource::Stdin,
+        };
+        Self {
+            source,
+            unknown_option,
+        }
+    }
+}
+
+/// Standard input redirected to a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stdin {
+    /// The text of a heredoc or here-string (`<<EOF`, `<<< 'text'`)
+    Text(String),
+    /// An unquoted file path (`< in.sql`)
+    File(String),
+}
+
+/// A redirect that writes a command's output to a file (`>`, `>>`, `&>`, `>|`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    /// Redirect operator without the file descriptor (`2>` is `>`)
+    pub operator: String,
+    /// Unquoted target path, with variables defined earlier in the string expanded
+    pub target: String,
+}
+
+impl Redirect {
+    /// Operators that truncate or replace the target instead of appending to it
+    const OVERWRITING_OPERATORS: &'static [&'static str] = &[">", ">|", "&>", ">&"];
+
+    /// Check if the redirect truncates or overwrites its target.
+    pub fn overwrites(&self) -> bool {
+        Self::OVERWRITING_OPERATORS.contains(&self.operator.as_str())
+    }
+}
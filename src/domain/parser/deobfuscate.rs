@@ -0,0 +1,400 @@
+//! Decoding of command text hidden from name-based checks: ANSI-C quoting (`$'\x72\x6d'`),
+//! brace expansion (`r{m,}`), printf/echo escapes and encoded payloads piped into shells
+//! (`echo cm0gLXJmIC4= | base64 -d | sh`).
+
+/// Commands that decode their input into text another command may run
+pub const DECODERS: &[&str] = &[
+    "base64", "base32", "basenc", "xxd", "openssl", "uudecode", "rev", "gunzip", "zcat",
+];
+
+/// Maximum number of words a brace expansion may produce
+const MAX_BRACE_WORDS: usize = 64;
+
+/// Decode backslash escapes as in `$'...'`, printf formats and `echo -e`
+/// (`\n`, `\x72`, `\162`, `\u00e9`, `\cA`). Unknown escapes are kept.
+pub(super) fn decode_escapes(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('a') => '\x07',
+            Some('b') => '\x08',
+            Some('e' | 'E') => '\x1b',
+            Some('f') => '\x0c',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('v') => '\x0b',
+            Some(c @ ('\\' | '\'' | '"' | '?')) => c,
+            Some(radix @ ('x' | 'u' | 'U')) => {
+                let max_digits = match radix {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 8,
+                };
+                match take_digits(&mut chars, 16, max_digits) {
+                    (value, digits) if digits > 0 => char::from_u32(value).unwrap_or('\u{fffd}'),
+                    _ => {
+                        decoded.push('\\');
+                        radix
+                    }
+                }
+            }
+            Some(digit @ '0'..='7') => {
+                let (rest, digits) = take_digits(&mut chars, 8, 2);
+                let value = (digit as u32 - '0' as u32) * 8u32.pow(digits) + rest;
+                char::from((value & 0xff) as u8)
+            }
+            Some('c') => match chars.next() {
+                Some(c) => char::from_u32(c as u32 & 0x1f).unwrap_or(c),
+                None => 'c',
+            },
+            Some(other) => {
+                decoded.push('\\');
+                other
+            }
+            None => '\\',
+        };
+        decoded.push(escaped);
+    }
+
+    decoded
+}
+
+/// Take up to `max` digits of a radix from the iterator, returning their value and count.
+fn take_digits(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    radix: u32,
+    max: u32,
+) -> (u32, u32) {
+    let (mut value, mut count) = (0, 0);
+    while count < max {
+        let Some(digit) = chars.peek().and_then(|c| c.to_digit(radix)) else {
+            break;
+        };
+        chars.next();
+        value = value * radix + digit;
+        count += 1;
+    }
+    (value, count)
+}
+
+/// Expand the brace expressions of a raw shell word (`r{m,}` is `rm r`,
+/// `/{etc,usr}` is `/etc /usr`). Quoted braces, `${...}` and braces without a
+/// comma are left alone, so a word without an expansion is returned as-is.
+pub(super) fn expand_braces(word: &str) -> Vec<String> {
+    let Some((open, close, commas)) = find_brace_expression(word) else {
+        return vec![word.to_string()];
+    };
+
+    let (prefix, suffix) = (&word[..open], &word[close + 1..]);
+    let mut bounds = vec![open];
+    bounds.extend(commas);
+    bounds.push(close);
+
+    let mut words = Vec::new();
+    for alternative in bounds.windows(2) {
+        let expanded = format!(
+            "{}{}{}",
+            prefix,
+            &word[alternative[0] + 1..alternative[1]],
+            suffix
+        );
+        words.extend(expand_braces(&expanded));
+        if words.len() >= MAX_BRACE_WORDS {
+            words.truncate(MAX_BRACE_WORDS);
+            break;
+        }
+    }
+    words
+}
+
+/// Find the first unquoted brace expression with a top-level comma,
+/// returning the positions of its braces and commas.
+fn find_brace_expression(word: &str) -> Option<(usize, usize, Vec<usize>)> {
+    let bytes = word.as_bytes();
+    let mut quote = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match (quote, bytes[i]) {
+            (None, b'\\') => i += 1,
+            (None, q @ (b'\'' | b'"')) => quote = Some(q),
+            (Some(q), c) if c == q => quote = None,
+            (None, b'{') if i == 0 || bytes[i - 1] != b'$' => {
+                if let Some((close, commas)) = match_brace(bytes, i) {
+                    if !commas.is_empty() {
+                        return Some((i, close, commas));
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Find the closing brace matching the one at `open` and the commas between them at its level.
+fn match_brace(bytes: &[u8], open: usize) -> Option<(usize, Vec<usize>)> {
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut quote = None;
+    let mut i = open + 1;
+
+    while i < bytes.len() {
+        match (quote, bytes[i]) {
+            (None, b'\\') => i += 1,
+            (None, q @ (b'\'' | b'"')) => quote = Some(q),
+            (Some(q), c) if c == q => quote = None,
+            (None, b'{') => depth += 1,
+            (None, b'}') if depth == 0 => return Some((i, commas)),
+            (None, b'}') => depth -= 1,
+            (None, b',') if depth == 0 => commas.push(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Check if a raw shell word hides its text behind ANSI-C escapes (`$'\x72\x6d'`),
+/// brace expansion (`r{m,}`) or a substitution that decodes it (`$(printf '\162\155')`).
+pub(super) fn is_encoded(word: &str) -> bool {
+    has_ansi_c_escape(word) || expand_braces(word).len() > 1 || has_decoding_substitution(word)
+}
+
+/// Check if a word contains an ANSI-C quoted string with backslash escapes.
+fn has_ansi_c_escape(word: &str) -> bool {
+    let mut rest = word;
+    while let Some(i) = rest.find("$'") {
+        let body = &rest[i + 2..];
+        let end = body.find('\'').unwrap_or(body.len());
+        if body[..end].contains('\\') {
+            return true;
+        }
+        rest = &body[end..];
+    }
+    false
+}
+
+/// Check if a word contains a command substitution that decodes escapes or encoded text.
+fn has_decoding_substitution(word: &str) -> bool {
+    (word.contains("$(") || word.contains('`'))
+        && (word.contains('\\')
+            || word
+                .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+                .any(|name| DECODERS.contains(&name)))
+}
+
+/// Get the text a pipeline of literal output and decoders produces
+/// (`echo cm0gLXJmIC4= | base64 -d`, `printf '726d' | xxd -r -p`), from the
+/// argument vectors of its stages.
+///
+/// Returns `None` unless the first stage prints literal text (`echo`, `printf`)
+/// and every later stage is a decoder this module understands.
+pub(super) fn decode_pipeline(stages: &[Vec<String>]) -> Option<String> {
+    let (first, decoders) = stages.split_first()?;
+    if stages
+        .iter()
+        .flatten()
+        .any(|word| word.contains(['$', '`']))
+    {
+        return None;
+    }
+
+    let mut text = literal_output(first)?;
+    for decoder in decoders {
+        text = decode(decoder, &text)?;
+    }
+    Some(text)
+}
+
+/// Get the output of `echo` or `printf` with literal arguments.
+fn literal_output(argv: &[String]) -> Option<String> {
+    let (name, args) = argv.split_first()?;
+    match name.as_str() {
+        "echo" => {
+            let flags = args
+                .iter()
+                .take_while(|arg| {
+                    arg.len() > 1
+                        && arg.starts_with('-')
+                        && arg[1..].chars().all(|c| "neE".contains(c))
+                })
+                .collect::<Vec<_>>();
+            let text = args[flags.len()..].join(" ");
+            let text = match flags.iter().any(|flag| flag.contains('e')) {
+                true => decode_escapes(&text),
+                false => text,
+            };
+            let newline = !flags.iter().any(|flag| flag.contains('n'));
+            Some(if newline { text + "\n" } else { text })
+        }
+        "printf" => match args {
+            [format] if !format.contains('%') => Some(decode_escapes(format)),
+            [format, values @ ..] if format == "%s" => Some(values.concat()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Run a decoder on its input, if it is one this module understands.
+fn decode(argv: &[String], input: &str) -> Option<String> {
+    let (name, args) = argv.split_first()?;
+    let has = |flags: &[&str]| args.iter().any(|arg| flags.contains(&arg.as_str()));
+
+    let bytes = match name.as_str() {
+        "base64" if has(&["-d", "-D", "--decode", "-di", "-id"]) => decode_base64(input)?,
+        "basenc" if has(&["--base64"]) && has(&["-d", "--decode"]) => decode_base64(input)?,
+        // `openssl base64 -d`, `openssl enc -d -base64`
+        "openssl"
+            if has(&["-d"])
+                && (args.first().is_some_and(|arg| arg == "base64") || has(&["-base64", "-a"])) =>
+        {
+            decode_base64(input)?
+        }
+        // `xxd -r -p`, `xxd -rp`, `xxd -r -ps`
+        "xxd"
+            if args.iter().any(|arg| arg.starts_with("-r"))
+                && args.iter().any(|arg| {
+                    arg.starts_with("-p") || (arg.starts_with("-r") && arg.contains('p'))
+                }) =>
+        {
+            decode_hex(input)?
+        }
+        "rev" if args.is_empty() => {
+            return Some(
+                input
+                    .lines()
+                    .map(|line| line.chars().rev().collect::<String>() + "\n")
+                    .collect(),
+            )
+        }
+        _ => return None,
+    };
+    String::from_utf8(bytes).ok()
+}
+
+/// Decode base64 text, ignoring whitespace and padding.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// Decode plain hex text (`726d`), ignoring whitespace.
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u32> = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16))
+        .collect::<Option<_>>()?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] * 16 + pair[1]) as u8)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argvs(pipeline: &str) -> Vec<Vec<String>> {
+        pipeline
+            .split('|')
+            .map(|stage| stage.split_whitespace().map(String::from).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_escapes() {
+        assert_eq!(decode_escapes(r"\x72\x6d"), "rm");
+        assert_eq!(decode_escapes(r"\162\155 -rf"), "rm -rf");
+        assert_eq!(decode_escapes(r"rm\n"), "rm\n");
+        assert_eq!(decode_escapes(r"it\'s \q"), r"it's \q");
+        assert_eq!(decode_escapes(r"\0"), "\0");
+    }
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("r{m,}"), vec!["rm", "r"]);
+        assert_eq!(expand_braces("/{etc,usr}/x"), vec!["/etc/x", "/usr/x"]);
+        assert_eq!(expand_braces("{a,b{c,d}}"), vec!["a", "bc", "bd"]);
+        assert_eq!(expand_braces("{r,}{m,}"), vec!["rm", "r", "m", ""]);
+        assert_eq!(expand_braces("'{a,b}'"), vec!["'{a,b}'"]);
+        assert_eq!(expand_braces("${x,y}"), vec!["${x,y}"]);
+        assert_eq!(expand_braces("{}"), vec!["{}"]);
+        assert_eq!(expand_braces("{a..c}"), vec!["{a..c}"]);
+    }
+
+    #[test]
+    fn test_is_encoded() {
+        assert!(is_encoded(r"$'\x72\x6d'"));
+        assert!(is_encoded("r{m,}"));
+        assert!(is_encoded(r"$(printf '\162\155')"));
+        assert!(is_encoded("\"$(echo cm0= | base64 -d)\""));
+
+        assert!(!is_encoded("rm"));
+        assert!(!is_encoded("$'hello'"));
+        assert!(!is_encoded("'{a,b}'"));
+        assert!(!is_encoded("$(git rev-parse HEAD)"));
+    }
+
+    #[test]
+    fn test_decode_pipeline() {
+        assert_eq!(
+            decode_pipeline(&argvs("echo cm0gLXJmIC4= | base64 -d")).as_deref(),
+            Some("rm -rf .")
+        );
+        assert_eq!(
+            decode_pipeline(&argvs("echo -n 726d202d7266202e | xxd -r -p")).as_deref(),
+            Some("rm -rf .")
+        );
+        assert_eq!(
+            decode_pipeline(&argvs(r"printf \162\155\40-rf\40.")).as_deref(),
+            Some("rm -rf .")
+        );
+        assert_eq!(
+            decode_pipeline(&argvs("echo . fr- mr | rev")).as_deref(),
+            Some("rm -rf .\n")
+        );
+        assert_eq!(
+            decode_pipeline(&argvs("echo cm0gLXJmIC4= | openssl base64 -d")).as_deref(),
+            Some("rm -rf .")
+        );
+
+        assert_eq!(decode_pipeline(&argvs("cat payload | base64 -d")), None);
+        assert_eq!(decode_pipeline(&argvs("echo $X | base64 -d")), None);
+        assert_eq!(decode_pipeline(&argvs("echo cm0= | base64")), None);
+        assert_eq!(decode_pipeline(&argvs("echo !!! | base64 -d")), None);
+    }
+}
@@ -0,0 +1,1129 @@
+//! Quote- and nesting-aware splitting of shell command strings, used when tree-sitter
+//! is not available (builds without the `ast-parser` feature) or gives up.
+//!
+//! The splitter recognizes the structure tree-sitter-bash does: lists, pipelines,
+//! subshells, brace groups, compound commands (`if`, `while`, `until`, `for`,
+//! `select`, `case`, `[[ ]]`, `(( ))`), function definitions, command and process
+//! substitutions, and heredocs. Words are kept as ranges of the source so that the
+//! parser resolves quoting the same way as for tree-sitter nodes.
+
+use std::ops::Range;
+
+/// Control operators, longest first
+const OPERATORS: &[&str] = &[
+    ";;&", ";;", ";&", "&&", "||", "|&", ";", "&", "|", "\n", "(", ")",
+];
+
+/// Operators that end a case item
+const CASE_TERMINATORS: &[&str] = &[";;&", ";;", ";&"];
+
+/// Nesting of lists, commands and expansions beyond which the rest is left unsplit,
+/// so that deeply nested strings cannot exhaust the stack
+const MAX_NESTING: usize = 100;
+
+/// Redirect operators, longest first
+const REDIRECT_OPERATORS: &[&str] = &[
+    "&>>", "&>", "<<<", "<<-", "<<", "<>", "<&", "<", ">>", ">&", ">|", ">",
+];
+
+/// A command string split into pipelines.
+pub(super) struct Script {
+    pub list: Vec<Pipeline>,
+    /// Heredocs, indexed by [`Redirection::heredoc`]
+    pub heredocs: Vec<Heredoc>,
+    /// Why the string is not valid shell syntax (an unterminated quote, a missing `fi`)
+    pub error: Option<String>,
+}
+
+/// Commands connected by pipes.
+pub(super) struct Pipeline {
+    pub stages: Vec<Stage>,
+    /// Followed by `&`, or part of an and-or list followed by `&`
+    pub background: bool,
+}
+
+/// A pipeline stage.
+pub(super) enum Stage {
+    Simple(Simple),
+    Compound(Compound),
+}
+
+impl Default for Stage {
+    fn default() -> Self {
+        Stage::Simple(Simple::default())
+    }
+}
+
+/// A simple command: assignments, words and redirects.
+#[derive(Default)]
+pub(super) struct Simple {
+    /// Range of the assignments and words, or of the redirects if there are none
+    pub span: Range<usize>,
+    /// Assignments and words, in order
+    pub words: Vec<Range<usize>>,
+    pub redirects: Vec<Redirection>,
+    /// Command and process substitutions in the words and redirects
+    pub substitutions: Vec<Vec<Pipeline>>,
+}
+
+/// A subshell, brace group, compound command or function definition.
+pub(super) struct Compound {
+    /// Runs in a subshell (`( list )`, `coproc { list; }`)
+    pub subshell: bool,
+    /// Conditions and bodies
+    pub body: Vec<Pipeline>,
+    pub redirects: Vec<Redirection>,
+    /// Command and process substitutions before the body (`for f in $(ls)`)
+    pub header_substitutions: Vec<Vec<Pipeline>>,
+    /// Command and process substitutions in the redirects
+    pub substitutions: Vec<Vec<Pipeline>>,
+}
+
+/// A redirect of a simple or compound command.
+pub(super) struct Redirection {
+    /// Operator without its file descriptor (`>` for `2>`)
+    pub operator: &'static str,
+    pub target: Option<Range<usize>>,
+    /// Index of the heredoc in [`Script::heredocs`]
+    pub heredoc: Option<usize>,
+}
+
+/// The body of a heredoc and the substitutions expanded in it.
+#[derive(Default)]
+pub(super) struct Heredoc {
+    pub body: Range<usize>,
+    pub substitutions: Vec<Vec<Pipeline>>,
+}
+
+/// Split a command string into pipelines.
+pub(super) fn split(source: &str) -> Script {
+    let mut splitter = Splitter {
+        source,
+        pos: 0,
+        end: source.len(),
+        substitutions: Vec::new(),
+        heredocs: Vec::new(),
+        pending: Vec::new(),
+        depth: 0,
+        error: None,
+    };
+    let list = splitter.top_list();
+    if let Some(heredoc) = splitter.pending.first() {
+        let reason = format!("missing heredoc body for `{}`", heredoc.delimiter);
+        splitter.fail(reason);
+    }
+    Script {
+        list,
+        heredocs: splitter.heredocs,
+        error: splitter.error,
+    }
+}
+
+/// A heredoc whose body starts after the next newline.
+struct PendingHeredoc {
+    index: usize,
+    delimiter: String,
+    /// `<<-` strips leading tabs from the body lines
+    strip_tabs: bool,
+    /// Unquoted delimiters expand substitutions in the body
+    expands: bool,
+}
+
+struct Splitter<'a> {
+    source: &'a str,
+    pos: usize,
+    /// End of the text being split (the closing backtick of a substitution)
+    end: usize,
+    /// Substitutions found since the start of the commands being split
+    substitutions: Vec<Vec<Pipeline>>,
+    heredocs: Vec<Heredoc>,
+    pending: Vec<PendingHeredoc>,
+    /// Nesting of the list, command or expansion being split
+    depth: usize,
+    error: Option<String>,
+}
+
+impl<'a> Splitter<'a> {
+    fn byte(&self, i: usize) -> Option<u8> {
+        (i < self.end).then(|| self.source.as_bytes()[i])
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        self.source.as_bytes()[self.pos..self.end].starts_with(text.as_bytes())
+    }
+
+    /// Move past the character at the current position.
+    fn advance(&mut self) {
+        if let Some(c) = self.source[self.pos..self.end].chars().next() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Record why the string is not valid syntax, keeping the first reason.
+    fn fail(&mut self, reason: String) {
+        self.error.get_or_insert(reason);
+    }
+
+    /// Record a syntax error at `start`.
+    fn fail_near(&mut self, start: usize) {
+        let text = self.source[start..self.end]
+            .lines()
+            .next()
+            .unwrap_or_default();
+        let text: String = text.chars().take(40).collect();
+        self.fail(format!("syntax error near `{}`", text.trim()));
+    }
+
+    /// Expect a closing reserved word or operator.
+    fn expect(&mut self, closer: &str) {
+        if self.keyword() == Some(closer) || (closer == ")" && self.byte(self.pos) == Some(b')')) {
+            self.pos += closer.len();
+        } else {
+            self.fail(format!("missing `{}`", closer));
+        }
+    }
+
+    /// Run `split` one nesting level deeper, unless the maximum is reached,
+    /// in which case the rest of the text is left unsplit.
+    fn nested<T: Default>(&mut self, split: impl FnOnce(&mut Self) -> T) -> T {
+        if self.depth >= MAX_NESTING {
+            self.fail("commands are nested too deeply".to_string());
+            self.pos = self.end;
+            return T::default();
+        }
+        self.depth += 1;
+        let split = split(self);
+        self.depth -= 1;
+        split
+    }
+
+    /// Skip blanks, line continuations and comments.
+    fn blanks(&mut self) {
+        while let Some(c) = self.byte(self.pos) {
+            match c {
+                b' ' | b'\t' | b'\r' => self.pos += 1,
+                b'\\' if self.byte(self.pos + 1) == Some(b'\n') => self.pos += 2,
+                b'#' => {
+                    while self.byte(self.pos).is_some_and(|c| c != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Skip blanks and newlines.
+    fn blank_lines(&mut self) {
+        loop {
+            self.blanks();
+            if self.byte(self.pos) != Some(b'\n') {
+                break;
+            }
+            self.newline();
+        }
+    }
+
+    /// Move past a newline and the bodies of the heredocs started on its line.
+    fn newline(&mut self) {
+        self.pos += 1;
+        for heredoc in std::mem::take(&mut self.pending) {
+            let start = self.pos;
+            let mut body_end = self.end;
+            while self.pos < self.end {
+                let line_end = self.source[self.pos..self.end]
+                    .find('\n')
+                    .map_or(self.end, |i| self.pos + i);
+                let line = &self.source[self.pos..line_end];
+                let line = match heredoc.strip_tabs {
+                    true => line.trim_start_matches('\t'),
+                    false => line,
+                };
+                let line_start = self.pos;
+                self.pos = (line_end + 1).min(self.end);
+                if line == heredoc.delimiter {
+                    body_end = line_start;
+                    break;
+                }
+            }
+
+            let substitutions = match heredoc.expands {
+                true => self.body_substitutions(start..body_end),
+                false => Vec::new(),
+            };
+            self.heredocs[heredoc.index] = Heredoc {
+                body: start..body_end,
+                substitutions,
+            };
+        }
+    }
+
+    /// Find the substitutions expanded in a heredoc body.
+    fn body_substitutions(&mut self, body: Range<usize>) -> Vec<Vec<Pipeline>> {
+        let (pos, end) = (self.pos, self.end);
+        let mark = self.substitutions.len();
+        self.pos = body.start;
+        self.end = body.end;
+        while let Some(c) = self.byte(self.pos) {
+            match c {
+                b'$' => self.dollar(),
+                b'`' => self.backticks(),
+                b'\\' => {
+                    self.pos += 1;
+                    self.advance();
+                }
+                _ => self.advance(),
+            }
+        }
+        (self.pos, self.end) = (pos, end);
+        self.substitutions.split_off(mark)
+    }
+
+    /// Get the control operator at the current position.
+    fn operator(&self) -> Option<&'static str> {
+        // `&>` and `&>>` are redirects
+        if self.starts_with("&>") {
+            return None;
+        }
+        OPERATORS.iter().copied().find(|op| self.starts_with(op))
+    }
+
+    /// Get the reserved word or plain name at the current position
+    /// (`if`, `{`, `}`, `!`, `done`), if it is followed by a delimiter.
+    fn keyword(&self) -> Option<&'a str> {
+        let len = self.source.as_bytes()[self.pos..self.end]
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic() || matches!(c, b'{' | b'}' | b'!' | b'['))
+            .count();
+        let delimited = match self.byte(self.pos + len) {
+            None => true,
+            Some(c) => matches!(
+                c,
+                b' ' | b'\t' | b'\r' | b'\n' | b';' | b'&' | b'|' | b'(' | b')' | b'<' | b'>'
+            ),
+        };
+        (len > 0 && delimited).then(|| &self.source[self.pos..self.pos + len])
+    }
+
+    /// Get the length (with file descriptor) and operator of the redirect at the current position.
+    fn redirect_operator(&self) -> Option<(usize, &'static str)> {
+        let bytes = &self.source.as_bytes()[self.pos..self.end];
+        let digits = bytes.iter().take_while(|c| c.is_ascii_digit()).count();
+        let rest = &bytes[digits..];
+        let operator = REDIRECT_OPERATORS
+            .iter()
+            .copied()
+            .find(|op| rest.starts_with(op.as_bytes()))?;
+        // `<(...)` and `>(...)` are process substitutions
+        let substitutes = matches!(operator, "<" | ">") && rest.get(1) == Some(&b'(');
+        if substitutes || (digits > 0 && operator.starts_with('&')) {
+            return None;
+        }
+        Some((digits + operator.len(), operator))
+    }
+
+    /// Split the commands of the whole string, reporting stray closers
+    /// (`)`, `;;`, `fi`) and continuing after them.
+    fn top_list(&mut self) -> Vec<Pipeline> {
+        let mut list = Vec::new();
+        loop {
+            list.extend(self.list(&[]));
+            if self.pos >= self.end {
+                return list;
+            }
+            self.fail_near(self.pos);
+            match self.operator() {
+                Some(op) => self.pos += op.len(),
+                None => self.advance(),
+            }
+        }
+    }
+
+    /// Split pipelines until the end of the text, a `)`, a case item terminator,
+    /// or one of the `stop` words in command position.
+    fn list(&mut self, stop: &[&str]) -> Vec<Pipeline> {
+        self.nested(|splitter| splitter.pipelines(stop))
+    }
+
+    /// Split the pipelines of a list at the current nesting level.
+    fn pipelines(&mut self, stop: &[&str]) -> Vec<Pipeline> {
+        let mut list: Vec<Pipeline> = Vec::new();
+        // First pipeline of the and-or list being split
+        let mut chain = 0;
+        loop {
+            self.blank_lines();
+            let operator = self.operator();
+            if self.pos >= self.end
+                || matches!(operator, Some(")" | ";;&" | ";;" | ";&"))
+                || self.keyword().is_some_and(|word| stop.contains(&word))
+            {
+                return list;
+            }
+            if operator.is_some_and(|op| op != "(") {
+                // An operator without a command before it (`; ls`, `&& ls`)
+                self.fail_near(self.pos);
+                self.pos += operator.map_or(1, str::len);
+                continue;
+            }
+
+            let start = self.pos;
+            list.push(self.pipeline());
+            if self.pos == start {
+                self.fail_near(self.pos);
+                self.advance();
+            }
+            self.blanks();
+            match self.operator() {
+                Some(op @ ("&&" | "||")) => {
+                    self.pos += op.len();
+                    continue;
+                }
+                Some("&") => {
+                    self.pos += 1;
+                    for pipeline in &mut list[chain..] {
+                        pipeline.background = true;
+                    }
+                }
+                Some(";") => self.pos += 1,
+                Some("\n") => self.newline(),
+                _ => {}
+            }
+            chain = list.len();
+        }
+    }
+
+    /// Split a pipeline.
+    fn pipeline(&mut self) -> Pipeline {
+        let mut stages = Vec::new();
+        self.blanks();
+        // `!` negates the exit status
+        if self.keyword() == Some("!") {
+            self.pos += 1;
+        }
+        loop {
+            stages.push(self.command());
+            self.blanks();
+            match self.operator() {
+                Some(op @ ("|" | "|&")) => self.pos += op.len(),
+                _ => break,
+            }
+            self.blank_lines();
+        }
+        Pipeline {
+            stages,
+            background: false,
+        }
+    }
+
+    /// Split a simple or compound command.
+    fn command(&mut self) -> Stage {
+        self.nested(Self::stage)
+    }
+
+    /// Split a command at the current nesting level.
+    fn stage(&mut self) -> Stage {
+        self.blanks();
+        let mark = self.substitutions.len();
+        let (subshell, body) = match self.keyword() {
+            Some("{") => {
+                self.pos += 1;
+                let body = self.list(&["}"]);
+                self.expect("}");
+                (false, body)
+            }
+            Some("if") => (false, self.if_clause()),
+            Some(word @ ("while" | "until")) => {
+                self.pos += word.len();
+                let mut body = self.list(&["do"]);
+                self.expect("do");
+                body.extend(self.list(&["done"]));
+                self.expect("done");
+                (false, body)
+            }
+            Some(word @ ("for" | "select")) => {
+                self.pos += word.len();
+                self.header();
+                self.expect("do");
+                let body = self.list(&["done"]);
+                self.expect("done");
+                (false, body)
+            }
+            Some("case") => (false, self.case_clause()),
+            Some("[[") => {
+                self.pos += 2;
+                self.test_clause();
+                (false, Vec::new())
+            }
+            Some("function") => {
+                self.pos += "function".len();
+                self.blanks();
+                self.word();
+                self.blanks();
+                if self.starts_with("(") {
+                    self.pos += 1;
+                    self.blanks();
+                    self.expect(")");
+                }
+                self.blank_lines();
+                return self.command();
+            }
+            Some("coproc") => match self.coprocess() {
+                Some(stage) => return stage,
+                None => return self.simple(mark),
+            },
+            // Timed compound commands (`time { make; }`); the parser looks through
+            // `time` in front of simple commands like a wrapper
+            Some("time") => {
+                let start = self.pos;
+                self.pos += "time".len();
+                self.blanks();
+                if self.starts_with("-p") {
+                    self.pos += 2;
+                    self.blanks();
+                }
+                if self.starts_with("(") || self.starts_compound() {
+                    return self.command();
+                }
+                self.pos = start;
+                return self.simple(mark);
+            }
+            _ if self.starts_with("((") => {
+                // Arithmetic command
+                self.pos += 1;
+                self.parenthesized();
+                (false, Vec::new())
+            }
+            _ if self.starts_with("(") => {
+                self.pos += 1;
+                let body = self.list(&[]);
+                self.expect(")");
+                (true, body)
+            }
+            _ => match self.function_name() {
+                Some(end) => {
+                    self.pos = end;
+                    self.blank_lines();
+                    return self.command();
+                }
+                None => return self.simple(mark),
+            },
+        };
+
+        let header_substitutions = self.substitutions.split_off(mark);
+        let mut redirects = Vec::new();
+        loop {
+            self.blanks();
+            match self.redirect() {
+                Some(redirect) => redirects.push(redirect),
+                None => break,
+            }
+        }
+        Stage::Compound(Compound {
+            subshell,
+            body,
+            redirects,
+            header_substitutions,
+            substitutions: self.substitutions.split_off(mark),
+        })
+    }
+
+    /// Split an `if` clause into its conditions and branches.
+    fn if_clause(&mut self) -> Vec<Pipeline> {
+        self.pos += "if".len();
+        let mut body = Vec::new();
+        loop {
+            body.extend(self.list(&["then"]));
+            self.expect("then");
+            body.extend(self.list(&["elif", "else", "fi"]));
+            match self.keyword() {
+                Some("elif") => self.pos += "elif".len(),
+                Some("else") => {
+                    self.pos += "else".len();
+                    body.extend(self.list(&["fi"]));
+                    self.expect("fi");
+                    return body;
+                }
+                _ => {
+                    self.expect("fi");
+                    return body;
+                }
+            }
+        }
+    }
+
+    /// Skip the words of a `for` or `select` header up to `do`.
+    fn header(&mut self) {
+        loop {
+            self.blanks();
+            match self.byte(self.pos) {
+                None => return,
+                Some(b'\n') => self.newline(),
+                Some(b';') => self.pos += 1,
+                // Arithmetic header (`for ((i = 0; i < 3; i++))`)
+                Some(b'(') => {
+                    self.pos += 1;
+                    self.parenthesized();
+                }
+                _ if self.keyword() == Some("do") => return,
+                _ => {
+                    if self.word().is_none() {
+                        self.fail_near(self.pos);
+                        self.advance();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Split a `case` clause into the commands of its items.
+    fn case_clause(&mut self) -> Vec<Pipeline> {
+        self.pos += "case".len();
+        self.blanks();
+        self.word();
+        self.blank_lines();
+        self.expect("in");
+
+        let mut body = Vec::new();
+        loop {
+            self.blank_lines();
+            if self.keyword() == Some("esac") || self.pos >= self.end {
+                self.expect("esac");
+                return body;
+            }
+            // Patterns up to the closing parenthesis (`(a|b)`, `*.rs)`)
+            if self.starts_with("(") {
+                self.pos += 1;
+            }
+            loop {
+                self.blanks();
+                match self.byte(self.pos) {
+                    Some(b')') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some(b'|') => self.pos += 1,
+                    None | Some(b'\n') => {
+                        self.fail("missing `)`".to_string());
+                        break;
+                    }
+                    _ => {
+                        if self.word().is_none() {
+                            self.fail_near(self.pos);
+                            self.advance();
+                        }
+                    }
+                }
+            }
+            body.extend(self.list(&["esac"]));
+            if let Some(terminator) = CASE_TERMINATORS.iter().find(|op| self.starts_with(op)) {
+                self.pos += terminator.len();
+            }
+        }
+    }
+
+    /// Skip a `[[ ]]` test, whose `<` and `>` compare strings.
+    fn test_clause(&mut self) {
+        loop {
+            self.blanks();
+            match self.byte(self.pos) {
+                None => {
+                    self.fail("missing `]]`".to_string());
+                    return;
+                }
+                _ if self.starts_with("]]") => {
+                    self.pos += 2;
+                    return;
+                }
+                Some(b'\n') => self.newline(),
+                _ => self.scan(),
+            }
+        }
+    }
+
+    /// Split `coproc` followed by a compound command, which runs in the background
+    /// in a subshell. Returns `None` for a simple command (`coproc rm x`), whose
+    /// coprocess the parser finds like a wrapped command.
+    fn coprocess(&mut self) -> Option<Stage> {
+        let start = self.pos;
+        self.pos += "coproc".len();
+        self.blanks();
+        // A name is only given before a compound command (`coproc NAME { ...; }`)
+        if let Some(name) = self.keyword().filter(|_| !self.starts_compound()) {
+            self.pos += name.len();
+            self.blanks();
+        }
+        if !self.starts_with("(") && !self.starts_compound() {
+            self.pos = start;
+            return None;
+        }
+
+        let stage = self.command();
+        Some(Stage::Compound(Compound {
+            subshell: true,
+            body: vec![Pipeline {
+                stages: vec![stage],
+                background: true,
+            }],
+            redirects: Vec::new(),
+            header_substitutions: Vec::new(),
+            substitutions: Vec::new(),
+        }))
+    }
+
+    /// Check if a compound command starting with a reserved word starts here.
+    fn starts_compound(&self) -> bool {
+        matches!(
+            self.keyword(),
+            Some("{" | "if" | "while" | "until" | "for" | "select" | "case" | "[[")
+        )
+    }
+
+    /// Get the end of a function definition header (`name()`), if one starts here.
+    fn function_name(&self) -> Option<usize> {
+        let bytes = self.source.as_bytes();
+        let mut i = self.pos;
+        while i < self.end
+            && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'-' | b'.' | b':'))
+        {
+            i += 1;
+        }
+        if i == self.pos {
+            return None;
+        }
+        let skip_blanks = |mut i: usize| {
+            while i < self.end && matches!(bytes[i], b' ' | b'\t') {
+                i += 1;
+            }
+            i
+        };
+        i = skip_blanks(i);
+        if self.byte(i) != Some(b'(') {
+            return None;
+        }
+        i = skip_blanks(i + 1);
+        (self.byte(i) == Some(b')')).then_some(i + 1)
+    }
+
+    /// Split a simple command. Substitutions from `mark` on belong to it.
+    fn simple(&mut self, mark: usize) -> Stage {
+        let start = self.pos;
+        let mut words: Vec<Range<usize>> = Vec::new();
+        let mut redirects = Vec::new();
+        // End of the last word or here-string, which is part of the command like its words
+        let mut end = None;
+        loop {
+            self.blanks();
+            if let Some(redirect) = self.redirect() {
+                if redirect.operator == "<<<" && !words.is_empty() {
+                    end = Some(self.pos);
+                }
+                redirects.push(redirect);
+                continue;
+            }
+            match self.operator() {
+                None if self.pos < self.end => {}
+                // A parenthesis after words (`echo (x)`)
+                Some("(") if !words.is_empty() => {
+                    self.fail_near(self.pos);
+                    break;
+                }
+                _ => break,
+            }
+            match self.word() {
+                Some(word) => {
+                    end = Some(word.end);
+                    words.push(word);
+                }
+                None => {
+                    self.fail_near(self.pos);
+                    self.advance();
+                }
+            }
+        }
+
+        let span = match (words.first(), end) {
+            (Some(first), Some(end)) => first.start..end,
+            _ => start..self.pos,
+        };
+        Stage::Simple(Simple {
+            span,
+            words,
+            redirects,
+            substitutions: self.substitutions.split_off(mark),
+        })
+    }
+
+    /// Split the redirect at the current position, registering its heredoc.
+    fn redirect(&mut self) -> Option<Redirection> {
+        let (len, operator) = self.redirect_operator()?;
+        let start = self.pos;
+        self.pos += len;
+        self.blanks();
+        let target = self.word();
+        if target.is_none() {
+            self.fail_near(start);
+        }
+
+        let heredoc = match (operator, &target) {
+            ("<<" | "<<-", Some(target)) => {
+                let delimiter = &self.source[target.clone()];
+                let index = self.heredocs.len();
+                self.heredocs.push(Heredoc::default());
+                self.pending.push(PendingHeredoc {
+                    index,
+                    delimiter: delimiter.replace(['\'', '"', '\\'], ""),
+                    strip_tabs: operator == "<<-",
+                    expands: !delimiter.contains(['\'', '"', '\\']),
+                });
+                Some(index)
+            }
+            _ => None,
+        };
+        Some(Redirection {
+            operator,
+            target,
+            heredoc,
+        })
+    }
+
+    /// Split a word, returning its range.
+    fn word(&mut self) -> Option<Range<usize>> {
+        let start = self.pos;
+        while let Some(c) = self.byte(self.pos) {
+            match c {
+                b' ' | b'\t' | b'\r' | b'\n' | b';' | b'&' | b'|' | b')' => break,
+                b'<' | b'>' if self.byte(self.pos + 1) == Some(b'(') => {
+                    self.pos += 2;
+                    self.substitution();
+                }
+                b'<' | b'>' => break,
+                // Array assignments (`a=(x y)`) and extended globs (`@(a|b)`)
+                b'(' if self.pos > start
+                    && matches!(
+                        self.source.as_bytes()[self.pos - 1],
+                        b'=' | b'@' | b'?' | b'*' | b'+' | b'!'
+                    ) =>
+                {
+                    self.pos += 1;
+                    self.parenthesized();
+                }
+                b'(' => break,
+                _ => self.scan(),
+            }
+        }
+        (self.pos > start).then_some(start..self.pos)
+    }
+
+    /// Move past a character of a word, or past the quoted string or expansion starting there.
+    fn scan(&mut self) {
+        match self.byte(self.pos) {
+            Some(b'\\') => {
+                self.pos += 1;
+                self.advance();
+            }
+            Some(b'\'') => {
+                let start = self.pos;
+                self.pos += 1;
+                match self.source.as_bytes()[self.pos..self.end]
+                    .iter()
+                    .position(|&c| c == b'\'')
+                {
+                    Some(i) => self.pos += i + 1,
+                    None => {
+                        self.fail_near(start);
+                        self.pos = self.end;
+                    }
+                }
+            }
+            Some(b'"') => self.double_quoted(),
+            Some(b'`') => self.backticks(),
+            Some(b'$') => self.dollar(),
+            _ => self.advance(),
+        }
+    }
+
+    /// Move past a double-quoted string, splitting the substitutions in it.
+    fn double_quoted(&mut self) {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.byte(self.pos) {
+                None => {
+                    self.fail_near(start);
+                    return;
+                }
+                Some(b'"') => {
+                    self.pos += 1;
+                    return;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    self.advance();
+                }
+                Some(b'$') => self.dollar(),
+                Some(b'`') => self.backticks(),
+                _ => self.advance(),
+            }
+        }
+    }
+
+    /// Move past an expansion or ANSI-C string starting with `$`.
+    fn dollar(&mut self) {
+        self.nested(Self::expansion)
+    }
+
+    /// Move past an expansion at the current nesting level.
+    fn expansion(&mut self) {
+        let start = self.pos;
+        match self.byte(self.pos + 1) {
+            Some(b'\'') => {
+                self.pos += 2;
+                loop {
+                    match self.byte(self.pos) {
+                        None => {
+                            self.fail_near(start);
+                            return;
+                        }
+                        Some(b'\'') => {
+                            self.pos += 1;
+                            return;
+                        }
+                        Some(b'\\') => {
+                            self.pos += 1;
+                            self.advance();
+                        }
+                        _ => self.advance(),
+                    }
+                }
+            }
+            // Arithmetic expansion (`$((1 + 2))`)
+            Some(b'(') if self.byte(self.pos + 2) == Some(b'(') => {
+                self.pos += 2;
+                self.parenthesized();
+            }
+            Some(b'(') => {
+                self.pos += 2;
+                self.substitution();
+            }
+            Some(b'{') => {
+                self.pos += 2;
+                let mut depth = 0;
+                loop {
+                    match self.byte(self.pos) {
+                        None => {
+                            self.fail("missing `}`".to_string());
+                            return;
+                        }
+                        Some(b'{') => {
+                            depth += 1;
+                            self.pos += 1;
+                        }
+                        Some(b'}') if depth == 0 => {
+                            self.pos += 1;
+                            return;
+                        }
+                        Some(b'}') => {
+                            depth -= 1;
+                            self.pos += 1;
+                        }
+                        _ => self.scan(),
+                    }
+                }
+            }
+            _ => self.pos += 1,
+        }
+    }
+
+    /// Move past the rest of a parenthesized expression after its opening parenthesis.
+    fn parenthesized(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.byte(self.pos) {
+                None => {
+                    self.fail("missing `)`".to_string());
+                    return;
+                }
+                Some(b'(') => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                Some(b')') if depth == 0 => {
+                    self.pos += 1;
+                    return;
+                }
+                Some(b')') => {
+                    depth -= 1;
+                    self.pos += 1;
+                }
+                _ => self.scan(),
+            }
+        }
+    }
+
+    /// Split the commands of a command or process substitution after its opening parenthesis.
+    fn substitution(&mut self) {
+        let mut list = Vec::new();
+        loop {
+            list.extend(self.list(&[]));
+            match self.byte(self.pos) {
+                Some(b')') => {
+                    self.pos += 1;
+                    break;
+                }
+                None => {
+                    self.fail("missing `)`".to_string());
+                    break;
+                }
+                // A case item terminator outside a case clause
+                _ => {
+                    self.fail_near(self.pos);
+                    self.pos += self.operator().map_or(1, str::len);
+                }
+            }
+        }
+        self.substitutions.push(list);
+    }
+
+    /// Split the commands of a backtick substitution.
+    fn backticks(&mut self) {
+        let start = self.pos;
+        let mut close = None;
+        let mut chars = self.source[start + 1..self.end].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '`' => {
+                    close = Some(start + 1 + i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let close = match close {
+            Some(close) => close,
+            None => {
+                self.fail_near(start);
+                self.end
+            }
+        };
+
+        let end = self.end;
+        self.pos = start + 1;
+        self.end = close;
+        let list = self.top_list();
+        self.end = end;
+        self.pos = (close + 1).min(end);
+        self.substitutions.push(list);
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// Get the words of every simple command in a split string, substitutions
+    /// after the command they are in.
+    fn commands(source: &str) -> Vec<Vec<&str>> {
+        fn collect<'a>(
+            source: &'a str,
+            list: &[Pipeline],
+            heredocs: &[Heredoc],
+            commands: &mut Vec<Vec<&'a str>>,
+        ) {
+            for stage in list.iter().flat_map(|pipeline| &pipeline.stages) {
+                let (substitutions, redirects) = match stage {
+                    Stage::Simple(simple) => {
+                        let words = simple.words.iter().map(|word| &source[word.clone()]);
+                        commands.push(words.collect());
+                        (&simple.substitutions, &simple.redirects)
+                    }
+                    Stage::Compound(compound) => {
+                        collect(source, &compound.body, heredocs, commands);
+                        (&compound.substitutions, &compound.redirects)
+                    }
+                };
+                let heredocs_substitutions = redirects
+                    .iter()
+                    .filter_map(|redirect| redirect.heredoc)
+                    .flat_map(|index| &heredocs[index].substitutions);
+                for list in substitutions.iter().chain(heredocs_substitutions) {
+                    collect(source, list, heredocs, commands);
+                }
+            }
+        }
+
+        let script = split(source);
+        assert_eq!(script.error, None, "{}", source);
+        let mut commands = Vec::new();
+        collect(source, &script.list, &script.heredocs, &mut commands);
+        commands
+    }
+
+    /// Strings and the words of every simple command split from them
+    pub(in crate::domain::parser) const SPLIT_CORPUS: &[(&str, &[&[&str]])] = &[
+        (
+            "echo 'a; rm x' \"b | rm y\" c\\;d && ls",
+            &[&["echo", "'a; rm x'", "\"b | rm y\"", "c\\;d"], &["ls"]],
+        ),
+        (
+            "echo \"$(rm -rf x; echo ')')\" `ls`",
+            &[
+                &["echo", "\"$(rm -rf x; echo ')')\"", "`ls`"],
+                &["rm", "-rf", "x"],
+                &["echo", "')'"],
+                &["ls"],
+            ],
+        ),
+        (
+            "(cd x && make) | { tee log; } # rm -rf y",
+            &[&["cd", "x"], &["make"], &["tee", "log"]],
+        ),
+        (
+            "case $x in\n  a|b) rm a ;;\n  *) rm b ;;\nesac",
+            &[&["rm", "a"], &["rm", "b"]],
+        ),
+        (
+            "cat <<EOF > out\n$(rm x) ; rm y\nEOF\nls",
+            &[&["cat"], &["rm", "x"], &["ls"]],
+        ),
+        (
+            "a=(1 2) b=$((1 + 2)) env",
+            &[&["a=(1 2)", "b=$((1 + 2))", "env"]],
+        ),
+        ("f() { rm \"${a[@]}\"; }", &[&["rm", "\"${a[@]}\""]]),
+    ];
+
+    /// Strings the splitter rejects and the reason it gives
+    pub(in crate::domain::parser) const SPLIT_ERROR_CORPUS: &[(&str, &str)] = &[
+        (
+            "echo 'unterminated; rm x",
+            "syntax error near `'unterminated; rm x`",
+        ),
+        ("if true; then rm x", "missing `fi`"),
+        ("(cd x && rm y", "missing `)`"),
+        ("echo $(ls", "missing `)`"),
+        ("ls && ; rm x", "syntax error near `; rm x`"),
+        ("cat <<EOF", "missing heredoc body for `EOF`"),
+    ];
+
+    #[test]
+    fn test_split_respects_quotes_and_nesting() {
+        for (source, expected) in SPLIT_CORPUS {
+            assert_eq!(commands(source), *expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_split_errors() {
+        let error = |source: &str| split(source).error.unwrap_or_default();
+        for (source, expected) in SPLIT_ERROR_CORPUS {
+            assert_eq!(error(source), *expected, "{}", source);
+        }
+        assert_eq!(
+            error(&format!("{}rm x{}", "$(".repeat(500), ")".repeat(500))),
+            "commands are nested too deeply"
+        );
+    }
+}
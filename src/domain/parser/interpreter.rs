@@ -0,0 +1,552 @@
+//! Inline interpreter code analysis.
+//!
+//! Translates code passed to `python -c`, `node -e`, `perl -e` and `ruby -e`
+//! into the equivalent shell commands, so the filters see `shutil.rmtree('x')`
+//! as `rm` and `os.system("kill 1")` as `kill`.
+
+use std::ops::Range;
+
+use super::ScriptSource;
+
+/// Scripting languages whose inline code is analyzed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Python,
+    Node,
+    Perl,
+    Ruby,
+}
+
+/// Logical action performed by an interpreter API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// Deletes the files given as arguments (reported as `rm`)
+    Delete,
+    /// Sends a signal to a process (reported as `kill`)
+    Kill,
+    /// Runs its arguments as a command
+    Spawn,
+}
+
+/// Python APIs mapped to the action they perform
+const PYTHON_APIS: &[(&str, Action)] = &[
+    ("os.remove", Action::Delete),
+    ("os.removedirs", Action::Delete),
+    ("shutil.rmtree", Action::Delete),
+    (".unlink", Action::Delete),
+    (".rmdir", Action::Delete),
+    ("os.kill", Action::Kill),
+    ("os.killpg", Action::Kill),
+    ("os.system", Action::Spawn),
+    ("os.popen", Action::Spawn),
+    ("os.execv", Action::Spawn),
+    ("os.execve", Action::Spawn),
+    ("os.execvp", Action::Spawn),
+    ("os.execvpe", Action::Spawn),
+    ("os.execl", Action::Spawn),
+    ("os.execle", Action::Spawn),
+    ("os.execlp", Action::Spawn),
+    ("os.execlpe", Action::Spawn),
+    ("os.spawnv", Action::Spawn),
+    ("os.spawnvp", Action::Spawn),
+    ("os.spawnl", Action::Spawn),
+    ("os.spawnlp", Action::Spawn),
+    ("os.posix_spawn", Action::Spawn),
+    ("os.posix_spawnp", Action::Spawn),
+    ("subprocess.run", Action::Spawn),
+    ("subprocess.call", Action::Spawn),
+    ("subprocess.check_call", Action::Spawn),
+    ("subprocess.check_output", Action::Spawn),
+    ("subprocess.Popen", Action::Spawn),
+    ("subprocess.getoutput", Action::Spawn),
+    ("subprocess.getstatusoutput", Action::Spawn),
+    ("pty.spawn", Action::Spawn),
+];
+
+/// Node.js APIs mapped to the action they perform
+const NODE_APIS: &[(&str, Action)] = &[
+    (".rm", Action::Delete),
+    (".rmSync", Action::Delete),
+    (".rmdir", Action::Delete),
+    (".rmdirSync", Action::Delete),
+    (".unlink", Action::Delete),
+    (".unlinkSync", Action::Delete),
+    ("process.kill", Action::Kill),
+    ("child_process.exec", Action::Spawn),
+    (".execSync", Action::Spawn),
+    (".execFile", Action::Spawn),
+    (".execFileSync", Action::Spawn),
+    (".spawn", Action::Spawn),
+    (".spawnSync", Action::Spawn),
+    ("execSync", Action::Spawn),
+    ("execFileSync", Action::Spawn),
+    ("spawnSync", Action::Spawn),
+];
+
+/// Perl functions mapped to the action they perform
+const PERL_APIS: &[(&str, Action)] = &[
+    ("unlink", Action::Delete),
+    ("rmdir", Action::Delete),
+    ("rmtree", Action::Delete),
+    ("remove_tree", Action::Delete),
+    ("kill", Action::Kill),
+    ("system", Action::Spawn),
+    ("exec", Action::Spawn),
+];
+
+/// Ruby APIs mapped to the action they perform
+const RUBY_APIS: &[(&str, Action)] = &[
+    ("File.delete", Action::Delete),
+    ("File.unlink", Action::Delete),
+    ("Dir.rmdir", Action::Delete),
+    ("Dir.delete", Action::Delete),
+    ("Dir.unlink", Action::Delete),
+    ("FileUtils.rm", Action::Delete),
+    ("FileUtils.rm_f", Action::Delete),
+    ("FileUtils.rm_r", Action::Delete),
+    ("FileUtils.rm_rf", Action::Delete),
+    ("FileUtils.rmdir", Action::Delete),
+    ("FileUtils.rmtree", Action::Delete),
+    ("FileUtils.remove", Action::Delete),
+    ("FileUtils.remove_dir", Action::Delete),
+    ("FileUtils.remove_entry", Action::Delete),
+    ("FileUtils.remove_entry_secure", Action::Delete),
+    ("Process.kill", Action::Kill),
+    ("system", Action::Spawn),
+    ("exec", Action::Spawn),
+    ("spawn", Action::Spawn),
+    ("IO.popen", Action::Spawn),
+    ("Open3.capture2", Action::Spawn),
+    ("Open3.capture3", Action::Spawn),
+    ("Open3.popen3", Action::Spawn),
+];
+
+impl Language {
+    /// Detect the language of an interpreter command name.
+    fn from_command(name: &str) -> Option<Self> {
+        match name {
+            "node" | "nodejs" => Some(Self::Node),
+            "perl" => Some(Self::Perl),
+            "ruby" => Some(Self::Ruby),
+            _ => {
+                // python, python3, python3.12
+                let version = name.strip_prefix("python")?;
+                version
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == '.')
+                    .then_some(Self::Python)
+            }
+        }
+    }
+
+    /// APIs of the language mapped to the action they perform.
+    fn apis(self) -> &'static [(&'static str, Action)] {
+        match self {
+            Self::Python => PYTHON_APIS,
+            Self::Node => NODE_APIS,
+            Self::Perl => PERL_APIS,
+            Self::Ruby => RUBY_APIS,
+        }
+    }
+
+    /// Check if a flag is followed by inline code (`-c`, `-e`, `-pe`, `--eval`).
+    fn is_code_flag(self, flag: &str) -> bool {
+        let cluster = |last: &[char]| {
+            flag.strip_prefix('-').is_some_and(|letters| {
+                !letters.starts_with('-')
+                    && letters.chars().all(|c| c.is_ascii_alphabetic())
+                    && letters.ends_with(last)
+            })
+        };
+        match self {
+            Self::Python => cluster(&['c']),
+            Self::Node => matches!(flag, "-e" | "--eval" | "-p" | "--print"),
+            Self::Perl => cluster(&['e', 'E']),
+            Self::Ruby => cluster(&['e']),
+        }
+    }
+
+    /// Check if a flag takes a separate argument that is not code.
+    fn flag_takes_arg(self, flag: &str) -> bool {
+        match self {
+            Self::Python => matches!(flag, "-W" | "-X"),
+            Self::Node => matches!(flag, "-r" | "--require" | "--import"),
+            Self::Perl => false,
+            Self::Ruby => matches!(flag, "-r" | "-I"),
+        }
+    }
+
+    /// Check if backtick literals run shell commands.
+    fn backticks_spawn(self) -> bool {
+        matches!(self, Self::Perl | Self::Ruby)
+    }
+}
+
+/// Translate the inline code run by an interpreter command into equivalent shell commands.
+///
+/// Returns `None` if the command is not an interpreter running inline code.
+/// Deletions become `rm` with the string literal arguments as operands, kills become `kill`,
+/// and spawned commands are returned as written. Arguments that are not string literals
+/// become a command substitution so they stay unresolvable.
+pub(super) fn shell_equivalent(name: &str, args: &[String]) -> Option<String> {
+    let language = Language::from_command(name)?;
+    let code = inline_code(language, args)?;
+    let commands = analyze(language, &code);
+    Some(commands.join("; "))
+}
+
+/// Translate the program an interpreter reads from standard input
+/// (`python3 - <<EOF`, `node <<< "..."`) into equivalent shell commands.
+///
+/// Returns `None` if the command is not an interpreter reading its program from stdin.
+pub(super) fn stdin_shell_equivalent(name: &str, args: &[String], code: &str) -> Option<String> {
+    let language = Language::from_command(name)?;
+    if inline_code(language, args).is_some() || script_file(language, args).is_some() {
+        return None;
+    }
+    let commands = analyze(language, code);
+    Some(commands.join("; "))
+}
+
+/// Get where an interpreter command reads the program it runs.
+///
+/// Returns `None` if the command is not an interpreter.
+pub(super) fn script_source(name: &str, args: &[String]) -> Option<ScriptSource> {
+    let language = Language::from_command(name)?;
+    if let Some(code) = inline_code(language, args) {
+        return Some(ScriptSource::Code(code));
+    }
+    Some(match script_file(language, args) {
+        Some(path) => ScriptSource::File(path.clone()),
+        None => ScriptSource::Stdin,
+    })
+}
+
+/// Get the script file argument of an interpreter without inline code.
+/// Returns `None` if it reads its program from standard input:
+/// there is no script file argument, or the script is `-`.
+fn script_file(language: Language, args: &[String]) -> Option<&String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-" {
+            return None;
+        }
+        if language.flag_takes_arg(arg) {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            return Some(arg);
+        }
+    }
+    None
+}
+
+/// Extract the inline code passed to an interpreter.
+/// Perl and Ruby accept several `-e` arguments, which run as consecutive lines.
+fn inline_code(language: Language, args: &[String]) -> Option<String> {
+    let mut code = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(inline) = arg
+            .strip_prefix("--eval=")
+            .or_else(|| arg.strip_prefix("--print="))
+            .filter(|_| language == Language::Node)
+        {
+            code.push(inline.to_string());
+        } else if language.is_code_flag(arg) {
+            code.extend(iter.next().cloned());
+            if language == Language::Python {
+                // Remaining arguments are passed to the code as sys.argv
+                break;
+            }
+        } else if language.flag_takes_arg(arg) {
+            iter.next();
+        } else if !arg.starts_with('-') || arg == "-" {
+            // Script file; remaining arguments belong to it
+            break;
+        }
+    }
+
+    if code.is_empty() {
+        None
+    } else {
+        Some(code.join("\n"))
+    }
+}
+
+/// Find the API calls in the code and translate them into shell commands.
+fn analyze(language: Language, code: &str) -> Vec<String> {
+    let literals = string_literals(code);
+
+    // Mask literal contents so that API names and parentheses inside strings are ignored
+    let mut skeleton = code.as_bytes().to_vec();
+    for literal in &literals {
+        for byte in &mut skeleton[literal.content.clone()] {
+            *byte = b' ';
+        }
+    }
+    let skeleton = String::from_utf8_lossy(&skeleton).into_owned();
+
+    // (API name range, shell command) of every call
+    let mut calls: Vec<(Range<usize>, String)> = Vec::new();
+    for (api, action) in language.apis() {
+        for (start, _) in skeleton.match_indices(api) {
+            let end = start + api.len();
+            if !is_api_boundary(&skeleton, start, end, api) {
+                continue;
+            }
+            let arguments = call_arguments(&skeleton, end);
+            let inner: Vec<&Literal> = literals
+                .iter()
+                .filter(|l| l.quote.start >= arguments.start && l.quote.end <= arguments.end)
+                .collect();
+            calls.push((
+                start..end,
+                render(*action, api, &skeleton[arguments], &inner),
+            ));
+        }
+    }
+
+    if language.backticks_spawn() {
+        for literal in literals.iter().filter(|l| l.delimiter == '`') {
+            calls.push((literal.quote.clone(), literal.value.clone()));
+        }
+    }
+
+    // Keep one call per API name, e.g. `.execSync` and `execSync` both match `cp.execSync`
+    calls.sort_by_key(|(range, _)| range.start);
+    let mut commands = Vec::new();
+    let mut covered = 0;
+    for (range, command) in calls {
+        if range.start >= covered || commands.is_empty() {
+            covered = range.end;
+            commands.push(command);
+        }
+    }
+    commands
+}
+
+/// Render an API call as a shell command.
+fn render(action: Action, api: &str, arguments: &str, literals: &[&Literal]) -> String {
+    // Anything besides literals (masked to their delimiters) and separators is only known at runtime
+    let dynamic = arguments.chars().any(|c| {
+        !matches!(
+            c,
+            ',' | '[' | ']' | '(' | ')' | '\'' | '"' | '`' | ' ' | '\t' | '\n' | '\r'
+        )
+    });
+    let unresolved = format!("\"$({})\"", api.trim_start_matches('.'));
+
+    match action {
+        Action::Delete => {
+            let mut command = String::from("rm --");
+            for literal in literals {
+                command.push(' ');
+                command.push_str(&shell_quote(&literal.value));
+            }
+            if dynamic {
+                command.push(' ');
+                command.push_str(&unresolved);
+            }
+            command
+        }
+        Action::Kill => String::from("kill"),
+        Action::Spawn => {
+            let leads_with_literal = arguments
+                .trim_start_matches(['(', '[', ' ', '\t', '\n', '\r'])
+                .starts_with(['\'', '"', '`']);
+            match literals {
+                [first, ..] if leads_with_literal && first.value.contains(char::is_whitespace) => {
+                    // Command line run through a shell
+                    first.value.clone()
+                }
+                [_, ..] if leads_with_literal => literals
+                    .iter()
+                    .map(|literal| shell_quote(&literal.value))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                _ => unresolved,
+            }
+        }
+    }
+}
+
+/// A string literal in interpreter code.
+struct Literal {
+    /// Opening delimiter (`'`, `"` or `` ` ``)
+    delimiter: char,
+    /// Byte range including the delimiters
+    quote: Range<usize>,
+    /// Byte range of the contents
+    content: Range<usize>,
+    /// Contents with backslash escapes removed
+    value: String,
+}
+
+/// Find the string literals in interpreter code.
+fn string_literals(code: &str) -> Vec<Literal> {
+    let mut literals = Vec::new();
+    let mut chars = code.char_indices();
+
+    while let Some((start, delimiter)) = chars.next() {
+        if !matches!(delimiter, '\'' | '"' | '`') {
+            continue;
+        }
+        let mut value = String::new();
+        let mut end = code.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => value.extend(chars.next().map(|(_, escaped)| escaped)),
+                c if c == delimiter => {
+                    end = i;
+                    break;
+                }
+                c => value.push(c),
+            }
+        }
+        literals.push(Literal {
+            delimiter,
+            quote: start..(end + 1).min(code.len()),
+            content: start + 1..end,
+            value,
+        });
+    }
+
+    literals
+}
+
+/// Check that an API name match is not part of a longer identifier.
+fn is_api_boundary(code: &str, start: usize, end: usize, api: &str) -> bool {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let before_ok = api.starts_with('.') || !code[..start].ends_with(is_ident);
+    let after_ok = !code[end..].starts_with(is_ident);
+    before_ok && after_ok
+}
+
+/// Get the byte range of the arguments of a call starting at `start`:
+/// the balanced parentheses if present, otherwise up to the end of the statement.
+fn call_arguments(code: &str, start: usize) -> Range<usize> {
+    let rest = &code[start..];
+    let trimmed = rest.trim_start_matches([' ', '\t']);
+    let begin = start + rest.len() - trimmed.len();
+
+    if trimmed.starts_with('(') {
+        let mut depth = 0;
+        for (i, c) in trimmed.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return begin..begin + i + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        return begin..code.len();
+    }
+
+    let end = trimmed
+        .find([';', '\n', '}'])
+        .map_or(code.len(), |i| begin + i);
+    begin..end
+}
+
+/// Quote a value as a single shell word.
+pub(super) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate(command: &str) -> Option<String> {
+        let tokens = crate::domain::parser::parse_shell_tokens(command);
+        shell_equivalent(&tokens[0], &tokens[1..])
+    }
+
+    #[test]
+    fn test_translate_deletions() {
+        assert_eq!(
+            translate("python3 -c \"import shutil; shutil.rmtree('build')\"").as_deref(),
+            Some("rm -- 'build'")
+        );
+        assert_eq!(
+            translate("node -e \"require('fs').rmSync('dist')\"").as_deref(),
+            Some("rm -- 'dist'")
+        );
+        assert_eq!(
+            translate("perl -e 'unlink \"a\", \"b\"'").as_deref(),
+            Some("rm -- 'a' 'b'")
+        );
+        assert_eq!(
+            translate("ruby -e 'FileUtils.rm_rf(path)'").as_deref(),
+            Some("rm -- \"$(FileUtils.rm_rf)\"")
+        );
+    }
+
+    #[test]
+    fn test_translate_kills_and_spawns() {
+        assert_eq!(
+            translate("python -c 'import os; os.kill(1234, 9)'").as_deref(),
+            Some("kill")
+        );
+        assert_eq!(
+            translate("python -c 'import os; os.system(\"rm -rf x\")'").as_deref(),
+            Some("rm -rf x")
+        );
+        assert_eq!(
+            translate(
+                "python -c 'import subprocess; subprocess.run([\"git\", \"clean\", \"-fd\"])'"
+            )
+            .as_deref(),
+            Some("'git' 'clean' '-fd'")
+        );
+        assert_eq!(
+            translate("node -e 'child_process.execSync(cmd)'").as_deref(),
+            Some("\"$(execSync)\"")
+        );
+        assert_eq!(
+            translate("ruby -e 'puts `pkill node`'").as_deref(),
+            Some("pkill node")
+        );
+    }
+
+    #[test]
+    fn test_api_names_in_strings_are_ignored() {
+        assert_eq!(
+            translate("python -c 'print(\"os.system is dangerous\")'").as_deref(),
+            Some("")
+        );
+        assert_eq!(
+            translate("python -c 'items = [1]; items.remove(1)'").as_deref(),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn test_translate_stdin_program() {
+        let args = |s: &str| crate::domain::parser::parse_shell_tokens(s);
+        assert_eq!(
+            stdin_shell_equivalent("python3", &args("-"), "import os\nos.kill(1, 9)\n").as_deref(),
+            Some("kill")
+        );
+        assert_eq!(
+            stdin_shell_equivalent("ruby", &[], "FileUtils.rm_rf('tmp')").as_deref(),
+            Some("rm -- 'tmp'")
+        );
+        assert!(stdin_shell_equivalent("python3", &args("script.py"), "os.kill(1, 9)").is_none());
+        assert!(stdin_shell_equivalent("node", &args("-e 1"), "process.kill(1)").is_none());
+    }
+
+    #[test]
+    fn test_inline_code_flags() {
+        assert!(translate("python script.py -c 'os.system(\"x\")'").is_none());
+        assert!(translate("python3.12 -c 'os.system(\"rm x\")'").is_some());
+        assert!(translate("perl -i -pe 's/a/b/' file").is_some());
+        assert!(translate("node --eval=\"process.kill(1)\"").is_some());
+        assert!(translate("node -r ts-node/register app.ts").is_none());
+        assert!(translate("rustc -e x").is_none());
+    }
+}
@@ -0,0 +1,1444 @@
+//! PowerShell and cmd.exe command analysis.
+//!
+//! Translates PowerShell and cmd.exe code into equivalent shell commands, so the
+//! filters see `Remove-Item -Recurse x`, `ri x` and `rd /s /q x` as `rm -r`, and
+//! `Stop-Process -Name node` as `pkill`. The analysis is plain string processing
+//! and works the same on every platform.
+
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use super::interpreter::shell_quote;
+
+/// Shell a command string is written for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellDialect {
+    /// POSIX shells (bash, zsh, sh)
+    #[default]
+    Bash,
+    /// Windows PowerShell and PowerShell 7 (`pwsh`)
+    PowerShell,
+    /// Windows command prompt (`cmd.exe`)
+    Cmd,
+}
+
+/// Logical action performed by a PowerShell cmdlet or cmd.exe builtin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// Deletes files and directories (reported as `rm`)
+    Delete,
+    /// Removes directories, with their contents if `/s` is given (reported as `rmdir` or `rm -r`)
+    RemoveDirectory,
+    /// Stops processes (reported as `kill` or `pkill`)
+    Kill,
+    /// Empties files (reported as `truncate -s 0`)
+    Truncate,
+    /// Changes the working directory (reported as `cd`)
+    ChangeDirectory,
+    /// Runs its argument as PowerShell code
+    Evaluate,
+    /// Starts the program given as argument
+    Start,
+}
+
+/// PowerShell cmdlets and their built-in aliases mapped to the action they perform
+const POWERSHELL_COMMANDS: &[(&str, Action)] = &[
+    ("remove-item", Action::Delete),
+    ("ri", Action::Delete),
+    ("rm", Action::Delete),
+    ("rmdir", Action::Delete),
+    ("rd", Action::Delete),
+    ("del", Action::Delete),
+    ("erase", Action::Delete),
+    ("stop-process", Action::Kill),
+    ("spps", Action::Kill),
+    ("kill", Action::Kill),
+    ("clear-content", Action::Truncate),
+    ("clc", Action::Truncate),
+    ("set-location", Action::ChangeDirectory),
+    ("sl", Action::ChangeDirectory),
+    ("cd", Action::ChangeDirectory),
+    ("chdir", Action::ChangeDirectory),
+    ("push-location", Action::ChangeDirectory),
+    ("pushd", Action::ChangeDirectory),
+    ("pop-location", Action::ChangeDirectory),
+    ("popd", Action::ChangeDirectory),
+    ("invoke-expression", Action::Evaluate),
+    ("iex", Action::Evaluate),
+    ("start-process", Action::Start),
+    ("saps", Action::Start),
+    ("start", Action::Start),
+];
+
+/// cmd.exe builtins mapped to the action they perform
+const CMD_COMMANDS: &[(&str, Action)] = &[
+    ("del", Action::Delete),
+    ("erase", Action::Delete),
+    ("rd", Action::RemoveDirectory),
+    ("rmdir", Action::RemoveDirectory),
+    ("cd", Action::ChangeDirectory),
+    ("chdir", Action::ChangeDirectory),
+    ("pushd", Action::ChangeDirectory),
+    ("popd", Action::ChangeDirectory),
+];
+
+/// PowerShell keywords that start a statement rather than a command
+const POWERSHELL_KEYWORDS: &[&str] = &[
+    "if", "elseif", "else", "switch", "while", "for", "foreach", "do", "until", "try", "catch",
+    "finally", "trap", "function", "filter", "param", "begin", "process", "end", "exit", "break",
+    "continue", "class", "enum", "using", "data",
+];
+
+/// PowerShell keywords followed by a pipeline whose commands run (`return Get-Item x`)
+const POWERSHELL_PIPELINE_KEYWORDS: &[&str] = &["return", "throw"];
+
+/// PowerShell assignment operators
+const ASSIGNMENT_OPERATORS: &[&str] = &["=", "+=", "-=", "*=", "/=", "%=", "??="];
+
+/// Parameters of `powershell`/`pwsh` that take a value
+const POWERSHELL_VALUE_PARAMS: &[&str] = &[
+    "executionpolicy",
+    "ep",
+    "windowstyle",
+    "workingdirectory",
+    "wd",
+    "version",
+    "inputformat",
+    "outputformat",
+    "psconsolefile",
+    "configurationname",
+    "settingsfile",
+];
+
+/// Parameters of cmdlets taking paths (`Remove-Item`, `Clear-Content`, `Set-Location`)
+const PATH_PARAMS: &[&str] = &["path", "literalpath", "pspath", "lp"];
+
+/// Parameters of `Remove-Item` and `Clear-Content` that take a value
+const DELETE_VALUE_PARAMS: &[&str] = &[
+    "path",
+    "literalpath",
+    "pspath",
+    "lp",
+    "filter",
+    "include",
+    "exclude",
+    "credential",
+    "stream",
+];
+
+/// Parameters of `Start-Process` naming the program to start
+const FILE_PARAMS: &[&str] = &["filepath", "path", "pspath"];
+
+/// Parameters of `Start-Process` holding the arguments of the program
+const ARGUMENT_PARAMS: &[&str] = &["argumentlist", "args"];
+
+/// Parameters of `Start-Process` that take a value
+const START_VALUE_PARAMS: &[&str] = &[
+    "filepath",
+    "path",
+    "pspath",
+    "argumentlist",
+    "args",
+    "credential",
+    "workingdirectory",
+    "redirectstandarderror",
+    "redirectstandardinput",
+    "redirectstandardoutput",
+    "verb",
+    "windowstyle",
+];
+
+/// .NET methods called from PowerShell expressions mapped to the action they perform
+/// (`[IO.File]::Delete('x')`, `(Get-Item x).Delete()`, `(Get-Process node).Kill()`)
+const DOTNET_METHODS: &[(&str, Action)] = &[
+    ("::delete", Action::Delete),
+    (".delete", Action::Delete),
+    ("::kill", Action::Kill),
+    (".kill", Action::Kill),
+];
+
+/// A token of PowerShell or cmd.exe code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A word with quoting removed; `quoted` if any part of it was quoted
+    Word { text: String, quoted: bool },
+    /// Code whose output is used as a value (`(...)`, `$(...)`, `@(...)`)
+    Group(String),
+    /// A PowerShell script block (`{...}`)
+    Block(String),
+    /// PowerShell call operator (`&`)
+    Call,
+    /// Redirect operator; the target is the next word unless the operator
+    /// duplicates a descriptor (`2>&1`)
+    Redirect(String),
+    /// Pipe between the commands of a pipeline
+    Pipe,
+    /// End of a statement (newline, `;`, `&&`, `||`, and `&` in cmd.exe)
+    End,
+}
+
+impl Token {
+    fn word(text: &str) -> Self {
+        Self::Word {
+            text: text.to_string(),
+            quoted: false,
+        }
+    }
+
+    /// Text of an unquoted word.
+    fn bare_word(&self) -> Option<&str> {
+        match self {
+            Self::Word {
+                text,
+                quoted: false,
+            } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Value of a word that is a literal (not a variable).
+    fn literal(&self) -> Option<&str> {
+        match self {
+            Self::Word { text, quoted } if *quoted || !text.starts_with('$') => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Collects the characters of a word while tokenizing.
+#[derive(Default)]
+struct WordBuilder {
+    text: String,
+    quoted: bool,
+    start: Option<usize>,
+    /// Subexpressions inside double-quoted strings (`"$(Remove-Item x)"`)
+    groups: Vec<String>,
+}
+
+impl WordBuilder {
+    fn push(&mut self, at: usize, text: &str) {
+        self.start.get_or_insert(at);
+        self.text.push_str(text);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start.is_none()
+    }
+
+    /// Push the word (and the subexpressions found in it) as tokens ending at `end`.
+    fn finish(&mut self, tokens: &mut Vec<(Token, Range<usize>)>, end: usize) {
+        let Some(start) = self.start.take() else {
+            return;
+        };
+        let word = std::mem::take(self);
+        tokens.push((
+            Token::Word {
+                text: word.text,
+                quoted: word.quoted,
+            },
+            start..end,
+        ));
+        for group in word.groups {
+            tokens.push((Token::Group(group), start..end));
+        }
+    }
+}
+
+/// Translate PowerShell or cmd.exe code into equivalent shell commands.
+///
+/// Returns one entry per statement that runs commands: its byte range in the code
+/// and the shell commands, one per line. Bash code is returned unchanged.
+pub(super) fn translate(dialect: ShellDialect, code: &str) -> Vec<(Range<usize>, String)> {
+    let tokens = match dialect {
+        ShellDialect::Bash => return vec![(0..code.len(), code.to_string())],
+        ShellDialect::PowerShell => tokenize_powershell(code),
+        ShellDialect::Cmd => tokenize_cmd(code),
+    };
+
+    let mut statements = Vec::new();
+    for statement in tokens.split(|(token, _)| *token == Token::End) {
+        let (Some((_, first)), Some((_, last))) = (statement.first(), statement.last()) else {
+            continue;
+        };
+        let tokens: Vec<Token> = statement.iter().map(|(token, _)| token.clone()).collect();
+        let script = match dialect {
+            ShellDialect::Cmd => cmd_statement(&tokens),
+            _ => powershell_statement(&tokens),
+        };
+        if !script.is_empty() {
+            statements.push((first.start..last.end, script));
+        }
+    }
+    statements
+}
+
+/// Translate the code run by `powershell -Command` or `cmd /c` into equivalent shell commands.
+///
+/// Returns `None` if the command is not PowerShell or cmd.exe running inline code.
+pub(super) fn shell_equivalent(name: &str, args: &[String]) -> Option<String> {
+    let name = name.to_ascii_lowercase();
+    let (dialect, code) = match name.as_str() {
+        "powershell" | "pwsh" => (ShellDialect::PowerShell, powershell_code(&name, args)?),
+        "cmd" => (ShellDialect::Cmd, cmd_code(args)?),
+        _ => return None,
+    };
+    Some(translate_lines(dialect, &code).join("\n"))
+}
+
+/// Translate code into shell command lines without statement ranges.
+fn translate_lines(dialect: ShellDialect, code: &str) -> Vec<String> {
+    translate(dialect, code)
+        .into_iter()
+        .map(|(_, script)| script)
+        .collect()
+}
+
+/// Extract the code passed to `powershell`/`pwsh` with `-Command` or `-EncodedCommand`.
+///
+/// Windows PowerShell runs positional arguments as a command, while `pwsh` runs them as a script file.
+fn powershell_code(name: &str, args: &[String]) -> Option<String> {
+    let mut iter = args.iter().enumerate();
+    while let Some((i, arg)) = iter.next() {
+        let Some(param) = arg.strip_prefix(['-', '/']) else {
+            return (name == "powershell").then(|| args[i..].join(" "));
+        };
+        let param = param.to_ascii_lowercase();
+        if param.is_empty() {
+            // Commands read from standard input
+            return None;
+        }
+        if "command".starts_with(&param) {
+            let code = args[i + 1..].join(" ");
+            return (!code.is_empty() && code != "-").then_some(code);
+        }
+        if "encodedcommand".starts_with(&param) || param == "ec" {
+            return decode_encoded_command(iter.next()?.1);
+        }
+        if "file".starts_with(&param) {
+            return None;
+        }
+        if POWERSHELL_VALUE_PARAMS
+            .iter()
+            .any(|value_param| value_param.starts_with(&param))
+        {
+            iter.next();
+        }
+    }
+    None
+}
+
+/// Extract the command line run by `cmd /c` or `cmd /k`.
+fn cmd_code(args: &[String]) -> Option<String> {
+    for (i, arg) in args.iter().enumerate() {
+        let lower = arg.to_ascii_lowercase();
+        if let Some(rest) = lower
+            .strip_prefix("/c")
+            .or_else(|| lower.strip_prefix("/k"))
+        {
+            let mut code = arg[arg.len() - rest.len()..].to_string();
+            for next in &args[i + 1..] {
+                if !code.is_empty() {
+                    code.push(' ');
+                }
+                code.push_str(next);
+            }
+            return Some(code);
+        }
+        if !arg.starts_with('/') {
+            return None;
+        }
+    }
+    None
+}
+
+/// Decode a `-EncodedCommand` argument: base64 of the UTF-16LE code.
+fn decode_encoded_command(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in encoded.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+/// Find the end of the bracketed code opening at `open`, skipping quoted strings.
+/// Returns the byte index of the closing bracket, or the end of the code.
+fn closing_bracket(code: &str, open: usize) -> usize {
+    let (opening, closing) = match code.as_bytes()[open] {
+        b'(' => ('(', ')'),
+        _ => ('{', '}'),
+    };
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut chars = code[open..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '`') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '`') => {
+                chars.next();
+            }
+            (None, c) if c == opening => depth += 1,
+            (None, c) if c == closing => {
+                depth -= 1;
+                if depth == 0 {
+                    return open + i;
+                }
+            }
+            _ => {}
+        }
+    }
+    code.len()
+}
+
+/// Split PowerShell code into tokens with their byte ranges.
+fn tokenize_powershell(code: &str) -> Vec<(Token, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut word = WordBuilder::default();
+    let mut i = 0;
+
+    while let Some(c) = code[i..].chars().next() {
+        let mut end = i + c.len_utf8();
+        let next = code[end..].chars().next();
+        match c {
+            ' ' | '\t' | '\r' | ',' => word.finish(&mut tokens, i),
+            '\n' | ';' => {
+                word.finish(&mut tokens, i);
+                tokens.push((Token::End, i..end));
+            }
+            '#' if word.is_empty() => {
+                end = code[i..].find('\n').map_or(code.len(), |n| i + n);
+            }
+            '<' if next == Some('#') => {
+                word.finish(&mut tokens, i);
+                end = code[i..].find("#>").map_or(code.len(), |n| i + n + 2);
+            }
+            '|' | '&' if next == Some(c) => {
+                word.finish(&mut tokens, i);
+                end += 1;
+                tokens.push((Token::End, i..end));
+            }
+            '|' => {
+                word.finish(&mut tokens, i);
+                tokens.push((Token::Pipe, i..end));
+            }
+            '&' => {
+                word.finish(&mut tokens, i);
+                tokens.push((Token::Call, i..end));
+            }
+            '\'' => {
+                word.push(i, "");
+                word.quoted = true;
+                let mut chars = code[end..].char_indices().peekable();
+                end = code.len();
+                while let Some((j, c)) = chars.next() {
+                    // A doubled quote is an escaped quote
+                    if c == '\'' && chars.next_if(|(_, c)| *c == '\'').is_none() {
+                        end = i + 1 + j + 1;
+                        break;
+                    }
+                    word.text.push(c);
+                }
+            }
+            '"' => {
+                word.push(i, "");
+                word.quoted = true;
+                let start = end;
+                let mut j = start;
+                end = code.len();
+                while let Some(c) = code[j..].chars().next() {
+                    let after = j + c.len_utf8();
+                    match c {
+                        '`' => {
+                            let escaped = code[after..].chars().next();
+                            word.text.extend(escaped);
+                            j = after + escaped.map_or(0, char::len_utf8);
+                            continue;
+                        }
+                        '"' if code[after..].starts_with('"') => {
+                            word.text.push('"');
+                            j = after + 1;
+                            continue;
+                        }
+                        '"' => {
+                            end = after;
+                            break;
+                        }
+                        '$' if code[after..].starts_with('(') => {
+                            let close = closing_bracket(code, after);
+                            word.groups
+                                .push(code[after + 1..close.min(code.len())].to_string());
+                            word.text.push_str(&code[j..(close + 1).min(code.len())]);
+                            j = (close + 1).min(code.len());
+                            continue;
+                        }
+                        c => word.text.push(c),
+                    }
+                    j = after;
+                }
+            }
+            '`' => match next {
+                // Line continuation
+                Some('\n') => end += 1,
+                Some(escaped) => {
+                    word.push(i, escaped.encode_utf8(&mut [0; 4]));
+                    end += escaped.len_utf8();
+                }
+                None => {}
+            },
+            '@' if next == Some('{') => {
+                // Hashtable literal
+                word.finish(&mut tokens, i);
+                let close = closing_bracket(code, end);
+                end = (close + 1).min(code.len());
+                tokens.push((
+                    Token::Word {
+                        text: code[i..end].to_string(),
+                        quoted: true,
+                    },
+                    i..end,
+                ));
+            }
+            '$' | '@' | '(' if c == '(' || next == Some('(') => {
+                word.finish(&mut tokens, i);
+                let open = if c == '(' { i } else { end };
+                let close = closing_bracket(code, open);
+                end = (close + 1).min(code.len());
+                let inner = code[open + 1..close.min(code.len())].to_string();
+                tokens.push((Token::Group(inner), i..end));
+            }
+            '{' => {
+                word.finish(&mut tokens, i);
+                let close = closing_bracket(code, i);
+                end = (close + 1).min(code.len());
+                let inner = code[i + 1..close.min(code.len())].to_string();
+                tokens.push((Token::Block(inner), i..end));
+            }
+            ')' | '}' => word.finish(&mut tokens, i),
+            '>' => {
+                // Descriptor prefix (`2>`, `*>`) collected as part of the word
+                let is_prefix = !word.quoted
+                    && !word.is_empty()
+                    && (word.text == "*" || word.text.chars().all(|c| c.is_ascii_digit()));
+                let mut operator = if is_prefix {
+                    std::mem::take(&mut word).text
+                } else {
+                    word.finish(&mut tokens, i);
+                    String::new()
+                };
+                operator.push('>');
+                if next == Some('>') {
+                    operator.push('>');
+                    end += 1;
+                }
+                if code[end..].starts_with('&') {
+                    let target: String = code[end + 1..]
+                        .chars()
+                        .take_while(char::is_ascii_digit)
+                        .collect();
+                    if !target.is_empty() {
+                        operator.push('&');
+                        operator.push_str(&target);
+                        end += 1 + target.len();
+                    }
+                }
+                tokens.push((Token::Redirect(operator), i..end));
+            }
+            '=' if !word.quoted && word.text.starts_with('$') => {
+                // `$x=1` without spaces around the operator
+                word.finish(&mut tokens, i);
+                tokens.push((Token::word("="), i..end));
+            }
+            c => word.push(i, c.encode_utf8(&mut [0; 4])),
+        }
+        i = end;
+    }
+    word.finish(&mut tokens, code.len());
+
+    tokens
+}
+
+/// Split cmd.exe code into tokens with their byte ranges.
+fn tokenize_cmd(code: &str) -> Vec<(Token, Range<usize>)> {
+    let mut tokens: Vec<(Token, Range<usize>)> = Vec::new();
+    let mut word = WordBuilder::default();
+    let mut i = 0;
+
+    while let Some(c) = code[i..].chars().next() {
+        let mut end = i + c.len_utf8();
+        let next = code[end..].chars().next();
+        match c {
+            ' ' | '\t' | '\r' | ',' | ';' => word.finish(&mut tokens, i),
+            '\n' | '&' | '|' | ')' => {
+                word.finish(&mut tokens, i);
+                if next == Some(c) && matches!(c, '&' | '|') {
+                    end += 1;
+                }
+                let token = match c {
+                    '|' if end == i + 1 => Token::Pipe,
+                    _ => Token::End,
+                };
+                tokens.push((token, i..end));
+            }
+            '(' => {
+                word.finish(&mut tokens, i);
+                let after_in = matches!(tokens.last(), Some((Token::Word { text, .. }, _)) if text.eq_ignore_ascii_case("in"));
+                if after_in {
+                    // The set of a `for` loop
+                    end = code[i..].find(')').map_or(code.len(), |n| i + n + 1);
+                    tokens.push((
+                        Token::Word {
+                            text: code[i..end].to_string(),
+                            quoted: true,
+                        },
+                        i..end,
+                    ));
+                } else {
+                    tokens.push((Token::End, i..end));
+                }
+            }
+            '"' => {
+                word.push(i, "");
+                word.quoted = true;
+                let close = code[end..].find('"').map_or(code.len(), |n| end + n);
+                word.text.push_str(&code[end..close]);
+                end = (close + 1).min(code.len());
+            }
+            '^' => match next {
+                Some('\r') if code[end..].starts_with("\r\n") => end += 2,
+                Some('\n') => end += 1,
+                Some(escaped) => {
+                    word.push(i, escaped.encode_utf8(&mut [0; 4]));
+                    end += escaped.len_utf8();
+                }
+                None => {}
+            },
+            '@' if word.is_empty()
+                && matches!(tokens.last(), None | Some((Token::End | Token::Pipe, _))) => {}
+            '>' | '<' => {
+                // Descriptor prefix (`2>`) collected as part of the word
+                let is_prefix = c == '>'
+                    && !word.quoted
+                    && !word.is_empty()
+                    && word.text.chars().all(|c| c.is_ascii_digit());
+                let mut operator = if is_prefix {
+                    std::mem::take(&mut word).text
+                } else {
+                    word.finish(&mut tokens, i);
+                    String::new()
+                };
+                operator.push(c);
+                if c == '>' && next == Some('>') {
+                    operator.push('>');
+                    end += 1;
+                }
+                if c == '>' && code[end..].starts_with('&') {
+                    let target: String = code[end + 1..]
+                        .chars()
+                        .take_while(char::is_ascii_digit)
+                        .collect();
+                    if !target.is_empty() {
+                        operator.push('&');
+                        operator.push_str(&target);
+                        end += 1 + target.len();
+                    }
+                }
+                tokens.push((Token::Redirect(operator), i..end));
+            }
+            c => word.push(i, c.encode_utf8(&mut [0; 4])),
+        }
+        i = end;
+    }
+    word.finish(&mut tokens, code.len());
+
+    tokens
+}
+
+/// Separate the redirects of a command from its arguments and render them as shell redirects.
+fn split_redirects(
+    tokens: &[Token],
+    word: fn(&Token) -> Option<String>,
+) -> (Vec<Token>, Vec<String>) {
+    let mut args = Vec::new();
+    let mut redirects = Vec::new();
+    let mut iter = tokens.iter();
+    while let Some(token) = iter.next() {
+        let Token::Redirect(operator) = token else {
+            args.push(token.clone());
+            continue;
+        };
+        if operator.contains('&') {
+            redirects.push(operator.clone());
+            continue;
+        }
+        let Some(target) = iter.next() else {
+            continue;
+        };
+        if operator == "<" {
+            continue;
+        }
+        let operator = operator.replacen('*', "&", 1);
+        let null = matches!(target.literal(), Some(text) if text.eq_ignore_ascii_case("nul"))
+            || target.bare_word() == Some("$null");
+        let target = match null {
+            true => "/dev/null".to_string(),
+            false => word(target).unwrap_or_else(|| "\"$(redirect)\"".to_string()),
+        };
+        redirects.push(format!("{} {}", operator, target));
+    }
+    (args, redirects)
+}
+
+/// Join a command with its arguments and redirects into a shell command line.
+fn command_line(words: Vec<String>, redirects: Vec<String>) -> String {
+    words
+        .into_iter()
+        .chain(redirects)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Translate a PowerShell statement into shell command lines.
+fn powershell_statement(tokens: &[Token]) -> String {
+    // Script blocks and subexpressions evaluated outside of arguments, run after the statement
+    let mut nested = Vec::new();
+    let stages: Vec<String> = tokens
+        .split(|token| *token == Token::Pipe)
+        .filter_map(|stage| powershell_stage(stage, &mut nested))
+        .collect();
+
+    let mut lines = Vec::new();
+    if !stages.is_empty() {
+        lines.push(stages.join(" | "));
+    }
+    lines.extend(nested);
+    lines.join("\n")
+}
+
+/// Translate one command of a PowerShell pipeline.
+/// Returns `None` for expressions and keyword statements, which run no command themselves.
+fn powershell_stage(mut tokens: &[Token], nested: &mut Vec<String>) -> Option<String> {
+    // Assignment: `$x = 'literal'` is kept so `& $x` resolves; `$x = cmd` runs `cmd`
+    if let [variable, Token::Word {
+        text: operator,
+        quoted: false,
+    }, rest @ ..] = tokens
+    {
+        if let Some(name) = variable.bare_word().and_then(|v| v.strip_prefix('$')) {
+            if ASSIGNMENT_OPERATORS.contains(&operator.as_str()) {
+                match rest {
+                    [Token::Word { text, quoted: true }]
+                        if operator == "=" && is_identifier(name) =>
+                    {
+                        return Some(format!("{}={}", name, shell_quote(text)));
+                    }
+                    _ => tokens = rest,
+                }
+            }
+        }
+    }
+
+    let first = tokens.first()?;
+    if let Some(keyword) = first.bare_word().map(str::to_ascii_lowercase) {
+        if POWERSHELL_PIPELINE_KEYWORDS.contains(&keyword.as_str()) {
+            return powershell_stage(&tokens[1..], nested);
+        }
+        if POWERSHELL_KEYWORDS.contains(&keyword.as_str()) {
+            return powershell_expression(tokens, nested);
+        }
+    }
+
+    let (name, args) = match tokens {
+        // `& 'C:\tools\x.exe'`, `& $cmd`, `. .\script.ps1`
+        [Token::Call, name, args @ ..] => (name, args),
+        [dot, name, args @ ..] if dot.bare_word() == Some(".") => (name, args),
+        [name @ Token::Word {
+            text,
+            quoted: false,
+        }, args @ ..]
+            if is_command_name(text) =>
+        {
+            (name, args)
+        }
+        _ => return powershell_expression(tokens, nested),
+    };
+
+    for token in std::iter::once(name).chain(args) {
+        if let Token::Block(code) = token {
+            nested.extend(translate_lines(ShellDialect::PowerShell, code));
+        }
+    }
+    let (args, redirects) = split_redirects(args, powershell_word);
+    let name_word = powershell_word(name)?;
+
+    let action = name.bare_word().and_then(|name| {
+        let name = name.to_ascii_lowercase();
+        let name = name
+            .strip_prefix("microsoft.powershell.management\\")
+            .unwrap_or(&name);
+        POWERSHELL_COMMANDS
+            .iter()
+            .find(|(command, _)| *command == name)
+            .map(|(_, action)| *action)
+    });
+    let words = match action {
+        Some(action) => render_cmdlet(action, &args),
+        None => std::iter::once(name_word)
+            .chain(args.iter().filter_map(powershell_word))
+            .collect(),
+    };
+    Some(command_line(words, redirects))
+}
+
+/// Translate a PowerShell expression: the .NET methods it calls become commands,
+/// and the code in its subexpressions and script blocks is translated after the statement.
+fn powershell_expression(tokens: &[Token], nested: &mut Vec<String>) -> Option<String> {
+    let mut commands = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Group(code) | Token::Block(code) => {
+                nested.extend(translate_lines(ShellDialect::PowerShell, code));
+            }
+            Token::Word {
+                text,
+                quoted: false,
+            } => {
+                let lower = text.to_ascii_lowercase();
+                let Some((_, action)) = DOTNET_METHODS
+                    .iter()
+                    .find(|(method, _)| lower.ends_with(method))
+                else {
+                    continue;
+                };
+                let literals: Vec<String> = match tokens.get(i + 1) {
+                    Some(Token::Group(args)) => tokenize_powershell(args)
+                        .into_iter()
+                        .filter_map(|(token, _)| match token {
+                            Token::Word { text, quoted: true } => Some(shell_quote(&text)),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                commands.push(match action {
+                    Action::Kill => "kill".to_string(),
+                    _ if literals.is_empty() => "rm -- \"$(Delete)\"".to_string(),
+                    _ => format!("rm -- {}", literals.join(" ")),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let mut commands = commands.into_iter();
+    let first = commands.next();
+    nested.extend(commands);
+    first
+}
+
+/// Check if a bare word at the start of a PowerShell statement names a command
+/// rather than starting an expression (variables, numbers, type literals).
+fn is_command_name(word: &str) -> bool {
+    !word.starts_with(['$', '[', '@', '-', '+', '!', '.'])
+        && !word.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Check if a PowerShell variable name is also a valid shell variable name.
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Render a PowerShell token as a shell word.
+///
+/// Variables that are valid shell names stay variables, so values assigned earlier
+/// in the same string resolve; subexpressions become command substitutions.
+fn powershell_word(token: &Token) -> Option<String> {
+    match token {
+        Token::Word {
+            text,
+            quoted: false,
+        } if text.strip_prefix('$').is_some_and(is_identifier) => Some(format!("\"{}\"", text)),
+        Token::Word { text, .. } => Some(shell_quote(text)),
+        Token::Group(code) => {
+            let lines = translate_lines(ShellDialect::PowerShell, code);
+            Some(format!("\"$({})\"", lines.join("; ")))
+        }
+        _ => None,
+    }
+}
+
+/// Parameters of a PowerShell command bound to their values.
+struct Arguments<'a> {
+    /// Positional arguments
+    positional: Vec<&'a Token>,
+    /// Named parameters (lowercase, without the dash) with their value; switches have none
+    named: Vec<(String, Option<&'a Token>)>,
+}
+
+impl<'a> Arguments<'a> {
+    /// Bind arguments to parameters. Parameters starting with one of `value_params`
+    /// take the next argument as their value (`-Path x`, `-Path:x`).
+    fn bind(args: &'a [Token], value_params: &[&str]) -> Self {
+        let mut positional = Vec::new();
+        let mut named = Vec::new();
+        let mut iter = args.iter();
+        while let Some(token) = iter.next() {
+            let param = token
+                .bare_word()
+                .and_then(|word| word.strip_prefix('-'))
+                .filter(|param| param.starts_with(|c: char| c.is_ascii_alphabetic()));
+            let Some(param) = param else {
+                positional.push(token);
+                continue;
+            };
+            let param = param.to_ascii_lowercase();
+            match param.split_once(':') {
+                Some((param, _)) => named.push((param.to_string(), None)),
+                None if value_params.iter().any(|p| p.starts_with(&param)) => {
+                    named.push((param, iter.next()));
+                }
+                None => named.push((param, None)),
+            }
+        }
+        Self { positional, named }
+    }
+
+    /// Check if a switch starting with at least `min` characters of `name` is present.
+    fn switch(&self, name: &str, min: usize) -> bool {
+        self.named
+            .iter()
+            .any(|(param, _)| param.len() >= min && name.starts_with(param.as_str()))
+    }
+
+    /// Values of the parameters that are a prefix of one of `names`.
+    fn values(&self, names: &[&str]) -> Vec<&'a Token> {
+        self.named
+            .iter()
+            .filter(|(param, _)| names.iter().any(|name| name.starts_with(param.as_str())))
+            .filter_map(|(_, value)| *value)
+            .collect()
+    }
+}
+
+/// Render a PowerShell cmdlet as an equivalent shell command.
+fn render_cmdlet(action: Action, args: &[Token]) -> Vec<String> {
+    let words = |tokens: Vec<&Token>| -> Vec<String> {
+        tokens.into_iter().filter_map(powershell_word).collect()
+    };
+
+    match action {
+        Action::Delete | Action::RemoveDirectory | Action::Truncate => {
+            let args = Arguments::bind(args, DELETE_VALUE_PARAMS);
+            let mut command = match action {
+                Action::Truncate => vec!["truncate".to_string(), "-s".to_string(), "0".to_string()],
+                _ => {
+                    let mut command = vec!["rm".to_string()];
+                    if args.switch("recurse", 1) {
+                        command.push("-r".to_string());
+                    }
+                    if args.switch("force", 2) {
+                        command.push("-f".to_string());
+                    }
+                    command
+                }
+            };
+            command.push("--".to_string());
+            command.extend(words(args.values(PATH_PARAMS)));
+            command.extend(words(args.positional));
+            command
+        }
+        Action::Kill => {
+            let args = Arguments::bind(args, &["name", "processname", "id", "inputobject"]);
+            let names = args.values(&["name", "processname"]);
+            if names.is_empty() {
+                let mut command = vec!["kill".to_string(), "--".to_string()];
+                command.extend(words(args.values(&["id"])));
+                command.extend(words(args.positional));
+                command
+            } else {
+                let mut command = vec!["pkill".to_string(), "-x".to_string(), "--".to_string()];
+                command.extend(words(names));
+                command
+            }
+        }
+        Action::ChangeDirectory => {
+            let args = Arguments::bind(args, PATH_PARAMS);
+            let mut command = vec!["cd".to_string()];
+            command.extend(words(args.values(PATH_PARAMS)));
+            command.extend(words(args.positional));
+            command.truncate(2);
+            command
+        }
+        Action::Evaluate => {
+            let args = Arguments::bind(args, &["command"]);
+            let code = args
+                .values(&["command"])
+                .into_iter()
+                .chain(args.positional)
+                .next();
+            match code {
+                Some(token) => match token.literal() {
+                    Some(code) => vec![
+                        "powershell".to_string(),
+                        "-Command".to_string(),
+                        shell_quote(code),
+                    ],
+                    None => vec![
+                        "eval".to_string(),
+                        powershell_word(token).unwrap_or_default(),
+                    ],
+                },
+                // Code read from the pipeline is only known at runtime
+                None => vec!["eval".to_string(), "\"$(Invoke-Expression)\"".to_string()],
+            }
+        }
+        Action::Start => {
+            let args = Arguments::bind(args, START_VALUE_PARAMS);
+            let mut positional = args.positional.iter().copied();
+            let file = args
+                .values(FILE_PARAMS)
+                .into_iter()
+                .next()
+                .or_else(|| positional.next());
+
+            let mut command = Vec::new();
+            // Elevated processes run like `sudo`
+            let elevated = args.values(&["verb"]).iter().any(|verb| {
+                verb.literal()
+                    .is_some_and(|v| v.eq_ignore_ascii_case("runas"))
+            });
+            if elevated {
+                command.push("sudo".to_string());
+            }
+            command.push(
+                file.and_then(powershell_word)
+                    .unwrap_or_else(|| "\"$(Start-Process)\"".to_string()),
+            );
+            for token in args.values(ARGUMENT_PARAMS).into_iter().chain(positional) {
+                match token.literal() {
+                    Some(arguments) => command.extend(
+                        split_windows_arguments(arguments)
+                            .iter()
+                            .map(|a| shell_quote(a)),
+                    ),
+                    None => command.extend(powershell_word(token)),
+                }
+            }
+            command
+        }
+    }
+}
+
+/// Split a Windows command line into arguments at whitespace outside double quotes.
+fn split_windows_arguments(line: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_argument = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_argument = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    has_argument = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_argument = true;
+            }
+        }
+    }
+    if has_argument {
+        arguments.push(current);
+    }
+    arguments
+}
+
+/// Translate a cmd.exe statement into a shell command line.
+fn cmd_statement(tokens: &[Token]) -> String {
+    let stages: Vec<String> = tokens
+        .split(|token| *token == Token::Pipe)
+        .filter_map(cmd_stage)
+        .collect();
+    stages.join(" | ")
+}
+
+/// Translate one command of a cmd.exe pipeline.
+fn cmd_stage(mut tokens: &[Token]) -> Option<String> {
+    // Skip the control flow around the command (`if exist x del x`, `for ... do del %i`)
+    while let Some(keyword) = tokens.first()?.bare_word().map(str::to_ascii_lowercase) {
+        tokens = match keyword.as_str() {
+            "do" | "else" | "call" => &tokens[1..],
+            "rem" => return None,
+            _ if keyword.starts_with("::") || keyword.starts_with(':') => return None,
+            "for" => {
+                let body = tokens.iter().position(
+                    |token| matches!(token.bare_word(), Some(w) if w.eq_ignore_ascii_case("do")),
+                )?;
+                &tokens[body + 1..]
+            }
+            "if" => {
+                let mut rest = &tokens[1..];
+                while let Some(flag) = rest.first().and_then(Token::bare_word) {
+                    if flag.eq_ignore_ascii_case("/i") || flag.eq_ignore_ascii_case("not") {
+                        rest = &rest[1..];
+                    } else {
+                        break;
+                    }
+                }
+                let condition = match rest.first().and_then(Token::bare_word) {
+                    Some(word)
+                        if ["exist", "defined", "errorlevel", "cmdextversion"]
+                            .iter()
+                            .any(|c| word.eq_ignore_ascii_case(c)) =>
+                    {
+                        2
+                    }
+                    _ => match rest.first() {
+                        Some(Token::Word { text, .. }) if text.contains("==") => 1,
+                        _ => 3,
+                    },
+                };
+                rest.get(condition..)?
+            }
+            "start" => {
+                let mut rest = &tokens[1..];
+                // Window title
+                if matches!(rest.first(), Some(Token::Word { quoted: true, .. })) {
+                    rest = &rest[1..];
+                }
+                while let Some(flag) = rest.first().and_then(Token::bare_word) {
+                    if !flag.starts_with('/') {
+                        break;
+                    }
+                    let skip = if flag.eq_ignore_ascii_case("/d") {
+                        2
+                    } else {
+                        1
+                    };
+                    rest = rest.get(skip..)?;
+                }
+                rest
+            }
+            _ => break,
+        };
+    }
+
+    let (tokens, redirects) = split_redirects(tokens, |token| match token {
+        Token::Word { text, .. } => Some(cmd_word(text)),
+        _ => None,
+    });
+    let (name, args) = tokens.split_first()?;
+    let Token::Word { text: name, .. } = name else {
+        return None;
+    };
+    let args: Vec<&str> = args
+        .iter()
+        .filter_map(|token| match token {
+            Token::Word { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let lower = name.to_ascii_lowercase();
+    let action = CMD_COMMANDS
+        .iter()
+        .find(|(command, _)| *command == lower)
+        .map(|(_, action)| *action);
+    // Switches may be combined (`/s/q`)
+    let switch = |letters: &[char]| {
+        args.iter()
+            .filter(|arg| arg.starts_with('/'))
+            .flat_map(|arg| arg.split('/'))
+            .any(|flag| {
+                let mut chars = flag.chars();
+                matches!((chars.next(), chars.next()), (Some(c), None) if letters.contains(&c.to_ascii_lowercase()))
+            })
+    };
+    let operands = args
+        .iter()
+        .filter(|arg| !arg.starts_with('/'))
+        .map(|arg| cmd_word(arg));
+
+    let mut words: Vec<String> = match action {
+        Some(Action::Delete) => {
+            let mut command = vec!["rm".to_string()];
+            if switch(&['s']) {
+                command.push("-r".to_string());
+            }
+            if switch(&['q', 'f']) {
+                command.push("-f".to_string());
+            }
+            command.push("--".to_string());
+            command
+        }
+        Some(Action::RemoveDirectory) if switch(&['s']) => {
+            let mut command = vec!["rm".to_string(), "-r".to_string()];
+            if switch(&['q']) {
+                command.push("-f".to_string());
+            }
+            command.push("--".to_string());
+            command
+        }
+        Some(Action::RemoveDirectory) => vec!["rmdir".to_string(), "--".to_string()],
+        Some(_) => vec!["cd".to_string()],
+        None => {
+            let mut command = vec![cmd_word(name)];
+            command.extend(args.iter().map(|arg| cmd_word(arg)));
+            return Some(command_line(command, redirects));
+        }
+    };
+    words.extend(operands);
+    Some(command_line(words, redirects))
+}
+
+/// Render a cmd.exe word as a shell word.
+///
+/// Variables (`%NAME%`, `!NAME!`, `%1`, `%%i`) become shell variables so that
+/// values only known at runtime stay unresolvable.
+fn cmd_word(text: &str) -> String {
+    let mut word = String::new();
+    let mut has_variable = false;
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['%', '!']) {
+        let delimiter = rest[start..].chars().next().unwrap_or('%');
+        let after = &rest[start + 1..];
+        let (name, consumed) = match after.find(delimiter) {
+            Some(end)
+                if end > 0
+                    && after[..end]
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                (&after[..end], end + 1)
+            }
+            // Batch parameters and `for` variables (`%1`, `%~dp0`, `%%i`)
+            _ if delimiter == '%' => {
+                let prefix = after.len() - after.trim_start_matches(['%', '~']).len();
+                let name: &str = &after[prefix..];
+                let len = name
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(name.len())
+                    .min(1);
+                (&name[..len], prefix + len)
+            }
+            _ => ("", 0),
+        };
+        if name.is_empty() {
+            word.push_str(&escape_double_quoted(&rest[..start + 1]));
+            rest = after;
+            continue;
+        }
+        has_variable = true;
+        word.push_str(&escape_double_quoted(&rest[..start]));
+        word.push_str(&format!("${{{}}}", name));
+        rest = &after[consumed..];
+    }
+
+    if !has_variable {
+        return shell_quote(text);
+    }
+    word.push_str(&escape_double_quoted(rest));
+    format!("\"{}\"", word)
+}
+
+/// Escape text for use inside a double-quoted shell word.
+fn escape_double_quoted(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '"' | '$' | '`' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn powershell(code: &str) -> Vec<String> {
+        translate_lines(ShellDialect::PowerShell, code)
+    }
+
+    fn cmd(code: &str) -> Vec<String> {
+        translate_lines(ShellDialect::Cmd, code)
+    }
+
+    #[test]
+    fn test_translate_powershell_cmdlets() {
+        assert_eq!(
+            powershell("Remove-Item -Recurse -Force C:\\build"),
+            vec!["rm -r -f -- 'C:\\build'"]
+        );
+        assert_eq!(
+            powershell("ri -Path 'a b','c' -r"),
+            vec!["rm -r -- 'a b' 'c'"]
+        );
+        assert_eq!(powershell("rm x; del y"), vec!["rm -- 'x'", "rm -- 'y'"]);
+        assert_eq!(
+            powershell("Stop-Process -Name node"),
+            vec!["pkill -x -- 'node'"]
+        );
+        assert_eq!(powershell("spps 1234 -Force"), vec!["kill -- '1234'"]);
+        assert_eq!(
+            powershell("clc app.log"),
+            vec!["truncate -s 0 -- 'app.log'"]
+        );
+        assert_eq!(
+            powershell("Get-ChildItem *.tmp | Remove-Item"),
+            vec!["'Get-ChildItem' '*.tmp' | rm --"]
+        );
+        assert_eq!(
+            powershell("Set-Content -Path x.txt -Value 1 > $null"),
+            vec!["'Set-Content' '-Path' 'x.txt' '-Value' '1' > /dev/null"]
+        );
+    }
+
+    #[test]
+    fn test_translate_powershell_nested_code() {
+        assert_eq!(
+            powershell("ls | ForEach-Object { Remove-Item $_.FullName }"),
+            vec!["'ls' | 'ForEach-Object'\nrm -- '$_.FullName'"]
+        );
+        assert_eq!(
+            powershell("if (Test-Path x) { rd x -Recurse }"),
+            vec!["'Test-Path' 'x'\nrm -r -- 'x'"]
+        );
+        assert_eq!(
+            powershell("iex 'Remove-Item x'"),
+            vec!["powershell -Command 'Remove-Item x'"]
+        );
+        assert_eq!(powershell("iex $code"), vec!["eval \"$code\""]);
+        assert_eq!(
+            powershell("$x = 'Remove-Item'; & $x foo"),
+            vec!["x='Remove-Item'", "\"$x\" 'foo'"]
+        );
+        assert_eq!(
+            powershell("Write-Output \"$(Stop-Process -Id 1)\""),
+            vec!["'Write-Output' '$(Stop-Process -Id 1)' \"$(kill -- '1')\""]
+        );
+        assert_eq!(
+            powershell("[IO.File]::Delete('C:\\x.txt')"),
+            vec!["rm -- 'C:\\x.txt'"]
+        );
+        assert_eq!(
+            powershell("(Get-Process node).Kill()"),
+            vec!["kill\n'Get-Process' 'node'"]
+        );
+        assert_eq!(
+            powershell("Start-Process cmd -ArgumentList '/c del x' -Verb RunAs"),
+            vec!["sudo 'cmd' '/c' 'del' 'x'"]
+        );
+    }
+
+    #[test]
+    fn test_powershell_strings_and_comments_are_not_commands() {
+        assert!(powershell("'Remove-Item x'").is_empty());
+        assert!(powershell("# Remove-Item x").is_empty());
+        assert!(powershell("<# Remove-Item x #>").is_empty());
+        assert_eq!(
+            powershell("Write-Host \"rm -rf x\""),
+            vec!["'Write-Host' 'rm -rf x'"]
+        );
+    }
+
+    #[test]
+    fn test_translate_cmd_builtins() {
+        assert_eq!(
+            cmd("del /s /q build\\*.obj"),
+            vec!["rm -r -f -- 'build\\*.obj'"]
+        );
+        assert_eq!(
+            cmd("rd /s /q node_modules"),
+            vec!["rm -r -f -- 'node_modules'"]
+        );
+        assert_eq!(cmd("rmdir empty"), vec!["rmdir -- 'empty'"]);
+        assert_eq!(
+            cmd("cd src && erase \"old file.txt\""),
+            vec!["cd 'src'", "rm -- 'old file.txt'"]
+        );
+        assert_eq!(
+            cmd("@echo off & del %TEMP%\\x"),
+            vec!["'echo' 'off'", "rm -- \"${TEMP}\\\\x\""]
+        );
+        assert_eq!(cmd("if exist x.txt del x.txt"), vec!["rm -- 'x.txt'"]);
+        assert_eq!(cmd("for %%f in (*.log) do del %%f"), vec!["rm -- \"${f}\""]);
+        assert_eq!(
+            cmd("start \"\" /b taskkill /im node.exe"),
+            vec!["'taskkill' '/im' 'node.exe'"]
+        );
+        assert_eq!(cmd("dir > nul 2>&1"), vec!["'dir' > /dev/null 2>&1"]);
+        assert_eq!(cmd("%CMD% x"), vec!["\"${CMD}\" 'x'"]);
+        assert!(cmd("rem del x").is_empty());
+    }
+
+    #[test]
+    fn test_shell_equivalent() {
+        let args = |s: &str| crate::domain::parser::parse_shell_tokens(s);
+        assert_eq!(
+            shell_equivalent("powershell", &args("-NoProfile -Command \"Remove-Item x\""))
+                .as_deref(),
+            Some("rm -- 'x'")
+        );
+        assert_eq!(
+            shell_equivalent("pwsh", &args("-ExecutionPolicy Bypass -c spps 1")).as_deref(),
+            Some("kill -- '1'")
+        );
+        assert_eq!(
+            shell_equivalent("cmd", &args("/d /c \"rd /s x\"")).as_deref(),
+            Some("rm -r -- 'x'")
+        );
+        // "ri x" encoded as UTF-16LE base64
+        assert_eq!(
+            shell_equivalent("powershell", &args("-enc cgBpACAAeAA=")).as_deref(),
+            Some("rm -- 'x'")
+        );
+        assert!(shell_equivalent("pwsh", &args("script.ps1")).is_none());
+        assert!(shell_equivalent("powershell", &args("-File script.ps1")).is_none());
+        assert!(shell_equivalent("cmd", &args("/q")).is_none());
+        assert!(shell_equivalent("bash", &args("-c ls")).is_none());
+    }
+
+    #[test]
+    fn test_statement_ranges() {
+        let code = "Get-Date; Remove-Item x";
+        let statements = translate(ShellDialect::PowerShell, code);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(&code[statements[1].0.clone()], "Remove-Item x");
+    }
+}
@@ -0,0 +1,460 @@
+//! Registry of wrappers and launchers that run another command.
+//!
+//! `sudo -u app make`, `timeout 10 make`, `pnpm exec eslint` and `flock app.lock make`
+//! run the command given in their arguments. Each wrapper declares the options that
+//! take a value and where the command starts, so the parser can unwrap the command
+//! and the filters check it like any other. The built-in wrappers are the defaults,
+//! extended by the `[wrappers]` config section.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
+
+use serde::Deserialize;
+
+use super::parse_shell_tokens;
+
+/// Built-in wrappers: name, options that take a value, subcommands that run
+/// the command and operands before the command.
+const BUILTIN_WRAPPERS: &[(&str, &[&str], &[&str], usize)] = &[
+    // Privilege escalation
+    (
+        "sudo",
+        &[
+            "-u",
+            "--user",
+            "-g",
+            "--group",
+            "-C",
+            "--close-from",
+            "-D",
+            "--chdir",
+            "-R",
+            "--chroot",
+            "-T",
+            "--command-timeout",
+            "-h",
+            "--host",
+            "-p",
+            "--prompt",
+            "-r",
+            "--role",
+            "-t",
+            "--type",
+            "-U",
+            "--other-user",
+        ],
+        &[],
+        0,
+    ),
+    ("doas", &["-u", "-C"], &[], 0),
+    ("pkexec", &["--user"], &[], 0),
+    (
+        "run0",
+        &["-u", "--user", "-g", "--group", "-D", "--chdir", "--setenv"],
+        &[],
+        0,
+    ),
+    // Environment, scheduling and tracing
+    ("env", &["-u", "--unset", "-C", "--chdir"], &[], 0),
+    ("nohup", &[], &[], 0),
+    ("setsid", &[], &[], 0),
+    (
+        "stdbuf",
+        &["-i", "--input", "-o", "--output", "-e", "--error"],
+        &[],
+        0,
+    ),
+    ("nice", &["-n", "--adjustment"], &[], 0),
+    ("ionice", &["-c", "--class", "-n", "--classdata"], &[], 0),
+    // `time -p` reports in POSIX format; only GNU time's -o and -f take values
+    ("time", &["-o", "--output", "-f", "--format"], &[], 0),
+    ("timeout", &["-k", "--kill-after", "-s", "--signal"], &[], 1),
+    (
+        "strace",
+        &[
+            "-a", "-b", "-e", "-E", "-o", "-O", "-p", "-P", "-s", "-S", "-u", "-X",
+        ],
+        &[],
+        0,
+    ),
+    (
+        "ltrace",
+        &["-a", "-e", "-n", "-o", "-p", "-s", "-u", "-x"],
+        &[],
+        0,
+    ),
+    ("watch", &["-n", "--interval", "-q", "--equexit"], &[], 0),
+    (
+        "parallel",
+        &[
+            "-j",
+            "--jobs",
+            "-S",
+            "--sshlogin",
+            "-a",
+            "--arg-file",
+            "-d",
+            "--delimiter",
+            "-I",
+            "--colsep",
+            "--joblog",
+            "--delay",
+            "--timeout",
+            "--results",
+            "--tmpdir",
+        ],
+        &[],
+        0,
+    ),
+    (
+        "flock",
+        &["-w", "--timeout", "-E", "--conflict-exit-code"],
+        &[],
+        1,
+    ),
+    ("chroot", &["--userspec", "--groups"], &[], 1),
+    (
+        "xvfb-run",
+        &[
+            "-n",
+            "--server-num",
+            "-s",
+            "--server-args",
+            "-f",
+            "--auth-file",
+            "-e",
+            "--error-file",
+            "-p",
+            "--xauth-protocol",
+        ],
+        &[],
+        0,
+    ),
+    // Shell builtins
+    ("command", &[], &[], 0),
+    ("builtin", &[], &[], 0),
+    ("exec", &["-a"], &[], 0),
+    // Package runners
+    ("npx", &["-p", "--package"], &[], 0),
+    (
+        "pnpm",
+        &["-C", "--dir", "-F", "--filter"],
+        &["exec", "dlx"],
+        0,
+    ),
+    (
+        "uv",
+        &[
+            "-p",
+            "--python",
+            "--with",
+            "--with-requirements",
+            "--directory",
+            "--project",
+            "--package",
+            "--env-file",
+            "--extra",
+            "--group",
+            "--index",
+        ],
+        &["run"],
+        0,
+    ),
+    (
+        "uvx",
+        &["-p", "--python", "--with", "--from", "--index"],
+        &[],
+        0,
+    ),
+    (
+        "poetry",
+        &["-C", "--directory", "-P", "--project"],
+        &["run"],
+        0,
+    ),
+    ("bundle", &["--gemfile"], &["exec"], 0),
+];
+
+/// How a wrapper's arguments lead to the command it runs.
+///
+/// # Examples
+///
+/// ```toml
+/// [wrappers.with-env]
+/// flags_with_values = ["-p", "--profile"]
+///
+/// [wrappers.mise]
+/// flags_with_values = ["-C", "--cd"]
+/// subcommands = ["exec", "x"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Wrapper {
+    /// Options that take a value in the next argument (`-u` in `sudo -u app make`)
+    pub flags_with_values: Vec<String>,
+
+    /// Subcommands that run the command (`exec` in `pnpm exec eslint`).
+    /// When set, the wrapper runs no command with other subcommands
+    pub subcommands: Vec<String>,
+
+    /// Operands between the options and the command (the duration in `timeout 10 make`)
+    pub operands_before_command: usize,
+}
+
+impl Wrapper {
+    /// Add the options and subcommands of a configured entry for the same wrapper,
+    /// taking its operand count if it sets one.
+    fn merge(&mut self, configured: &Wrapper) {
+        for flag in &configured.flags_with_values {
+            if !self.flags_with_values.contains(flag) {
+                self.flags_with_values.push(flag.clone());
+            }
+        }
+        for subcommand in &configured.subcommands {
+            if !self.subcommands.contains(subcommand) {
+                self.subcommands.push(subcommand.clone());
+            }
+        }
+        if configured.operands_before_command > 0 {
+            self.operands_before_command = configured.operands_before_command;
+        }
+    }
+
+    /// Find the index of the command run with the wrapper's arguments,
+    /// skipping its options, subcommand, operands and variable assignments.
+    fn command_index(&self, args: &[String]) -> Option<usize> {
+        let mut subcommand = self.subcommands.is_empty();
+        let mut operands = self.operands_before_command;
+        let mut options = true;
+        let mut skip_next = false;
+        for (i, arg) in args.iter().enumerate() {
+            if skip_next {
+                skip_next = false;
+                continue;
+            }
+            if options && arg == "--" {
+                options = false;
+                continue;
+            }
+            if options && arg.starts_with('-') {
+                skip_next = self.flags_with_values.contains(arg);
+                continue;
+            }
+            if arg.contains('=') {
+                continue;
+            }
+            if !subcommand {
+                if !self.subcommands.contains(arg) {
+                    return None;
+                }
+                subcommand = true;
+                options = true;
+                continue;
+            }
+            if operands > 0 {
+                operands -= 1;
+                continue;
+            }
+            return Some(i);
+        }
+        None
+    }
+}
+
+/// Wrappers by command name: the built-ins and those configured in `[wrappers]`.
+#[derive(Debug, Clone)]
+pub struct WrapperRegistry {
+    wrappers: HashMap<String, Wrapper>,
+}
+
+impl Default for WrapperRegistry {
+    /// The built-in wrappers.
+    fn default() -> Self {
+        let wrappers = BUILTIN_WRAPPERS
+            .iter()
+            .map(|&(name, flags, subcommands, operands)| {
+                let wrapper = Wrapper {
+                    flags_with_values: flags.iter().map(|flag| flag.to_string()).collect(),
+                    subcommands: subcommands.iter().map(|sub| sub.to_string()).collect(),
+                    operands_before_command: operands,
+                };
+                (name.to_string(), wrapper)
+            })
+            .collect();
+        Self { wrappers }
+    }
+}
+
+impl WrapperRegistry {
+    /// Get the shared registry of the built-in wrappers.
+    pub fn builtin() -> Arc<Self> {
+        static BUILTIN: OnceLock<Arc<WrapperRegistry>> = OnceLock::new();
+        Arc::clone(BUILTIN.get_or_init(|| Arc::new(Self::default())))
+    }
+
+    /// Add configured wrappers. A wrapper that is already registered keeps its
+    /// options and subcommands and gains the configured ones, so `[wrappers.sudo]`
+    /// with one extra flag still skips the value of `sudo -u`.
+    pub fn with_wrappers(mut self, wrappers: &BTreeMap<String, Wrapper>) -> Self {
+        for (name, wrapper) in wrappers {
+            match self.wrappers.get_mut(name) {
+                Some(existing) => existing.merge(wrapper),
+                None => {
+                    self.wrappers.insert(name.clone(), wrapper.clone());
+                }
+            }
+        }
+        self
+    }
+
+    /// Check if a command is a wrapper.
+    pub fn contains(&self, name: &str) -> bool {
+        self.wrappers.contains_key(name)
+    }
+
+    /// Split a wrapper's arguments into its own arguments and the command it runs
+    /// (`sudo -u app make -j4` gives `-u app` and `make -j4`).
+    ///
+    /// The string of `env -S 'rm -rf x'` is split into the words it runs.
+    pub fn split_command(&self, name: &str, args: &[String]) -> Option<(Vec<String>, Vec<String>)> {
+        let mut args = match name {
+            "env" => self.split_env_strings(args),
+            _ => args.to_vec(),
+        };
+        let i = self.command_index(name, &args)?;
+        let command = args.split_off(i);
+        Some((args, command))
+    }
+
+    /// Replace the strings of `env -S`/`--split-string` with the words they split into.
+    fn split_env_strings(&self, args: &[String]) -> Vec<String> {
+        let flags = self
+            .wrappers
+            .get("env")
+            .map(|env| env.flags_with_values.as_slice())
+            .unwrap_or_default();
+        let mut expanded = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let split = match arg.as_str() {
+                "-S" | "--split-string" => iter.next().map(String::as_str),
+                _ => arg
+                    .strip_prefix("--split-string=")
+                    .or_else(|| arg.strip_prefix("-S")),
+            };
+            if let Some(split) = split {
+                expanded.extend(parse_shell_tokens(split));
+                continue;
+            }
+            expanded.push(arg.clone());
+            if flags.contains(arg) {
+                expanded.extend(iter.next().cloned());
+            } else if !arg.starts_with('-') && !arg.contains('=') {
+                // The command and its own arguments
+                expanded.extend(iter.cloned());
+                break;
+            }
+        }
+        expanded
+    }
+
+    /// Find the index of the command a wrapper runs, skipping the wrapper's
+    /// options, subcommand, operands and variable assignments.
+    fn command_index(&self, name: &str, args: &[String]) -> Option<usize> {
+        // `command -v`/`command -V` only look the command up without running it
+        if name == "command"
+            && args
+                .iter()
+                .take_while(|arg| arg.starts_with('-'))
+                .any(|arg| arg == "-v" || arg == "-V")
+        {
+            return None;
+        }
+        self.wrappers.get(name)?.command_index(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(registry: &WrapperRegistry, line: &str) -> Option<String> {
+        let words = parse_shell_tokens(line);
+        let (name, args) = words.split_first()?;
+        let (_, command) = registry.split_command(name, args)?;
+        Some(command.join(" "))
+    }
+
+    #[test]
+    fn test_builtin_wrappers() {
+        let registry = WrapperRegistry::default();
+        let cases = [
+            ("sudo -u app make install", Some("make install")),
+            ("env -u HOME FOO=1 make", Some("make")),
+            ("env -S 'rm -rf x'", Some("rm -rf x")),
+            ("env -u HOME --split-string='FOO=1 rm x' y", Some("rm x y")),
+            ("setsid -f rm -rf x", Some("rm -rf x")),
+            ("stdbuf -o0 rm -rf x", Some("rm -rf x")),
+            ("stdbuf -o L rm -rf x", Some("rm -rf x")),
+            ("nice -n 10 make", Some("make")),
+            ("time -p make", Some("make")),
+            ("timeout -s KILL 10 rm -rf x", Some("rm -rf x")),
+            ("flock -w 5 app.lock rm x", Some("rm x")),
+            ("chroot --userspec app /srv/root rm x", Some("rm x")),
+            ("strace -o trace.log rm x", Some("rm x")),
+            ("sudo -- rm x", Some("rm x")),
+            ("npx -p rimraf rimraf dist", Some("rimraf dist")),
+            ("pnpm --filter web exec rm -rf dist", Some("rm -rf dist")),
+            ("uv run --with ruff ruff check", Some("ruff check")),
+            ("poetry run pytest", Some("pytest")),
+            ("bundle exec rake db:drop", Some("rake db:drop")),
+            ("watch -n 1 ls", Some("ls")),
+            ("parallel -j 4 rm ::: a b", Some("rm ::: a b")),
+            ("xvfb-run -a npm test", Some("npm test")),
+            // Subcommands that do not run a command
+            ("pnpm install", None),
+            ("uv pip install ruff", None),
+            ("command -v rm", None),
+            ("timeout 10", None),
+            ("make install", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(command(&registry, line).as_deref(), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_configured_wrappers() {
+        let toml = r#"
+            [with-env]
+            flags_with_values = ["-p", "--profile"]
+
+            [sudo]
+            flags_with_values = ["--askpass-prompt"]
+
+            [timeout]
+        "#;
+        let wrappers: BTreeMap<String, Wrapper> = toml::from_str(toml).unwrap();
+        let registry = WrapperRegistry::default().with_wrappers(&wrappers);
+
+        assert!(registry.contains("with-env"));
+        assert_eq!(
+            command(&registry, "with-env -p staging rm -rf x").as_deref(),
+            Some("rm -rf x")
+        );
+        // Configured entries extend the built-ins of the same name
+        assert_eq!(
+            command(&registry, "sudo --askpass-prompt x rm -rf /").as_deref(),
+            Some("rm -rf /")
+        );
+        assert_eq!(
+            command(&registry, "sudo -u root rm -rf /").as_deref(),
+            Some("rm -rf /")
+        );
+        assert_eq!(
+            command(&registry, "timeout 10 make").as_deref(),
+            Some("make")
+        );
+        assert!(!WrapperRegistry::default().contains("with-env"));
+    }
+}
@@ -17,6 +17,10 @@ pub struct HookInput {
     /// Optional session identifier
     #[serde(default)]
     pub session_id: Option<String>,
+
+    /// Working directory of the agent (used to locate the project root)
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 /// Tool-specific input variants.
@@ -79,8 +83,9 @@ pub struct StopInput {
 /// Hook output sent back to AI agent.
 #[derive(Debug, Clone, Serialize)]
 pub struct HookOutput {
-    /// Decision: "approve" or "block"
-    pub decision: String,
+    /// Decision: "approve" or "block" (omitted when asking the user)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
 
     /// Optional message (usually present when blocking)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -91,7 +96,7 @@ pub struct HookOutput {
     pub hook_specific_output: Option<HookSpecificOutput>,
 }
 
-/// Hook-specific output for Claude Code PostToolUse.
+/// Hook-specific output for Claude Code (PostToolUse context, PreToolUse permission).
 #[derive(Debug, Clone, Serialize)]
 pub struct HookSpecificOutput {
     /// Hook event name
@@ -101,6 +106,17 @@ pub struct HookSpecificOutput {
     /// Additional context for the agent (e.g., lint warnings)
     #[serde(rename = "additionalContext", skip_serializing_if = "Option::is_none")]
     pub additional_context: Option<String>,
+
+    /// PreToolUse permission decision: "allow", "deny" or "ask"
+    #[serde(rename = "permissionDecision", skip_serializing_if = "Option::is_none")]
+    pub permission_decision: Option<String>,
+
+    /// Reason for the permission decision, shown to the user
+    #[serde(
+        rename = "permissionDecisionReason",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub permission_decision_reason: Option<String>,
 }
 
 /// Processing decision with optional block message.
@@ -113,6 +129,8 @@ pub enum Decision {
     },
     /// Block the operation with a message
     Block { message: String },
+    /// Ask the user to confirm the operation, with a message explaining why
+    Ask { message: String },
 }
 
 impl Default for Decision {
@@ -146,22 +164,34 @@ impl Decision {
                     additional_context.map(|ctx| HookSpecificOutput {
                         hook_event_name: "PostToolUse".to_string(),
                         additional_context: Some(ctx),
+                        permission_decision: None,
+                        permission_decision_reason: None,
                     })
                 } else {
                     None
                 };
 
                 HookOutput {
-                    decision: "approve".to_string(),
+                    decision: Some("approve".to_string()),
                     message: None,
                     hook_specific_output,
                 }
             }
             Decision::Block { message } => HookOutput {
-                decision: "block".to_string(),
+                decision: Some("block".to_string()),
                 message: Some(message),
                 hook_specific_output: None,
             },
+            Decision::Ask { message } => HookOutput {
+                decision: None,
+                message: None,
+                hook_specific_output: Some(HookSpecificOutput {
+                    hook_event_name: event.to_string(),
+                    additional_context: None,
+                    permission_decision: Some("ask".to_string()),
+                    permission_decision_reason: Some(message),
+                }),
+            },
         }
     }
 
@@ -169,9 +199,10 @@ impl Decision {
     ///
     /// - Allow: 0
     /// - Block: 2
+    /// - Ask: 0 (the agent reads the permission decision from the output)
     pub fn exit_code(&self) -> i32 {
         match self {
-            Decision::Allow { .. } => 0,
+            Decision::Allow { .. } | Decision::Ask { .. } => 0,
             Decision::Block { .. } => 2,
         }
     }
@@ -192,7 +223,42 @@ impl Decision {
                     additional_context: merged,
                 }
             }
-            Decision::Block { message } => Decision::Block { message },
+            Decision::Block { .. } | Decision::Ask { .. } => self,
+        }
+    }
+}
+
+/// Decision a filter makes when its condition matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Block the operation
+    #[default]
+    Block,
+    /// Ask the user to confirm the operation
+    Ask,
+    /// Let the operation run
+    Allow,
+}
+
+impl FilterAction {
+    /// Create the decision for this action with a message.
+    pub fn decision(self, message: String) -> Decision {
+        match self {
+            FilterAction::Block => Decision::Block { message },
+            FilterAction::Ask => Decision::Ask { message },
+            FilterAction::Allow => Decision::Allow {
+                additional_context: None,
+            },
+        }
+    }
+
+    /// Rank of the action from most to least restrictive (block is 0).
+    pub fn strictness(self) -> u8 {
+        match self {
+            FilterAction::Block => 0,
+            FilterAction::Ask => 1,
+            FilterAction::Allow => 2,
         }
     }
 }
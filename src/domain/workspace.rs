@@ -0,0 +1,130 @@
+//! Project workspace detection and path containment checks.
+//!
+//! Used by filters that allow operations only inside the project root.
+
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// Characters that make a path operand impossible to resolve statically.
+const DYNAMIC_PATH_CHARS: &[char] = &['$', '`', '*', '?', '[', '{'];
+
+/// Project workspace rooted at the enclosing git repository.
+pub struct Workspace {
+    /// Canonical path of the repository root
+    root: PathBuf,
+    /// Canonical working directory used to resolve relative paths
+    cwd: PathBuf,
+}
+
+impl Workspace {
+    /// Detect the workspace containing `cwd` (or the process working directory).
+    ///
+    /// Returns `None` if the directory is not inside a git repository.
+    pub fn detect(cwd: Option<&str>) -> Option<Self> {
+        let cwd = match cwd {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => env::current_dir().ok()?,
+        };
+        let cwd = fs::canonicalize(cwd).ok()?;
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&cwd)
+            .args(["rev-parse", "--show-toplevel"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let root = fs::canonicalize(root).ok()?;
+        Some(Self { root, cwd })
+    }
+
+    /// Resolve a path operand to an absolute path.
+    ///
+    /// Returns `None` for operands that depend on runtime expansion
+    /// (variables, globs, `~`) or that escape the filesystem root.
+    /// Symlinks in parent directories are resolved before any `..` after them
+    /// is applied, as the kernel does (`link/../x` is `x` next to the link target);
+    /// the final component is not, because deleting a symlink does not touch its target.
+    pub fn resolve(&self, operand: &str) -> Option<PathBuf> {
+        if operand.is_empty() || operand.starts_with('~') || operand.contains(DYNAMIC_PATH_CHARS) {
+            return None;
+        }
+
+        let joined = self.cwd.join(operand);
+        let components: Vec<Component> = joined.components().collect();
+        let mut resolved = PathBuf::new();
+        for (i, component) in components.iter().enumerate() {
+            match component {
+                Component::ParentDir => {
+                    if !resolved.pop() {
+                        return None;
+                    }
+                }
+                Component::CurDir => {}
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    // Prefixes that do not exist yet are kept as they are
+                    if i + 1 < components.len() {
+                        if let Ok(canonical) = fs::canonicalize(&resolved) {
+                            resolved = canonical;
+                        }
+                    }
+                }
+                other => resolved.push(other),
+            }
+        }
+        Some(resolved)
+    }
+
+    /// Canonical path of the repository root.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Check if a resolved path is strictly inside the workspace.
+    ///
+    /// The root itself and anything inside a `.git` directory are never considered inside.
+    pub fn contains(&self, path: &Path) -> bool {
+        if path == self.root || !path.starts_with(&self.root) {
+            return false;
+        }
+        !path
+            .components()
+            .any(|c| matches!(c, Component::Normal(name) if name == ".git"))
+    }
+
+    /// Check if any of the paths (or any file beneath them) is tracked by git.
+    ///
+    /// SECURITY: Returns true if git cannot be queried (fail-closed).
+    pub fn has_tracked_files(&self, paths: &[PathBuf]) -> bool {
+        let relative: Vec<&Path> = paths
+            .iter()
+            .filter_map(|p| p.strip_prefix(&self.root).ok())
+            .collect();
+        if relative.len() != paths.len() {
+            return true;
+        }
+        if relative.is_empty() {
+            return false;
+        }
+
+        let output = Command::new("git")
+            .arg("--literal-pathspecs")
+            .arg("-C")
+            .arg(&self.root)
+            .args(["ls-files", "-z", "--cached", "--"])
+            .args(&relative)
+            .output();
+
+        match output {
+            Ok(output) if output.status.success() => !output.stdout.is_empty(),
+            _ => true,
+        }
+    }
+}
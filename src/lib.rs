@@ -0,0 +1,8 @@
+//! claw-hooks: AI coding agent hook system
+//!
+//! Library crate behind the `claw-hooks` binary, also used by the benchmarks.
+
+pub mod cli;
+pub mod config;
+pub mod domain;
+pub mod service;
@@ -3,17 +3,13 @@
 //! A CLI tool that integrates with AI coding agents (Claude Code, Cursor, Windsurf)
 //! to filter dangerous commands, suggest safer alternatives, and execute extension-based hooks.
 
-mod cli;
-mod config;
-mod domain;
-mod service;
-
 use anyhow::Result;
 use clap::Parser;
 
-use cli::{Cli, Commands};
-use config::ConfigService;
-use service::HookService;
+use claw_hooks::cli::{Cli, Commands};
+use claw_hooks::config::{self, ConfigService};
+use claw_hooks::domain;
+use claw_hooks::service::HookService;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
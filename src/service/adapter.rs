@@ -44,8 +44,12 @@ impl FormatAdapter {
 
     /// Get the exit code for the decision.
     /// Note: Cursor uses different semantics but still uses exit codes.
+    /// Windsurf cannot ask the user, so Ask decisions block there.
     pub fn exit_code(&self, decision: &Decision) -> i32 {
-        decision.exit_code()
+        match (self.format, decision) {
+            (Format::Windsurf, Decision::Ask { .. }) => 2,
+            _ => decision.exit_code(),
+        }
     }
 
     /// Format an error message for output.
@@ -123,6 +127,7 @@ impl FormatAdapter {
             tool_name,
             tool_input,
             session_id: claude_input.session_id,
+            cwd: claude_input.cwd,
         })
     }
 
@@ -161,6 +166,7 @@ impl FormatAdapter {
                         response: None,
                     }),
                     session_id: None,
+                    cwd: None,
                 })
             }
             CursorInput::ShellExecution { command, cwd } => {
@@ -183,6 +189,7 @@ impl FormatAdapter {
                         timeout: None,
                     }),
                     session_id: None,
+                    cwd,
                 })
             }
             CursorInput::FileEdit { file_path } => {
@@ -204,6 +211,7 @@ impl FormatAdapter {
                         content: None,
                     }),
                     session_id: None,
+                    cwd: None,
                 })
             }
         }
@@ -221,6 +229,11 @@ impl FormatAdapter {
                 user_message: Some(message.clone()),
                 agent_message: Some("Command blocked by claw-hooks".to_string()),
             },
+            Decision::Ask { message } => CursorOutput {
+                permission: "ask".to_string(),
+                user_message: Some(message.clone()),
+                agent_message: Some("Command requires confirmation by claw-hooks".to_string()),
+            },
         };
         serde_json::to_string(&output)
             .map_err(|e| anyhow!("Failed to serialize Cursor output: {}", e))
@@ -300,25 +313,29 @@ impl FormatAdapter {
             "Parsed Windsurf input"
         );
 
+        let cwd = windsurf_input.tool_info.and_then(|ti| ti.cwd);
+
         Ok(HookInput {
             event,
             tool_name,
             tool_input,
             session_id: None,
+            cwd,
         })
     }
 
     fn format_windsurf_output(&self, decision: &Decision, _event: &str) -> Result<String> {
         // Windsurf uses the same output format as Claude Code (but without hookSpecificOutput)
         // Since Windsurf doesn't support additionalContext, we use a simplified output
+        // Windsurf cannot ask the user, so Ask decisions block (fail-closed)
         let output = match decision {
             Decision::Allow { .. } => crate::domain::HookOutput {
-                decision: "approve".to_string(),
+                decision: Some("approve".to_string()),
                 message: None,
                 hook_specific_output: None,
             },
-            Decision::Block { message } => crate::domain::HookOutput {
-                decision: "block".to_string(),
+            Decision::Block { message } | Decision::Ask { message } => crate::domain::HookOutput {
+                decision: Some("block".to_string()),
                 message: Some(message.clone()),
                 hook_specific_output: None,
             },
@@ -348,6 +365,10 @@ struct ClaudeInput {
     #[serde(default)]
     session_id: Option<String>,
 
+    /// Current working directory
+    #[serde(default)]
+    cwd: Option<String>,
+
     /// Whether stop hooks are active in this session
     #[serde(default)]
     #[allow(dead_code)]
@@ -374,7 +395,6 @@ enum CursorInput {
         command: String,
         /// Current working directory
         #[serde(default)]
-        #[allow(dead_code)]
         cwd: Option<String>,
     },
     /// afterFileEdit hook - provides edited file path
@@ -418,7 +438,6 @@ struct WindsurfToolInfo {
     command_line: Option<String>,
     /// Current working directory
     #[serde(default)]
-    #[allow(dead_code)]
     cwd: Option<String>,
     /// File path for post_write_code
     #[serde(default)]
@@ -569,6 +588,31 @@ mod tests {
         assert!(output.contains("Command blocked for safety"));
     }
 
+    #[test]
+    fn test_output_ask() {
+        let decision = Decision::Ask {
+            message: "Confirm write to /etc/hosts".to_string(),
+        };
+
+        let claude = FormatAdapter::new(Format::Claude);
+        let output = claude.format_output(&decision, "PreToolUse").unwrap();
+        assert!(output.contains(r#""permissionDecision":"ask""#));
+        assert!(output.contains("Confirm write to /etc/hosts"));
+        assert!(!output.contains(r#""decision""#));
+        assert_eq!(claude.exit_code(&decision), 0);
+
+        let cursor = FormatAdapter::new(Format::Cursor);
+        let output = cursor.format_output(&decision, "PreToolUse").unwrap();
+        assert!(output.contains(r#""permission":"ask""#));
+        assert_eq!(cursor.exit_code(&decision), 0);
+
+        // Windsurf cannot ask, so the decision blocks
+        let windsurf = FormatAdapter::new(Format::Windsurf);
+        let output = windsurf.format_output(&decision, "PreToolUse").unwrap();
+        assert!(output.contains(r#""decision":"block""#));
+        assert_eq!(windsurf.exit_code(&decision), 2);
+    }
+
     #[test]
     fn test_cursor_input_parsing_stop() {
         let adapter = FormatAdapter::new(Format::Cursor);